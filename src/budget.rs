@@ -0,0 +1,237 @@
+use crate::{
+    App, DisplayRow, SortField, SortOrder, build_path_rows, cost, format_bytes, format_cost,
+    format_count, group_digits,
+    report::{JsonAsset, JsonScope, scope, share, write_aligned},
+    truncate_id, type_label,
+};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Assets listed under each exceeded budget.
+const TOP_CONTRIBUTORS: usize = 5;
+/// Longest ID shown among the contributors.
+const MAX_ID_WIDTH: usize = 60;
+
+/// Limits set with `check`, compared against the requests that pass the load filters. Filters
+/// that only narrow the table, such as `--min-bandwidth`, don't apply, so they can't hide
+/// traffic from a budget.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budgets {
+    pub max_bandwidth: Option<u64>,
+    pub max_requests: Option<u64>,
+}
+
+/// Reads what a budget limits from a row.
+type Measure = fn(&DisplayRow) -> u64;
+/// Formats an amount of what a budget limits.
+type Format = fn(u64) -> String;
+
+/// A budget's name, limit, abbreviated and full formats, whether it has a cost, and what it
+/// measures.
+type Spec = (&'static str, Option<u64>, Format, Format, bool, Measure);
+
+/// One budget and how the logs measured against it.
+struct Outcome<'a> {
+    name: &'static str,
+    limit: u64,
+    actual: u64,
+    /// Abbreviated, as the table shows it.
+    format: Format,
+    /// In full, so an actual just over its limit doesn't read the same as it.
+    exact: Format,
+    /// Whether the amounts have a cost, given a price per GB.
+    priced: bool,
+    /// The assets adding the most to the measure, largest first.
+    contributors: Vec<&'a DisplayRow>,
+    measure: Measure,
+}
+
+impl Outcome<'_> {
+    fn exceeded(&self) -> bool {
+        self.actual > self.limit
+    }
+}
+
+/// Writes each budget with the measured value, and the assets contributing most to any that
+/// are exceeded. Returns whether every budget was met.
+pub fn write_check(mut out: impl Write, app: &App, budgets: Budgets) -> io::Result<bool> {
+    let rows = measured_rows(app);
+    let outcomes = outcomes(&rows, budgets);
+    let requests = rows.iter().map(|row| row.request_count).sum();
+    writeln!(out, "{}\n", scope(app, requests).replace('`', ""))?;
+    let lines = outcomes
+        .iter()
+        .map(|outcome| {
+            let format = if outcome.exceeded() {
+                outcome.exact
+            } else {
+                outcome.format
+            };
+            // Amounts of bandwidth are followed by their cost, such as `2.27 GiB ($2.44)`.
+            let amount = |value| match app.price_per_gb {
+                Some(price) if outcome.priced => {
+                    format!("{} ({})", format(value), format_cost(value, price))
+                }
+                _ => format(value),
+            };
+            let status = if outcome.exceeded() {
+                format!("exceeded by {}", amount(outcome.actual - outcome.limit))
+            } else {
+                "ok".to_string()
+            };
+            vec![
+                outcome.name.to_string(),
+                amount(outcome.actual),
+                format!("of {}", amount(outcome.limit)),
+                status,
+            ]
+        })
+        .collect();
+    write_aligned(
+        &mut out,
+        &[
+            ("Budget", false),
+            ("Actual", true),
+            ("Limit", false),
+            ("Status", false),
+        ],
+        lines,
+    )?;
+
+    for outcome in outcomes.iter().filter(|outcome| outcome.exceeded()) {
+        writeln!(out, "\nTop assets by {}\n", outcome.name.to_lowercase())?;
+        let rows = outcome
+            .contributors
+            .iter()
+            .enumerate()
+            .map(|(rank, row)| {
+                let value = (outcome.measure)(row);
+                vec![
+                    (rank + 1).to_string(),
+                    type_label(row.req_type).to_string(),
+                    truncate_id(&row.label, MAX_ID_WIDTH),
+                    (outcome.format)(value),
+                    share(value, outcome.actual),
+                ]
+            })
+            .collect();
+        write_aligned(
+            &mut out,
+            &[
+                ("#", true),
+                ("Type", false),
+                ("ID", false),
+                (outcome.name, true),
+                ("Share", true),
+            ],
+            rows,
+        )?;
+    }
+    out.flush()?;
+    Ok(!outcomes.iter().any(Outcome::exceeded))
+}
+
+/// Writes the same as `write_check` as JSON, with sizes in bytes and counts in full. Only
+/// exceeded budgets list their top contributors. Returns whether every budget was met.
+pub fn write_check_json(mut out: impl Write, app: &App, budgets: Budgets) -> io::Result<bool> {
+    let rows = measured_rows(app);
+    let outcomes = outcomes(&rows, budgets);
+    let passed = !outcomes.iter().any(Outcome::exceeded);
+    let check = JsonCheck {
+        passed,
+        scope: JsonScope::of(app),
+        budgets: outcomes
+            .iter()
+            .map(|outcome| JsonBudget {
+                name: outcome.name.to_lowercase(),
+                limit: outcome.limit,
+                actual: outcome.actual,
+                cost: app
+                    .price_per_gb
+                    .filter(|_| outcome.priced)
+                    .map(|price| cost(outcome.actual, price)),
+                passed: !outcome.exceeded(),
+                top_contributors: outcome
+                    .contributors
+                    .iter()
+                    .filter(|_| outcome.exceeded())
+                    .map(|row| JsonAsset::of(row))
+                    .collect(),
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut out, &check)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(passed)
+}
+
+#[derive(Serialize)]
+struct JsonCheck<'a> {
+    passed: bool,
+    scope: JsonScope,
+    budgets: Vec<JsonBudget<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonBudget<'a> {
+    name: String,
+    limit: u64,
+    actual: u64,
+    /// What the bandwidth measured cost, given a price per GB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost: Option<f64>,
+    passed: bool,
+    top_contributors: Vec<JsonAsset<'a>>,
+}
+
+/// A row for every asset requested, whatever the table's filters would hide.
+fn measured_rows(app: &App) -> Vec<DisplayRow> {
+    build_path_rows(
+        &app.base_items,
+        &app.trends,
+        None,
+        SortOrder::by(SortField::Bandwidth, true),
+    )
+}
+
+/// The budgets that were set, measured against every asset's row.
+fn outcomes(rows: &[DisplayRow], budgets: Budgets) -> Vec<Outcome<'_>> {
+    let measures: [Spec; 2] = [
+        (
+            "Bandwidth",
+            budgets.max_bandwidth,
+            format_bytes,
+            |bytes| format!("{} B", group_digits(bytes)),
+            true,
+            |row| row.bandwidth_sum,
+        ),
+        (
+            "Requests",
+            budgets.max_requests,
+            format_count,
+            group_digits,
+            false,
+            |row| row.request_count,
+        ),
+    ];
+    measures
+        .into_iter()
+        .filter_map(|(name, limit, format, exact, priced, measure)| {
+            let limit = limit?;
+            let mut contributors: Vec<&DisplayRow> = rows.iter().collect();
+            contributors.sort_by_key(|row| std::cmp::Reverse(measure(row)));
+            contributors.truncate(TOP_CONTRIBUTORS);
+            Some(Outcome {
+                name,
+                limit,
+                actual: rows.iter().map(measure).sum(),
+                format,
+                exact,
+                priced,
+                contributors,
+                measure,
+            })
+        })
+        .collect()
+}
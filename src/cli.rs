@@ -0,0 +1,535 @@
+use crate::{
+    SortField, StartView,
+    config::Config,
+    export::{ExportFormat, RecordFormat},
+    ingest::ParamFilter,
+    record::{InputFormat, JsonFields, Schema},
+    report::ReportFormat,
+    theme::ThemeName,
+    units::{self, ByteUnits},
+};
+use clap::{ArgGroup, Parser, Subcommand};
+use jiff::{Span, Timestamp, Unit, civil::Date, tz::TimeZone};
+use regex::{Regex, RegexBuilder};
+use std::{env, path::PathBuf, time::Duration};
+use url::Url;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "sanity-log-explorer",
+    version,
+    about = "Explore Sanity request logs and spot high-bandwidth assets",
+    subcommand_negates_reqs = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Log files, glob patterns, or s3://, gs://, https:// URLs
+    #[arg(value_name = "LOG", required_unless_present = "project")]
+    pub inputs: Vec<String>,
+
+    /// Experimental: fetch request logs for this Sanity project from an undocumented API endpoint
+    #[arg(long, value_name = "PROJECT_ID")]
+    pub project: Option<String>,
+
+    /// Only include requests with this query parameter: `w`, `auto=format`, or `!auto` for
+    /// without (repeatable)
+    #[arg(long, global = true, value_name = "PARAM", allow_hyphen_values = true)]
+    pub param: Vec<ParamFilter>,
+
+    /// Only include requests for this dataset, read from the URL path (also sent with --project)
+    #[arg(long, global = true)]
+    pub dataset: Option<String>,
+
+    /// Sanity API token used with --project
+    #[arg(long, env = "SANITY_AUTH_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// Abort on the first malformed record instead of skipping it
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Count records sharing a request ID (requestId/id) only once
+    #[arg(long, global = true)]
+    pub dedupe: bool,
+
+    /// Only include requests at or after this time (RFC 3339, a date, or relative like 7d)
+    #[arg(long, global = true, value_name = "TIME", value_parser = parse_time)]
+    pub since: Option<Timestamp>,
+
+    /// Only include requests before this time (RFC 3339, a date, or relative like 24h)
+    #[arg(long, global = true, value_name = "TIME", value_parser = parse_time)]
+    pub until: Option<Timestamp>,
+
+    /// Only include requests whose URL path matches this regular expression (e.g. '\.pdf$')
+    #[arg(long, global = true, value_name = "REGEX")]
+    pub filter_regex: Option<Regex>,
+
+    /// Leave out requests whose URL path matches this regular expression (repeatable)
+    #[arg(long, global = true, value_name = "REGEX")]
+    pub exclude: Vec<Regex>,
+
+    /// Only include requests whose referer matches this regular expression, ignoring case
+    #[arg(long, global = true, value_name = "PATTERN", value_parser = parse_pattern)]
+    pub referer: Option<Regex>,
+
+    /// Only include requests whose user agent matches this regular expression, ignoring case
+    #[arg(long, global = true, value_name = "PATTERN", value_parser = parse_pattern)]
+    pub ua: Option<Regex>,
+
+    /// Leave out requests whose user agent matches this regular expression (repeatable)
+    #[arg(long, global = true, value_name = "PATTERN", value_parser = parse_pattern)]
+    pub exclude_ua: Vec<Regex>,
+
+    /// Leave out requests from crawlers, monitors, and HTTP libraries, by user agent
+    #[arg(long, global = true)]
+    pub no_bots: bool,
+
+    /// Hide table rows that used less bandwidth than this (e.g. 10MB). KB, MB, and GB are
+    /// powers of 1000, KiB, MiB, and GiB powers of 1024
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    pub min_bandwidth: Option<u64>,
+
+    /// Apply a named filter set from the config file
+    #[arg(long, global = true, value_name = "NAME")]
+    pub filter_preset: Option<String>,
+
+    /// Print the By Asset table in this format instead of opening the TUI
+    #[arg(long, global = true, value_name = "FORMAT", value_enum)]
+    pub export: Option<ExportFormat>,
+
+    /// Print totals, the split by type, and the top 10 assets as plain text instead of opening
+    /// the TUI
+    #[arg(long, global = true, conflicts_with = "export")]
+    pub summary: bool,
+
+    /// With `--summary` or `check`, print JSON instead of text, with sizes in bytes
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Column to sort the TUI by at startup
+    #[arg(long, global = true, value_name = "FIELD", value_enum)]
+    pub sort: Option<SortField>,
+
+    /// View to open the TUI on
+    #[arg(long, global = true, value_enum)]
+    pub view: Option<StartView>,
+
+    /// Colors the TUI draws with [default: dark]
+    #[arg(long, global = true, value_enum)]
+    pub theme: Option<ThemeName>,
+
+    /// Draw the TUI without colors, as the monochrome theme does. Setting NO_COLOR does the
+    /// same
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Sanity Studio to open document lookups in, such as https://my-project.sanity.studio
+    #[arg(long, global = true, value_name = "URL", value_parser = Url::parse)]
+    pub studio_url: Option<Url>,
+
+    /// What a GB (10^9 bytes) of bandwidth costs, to show the cost of the traffic beside it
+    #[arg(long, global = true, value_name = "PRICE", value_parser = parse_price)]
+    pub price_per_gb: Option<f64>,
+
+    /// Format of the input logs [default: ndjson]
+    #[arg(long, global = true, value_enum)]
+    pub format: Option<InputFormat>,
+
+    /// Field names used by JSON records [default: sanity]
+    #[arg(long, global = true, value_enum)]
+    pub schema: Option<Schema>,
+
+    /// JSON field holding the request URL, as a dotted path (e.g. body.url)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub url_field: Option<String>,
+
+    /// JSON field holding the request size in bytes
+    #[arg(long, global = true, value_name = "PATH")]
+    pub request_size_field: Option<String>,
+
+    /// JSON field holding the response size in bytes
+    #[arg(long, global = true, value_name = "PATH")]
+    pub response_size_field: Option<String>,
+
+    /// JSON field holding the request timestamp
+    #[arg(long, global = true, value_name = "PATH")]
+    pub timestamp_field: Option<String>,
+
+    /// JSON field holding the time taken to serve the request, in milliseconds
+    #[arg(long, global = true, value_name = "PATH")]
+    pub duration_field: Option<String>,
+
+    /// MaxMind GeoLite2/GeoIP2 Country or City database, to group the By Client view by country
+    #[arg(long, global = true, value_name = "FILE")]
+    pub geoip: Option<PathBuf>,
+
+    /// CSV column holding the request URL (with --format csv) [default: url]
+    #[arg(long, global = true, value_name = "NAME")]
+    pub url_column: Option<String>,
+
+    /// CSV column holding the response size in bytes (with --format csv) [default:
+    /// responseSize]
+    #[arg(long, global = true, value_name = "NAME")]
+    pub size_column: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Compare two sets of logs, showing the change in requests and bandwidth per asset
+    Diff {
+        /// Logs from before the change (a file, glob pattern, or URL)
+        before: String,
+        /// Logs from after the change
+        after: String,
+    },
+    /// Print a report of the logs: totals, top assets, offenders, and recommendations
+    Report {
+        /// Log files, glob patterns, or s3://, gs://, https:// URLs
+        #[arg(value_name = "LOG", required = true)]
+        inputs: Vec<String>,
+        /// Write Markdown, or a self-contained HTML page with charts and sortable tables
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        output: ReportFormat,
+    },
+    /// Print the top assets as a table, sorted the way the TUI sorts them
+    Top {
+        /// Column to sort by
+        #[arg(long, value_enum, default_value_t = SortField::Bandwidth)]
+        by: SortField,
+        /// Number of rows to print
+        #[arg(short = 'n', long, default_value_t = 25)]
+        limit: usize,
+        /// Log files, glob patterns, or s3://, gs://, https:// URLs
+        #[arg(value_name = "LOG", required = true)]
+        inputs: Vec<String>,
+    },
+    /// Compare the logs against bandwidth and request budgets, exiting with status 1 if any is
+    /// exceeded
+    #[command(group(
+        ArgGroup::new("budget")
+            .args(["max_bandwidth", "max_requests"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Check {
+        /// Most bandwidth allowed, such as 50GB. KB, MB, and GB are powers of 1000, as Sanity
+        /// bills them, and KiB, MiB, and GiB powers of 1024
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        max_bandwidth: Option<u64>,
+        /// Most requests allowed, such as 2M or 500K
+        #[arg(long, value_name = "COUNT", value_parser = parse_count)]
+        max_requests: Option<u64>,
+        /// Log files, glob patterns, or s3://, gs://, https:// URLs
+        #[arg(value_name = "LOG", required = true)]
+        inputs: Vec<String>,
+    },
+    /// Keep running and write a report every so often, covering the requests logged in the
+    /// period before it. Each report reads every input again from the start, downloading remote
+    /// ones again, rather than following the files. CSV logs have no timestamps and are rejected
+    Watch {
+        /// How often to write the report, such as 15m, 1h, or 1d
+        #[arg(long, value_name = "INTERVAL", value_parser = parse_interval)]
+        report_every: Duration,
+        /// File to write the report to, replaced each time
+        #[arg(long, value_name = "FILE")]
+        report_file: PathBuf,
+        /// Write Markdown, or a self-contained HTML page with charts and sortable tables
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        output: ReportFormat,
+        /// Log files, glob patterns, or s3://, gs://, https:// URLs
+        #[arg(value_name = "LOG", required = true)]
+        inputs: Vec<String>,
+    },
+    /// Write the parsed records and aggregates to a file for analysis in other tools
+    Export {
+        /// Format to write
+        #[arg(id = "record_format", value_name = "FORMAT", value_enum)]
+        format: RecordFormat,
+        /// Log files, glob patterns, or s3://, gs://, https:// URLs
+        #[arg(value_name = "LOG", required = true)]
+        inputs: Vec<String>,
+        /// File to write, such as `logs.db` or `logs.parquet`
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+}
+
+impl Cli {
+    /// Takes the config file's defaults for anything not given on the command line. The
+    /// config's `sort` and `view` are left for the TUI, as where it was left with the same logs
+    /// comes first.
+    pub fn fill_from(&mut self, config: &Config) {
+        self.theme = self.theme.or(config.theme);
+        self.format = self.format.or(config.format);
+        self.schema = self.schema.or(config.schema);
+        if self.studio_url.is_none() {
+            self.studio_url.clone_from(&config.studio_url);
+        }
+        self.price_per_gb = self.price_per_gb.or(config.price_per_gb);
+        let fields = &config.fields;
+        let defaults = [
+            (&mut self.url_field, &fields.url),
+            (&mut self.request_size_field, &fields.request_size),
+            (&mut self.response_size_field, &fields.response_size),
+            (&mut self.timestamp_field, &fields.timestamp),
+            (&mut self.duration_field, &fields.duration),
+            (&mut self.url_column, &fields.url_column),
+            (&mut self.size_column, &fields.size_column),
+        ];
+        for (value, default) in defaults {
+            if value.is_none() {
+                value.clone_from(default);
+            }
+        }
+    }
+
+    /// The theme to draw the TUI with. `--no-color` and a non-empty NO_COLOR override any
+    /// other choice, following <https://no-color.org>.
+    pub fn theme(&self) -> ThemeName {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if self.no_color || no_color {
+            ThemeName::Monochrome
+        } else {
+            self.theme.unwrap_or_default()
+        }
+    }
+
+    /// The JSON fields of the chosen schema, with any per-field overrides applied.
+    pub fn json_fields(&self) -> JsonFields {
+        let mut fields = self.schema.unwrap_or_default().fields();
+        let overrides = [
+            (&self.url_field, &mut fields.url),
+            (&self.request_size_field, &mut fields.request_size),
+            (&self.response_size_field, &mut fields.response_size),
+            (&self.timestamp_field, &mut fields.timestamp),
+            (&self.duration_field, &mut fields.duration),
+        ];
+        for (value, field) in overrides {
+            if let Some(value) = value {
+                field.clone_from(value);
+            }
+        }
+        fields
+    }
+}
+
+/// Compiles a case-insensitive regular expression.
+pub fn parse_pattern(value: &str) -> Result<Regex, String> {
+    RegexBuilder::new(value)
+        .case_insensitive(true)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// Parses a size such as `500KB`, `10MiB`, or `1.5 GB` into bytes. `KB`, `MB`, or a bare `K`
+/// or `M` are always powers of 1000, as Sanity bills them, and `KiB`, `MiB`, and the like
+/// always powers of 1024, whatever units the table shows; a plain number is bytes.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (amount, unit) =
+        split_amount(value).ok_or_else(|| format!("expected a size like 10MB, got `{value}`"))?;
+    let unit = unit.trim().to_ascii_uppercase();
+    let (prefix, base) = match unit.strip_suffix("IB") {
+        Some(prefix) => (prefix, ByteUnits::Binary.base()),
+        None => (
+            unit.strip_suffix('B').unwrap_or(&unit),
+            ByteUnits::Decimal.base(),
+        ),
+    };
+    let exponent = match prefix {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => {
+            return Err(format!(
+                "unknown size unit `{unit}` (use B, KB, MB, GB, TB, or KiB to TiB)"
+            ));
+        }
+    };
+    Ok((amount * base.powi(exponent)).round() as u64)
+}
+
+/// Parses a count such as `500K`, `2M`, or `1.5B`. Units are powers of 1000, like the counts
+/// shown in the table.
+pub fn parse_count(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (amount, unit) =
+        split_amount(value).ok_or_else(|| format!("expected a count like 2M, got `{value}`"))?;
+    let exponent = match unit.trim().to_ascii_uppercase().as_str() {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "B" | "G" => 3,
+        _ => return Err(format!("unknown count unit `{unit}` (use K, M, or B)")),
+    };
+    Ok((amount * 1000f64.powi(exponent)).round() as u64)
+}
+
+/// Parses a price such as `0.30`, which can't be negative.
+fn parse_price(value: &str) -> Result<f64, String> {
+    match value
+        .trim()
+        .replace(units::separators().decimal, ".")
+        .parse()
+    {
+        Ok(price) if price >= 0.0 => Ok(price),
+        _ => Err(format!("expected a price like 0.30, got `{value}`")),
+    }
+}
+
+/// Splits a number such as `1.5` or `1,5`, written with `.` or the configured decimal
+/// separator, from the unit after it.
+fn split_amount(value: &str) -> Option<(f64, &str)> {
+    let decimal = units::separators().decimal;
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.' && c != decimal)
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount = amount.replace(decimal, ".").parse().ok()?;
+    Some((amount, unit))
+}
+
+/// Parses an RFC 3339 timestamp, a `YYYY-MM-DD` date (midnight UTC), or a duration before now
+/// such as `30m`, `24h`, `7d`, or `2w`.
+pub fn parse_time(value: &str) -> Result<Timestamp, String> {
+    if let Ok(timestamp) = value.parse::<Timestamp>() {
+        return Ok(timestamp);
+    }
+    if let Ok(date) = value.parse::<Date>() {
+        return date
+            .to_zoned(TimeZone::UTC)
+            .map(|zoned| zoned.timestamp())
+            .map_err(|err| err.to_string());
+    }
+    let span = parse_span(value).map_err(|err| match err {
+        SpanError::Amount => {
+            format!("expected an RFC 3339 time, a date, or a duration like 7d, got `{value}`")
+        }
+        SpanError::Unit(message) => message,
+        SpanError::TooLong => format!("duration `{value}` is too long"),
+    })?;
+    Timestamp::now()
+        .checked_sub(span)
+        .map_err(|err| err.to_string())
+}
+
+/// Parses a period like `15m`, `1h`, or `1d`, for `watch --report-every`.
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    let span = parse_span(value).map_err(|err| match err {
+        SpanError::Amount => format!("expected a duration like 15m, 1h, or 1d, got `{value}`"),
+        SpanError::Unit(message) => message,
+        SpanError::TooLong => format!("interval `{value}` is too long"),
+    })?;
+    let seconds = span.total(Unit::Second).map_err(|err| err.to_string())?;
+    if seconds <= 0.0 {
+        return Err("the interval must be longer than zero".to_string());
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+enum SpanError {
+    /// The value doesn't start with a number.
+    Amount,
+    Unit(String),
+    /// More hours than a span can hold.
+    TooLong,
+}
+
+/// A number followed by `m`, `h`, `d`, or `w`. Days are always 24 hours.
+fn parse_span(value: &str) -> Result<Span, SpanError> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| SpanError::Amount)?;
+    let hours = |per: i64| amount.checked_mul(per).ok_or(SpanError::TooLong);
+    let span = match unit {
+        "m" => Span::new().try_minutes(amount),
+        "h" => Span::new().try_hours(amount),
+        "d" => Span::new().try_hours(hours(24)?),
+        "w" => Span::new().try_hours(hours(24 * 7)?),
+        _ => {
+            return Err(SpanError::Unit(format!(
+                "unknown duration unit `{unit}` (use m, h, d, or w)"
+            )));
+        }
+    };
+    span.map_err(|_| SpanError::TooLong)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_spans() {
+        let hours = |value| parse_span(value).ok().map(|span: Span| span.get_hours());
+        assert_eq!(hours("3h"), Some(3));
+        assert_eq!(hours("2d"), Some(48));
+        assert_eq!(hours("1w"), Some(168));
+        assert_eq!(
+            parse_span("90m").ok().map(|span| span.get_minutes()),
+            Some(90)
+        );
+        assert!(matches!(parse_span("d"), Err(SpanError::Amount)));
+        assert!(matches!(parse_span("5y"), Err(SpanError::Unit(_))));
+    }
+
+    #[test]
+    fn rejects_spans_too_long_rather_than_panicking() {
+        for value in [
+            "9999999999999d",
+            "9999999999999w",
+            "9223372036854775807w",
+            "99999999999999h",
+            "999999999999999m",
+        ] {
+            assert!(
+                matches!(parse_span(value), Err(SpanError::TooLong)),
+                "{value}"
+            );
+        }
+        assert!(parse_time("9999999999999d").is_err());
+        assert!(parse_interval("9999999999999d").is_err());
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(
+            parse_time("2026-06-02"),
+            Ok("2026-06-02T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            parse_time("2026-06-02T12:30:00Z"),
+            Ok("2026-06-02T12:30:00Z".parse().unwrap())
+        );
+        let day_ago = parse_time("1d").unwrap();
+        let elapsed = Timestamp::now().duration_since(day_ago);
+        assert!(elapsed.as_secs() >= 24 * 3600 && elapsed.as_secs() < 24 * 3600 + 60);
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn reads_si_sizes_as_decimal_whatever_the_display_units() {
+        for display in [ByteUnits::Binary, ByteUnits::Decimal] {
+            units::set(display);
+            assert_eq!(parse_size("50GB"), Ok(50_000_000_000));
+            assert_eq!(parse_size("1.5 MB"), Ok(1_500_000));
+            assert_eq!(parse_size("10K"), Ok(10_000));
+            assert_eq!(parse_size("10KiB"), Ok(10_240));
+            assert_eq!(parse_size("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+            assert_eq!(parse_size("512"), Ok(512));
+        }
+        assert!(parse_size("5 parsecs").is_err());
+    }
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(parse_interval("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert!(parse_interval("0h").is_err());
+    }
+}
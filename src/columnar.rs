@@ -0,0 +1,181 @@
+use crate::{App, detect_request_type, extract_extension, image::is_unoptimized, ingest::Request};
+use anyhow::{Context, Result};
+use parquet::{
+    basic::Compression,
+    data_type::{BoolType, ByteArray, ByteArrayType, DataType, Int32Type, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    schema::parser::parse_message_type,
+};
+use std::{fs::File, path::Path, sync::Arc};
+
+/// One row per request. Columns are in the order [`write_row_group`] writes them.
+const SCHEMA: &str = "
+message request {
+    OPTIONAL INT64 timestamp (TIMESTAMP(MILLIS,true));
+    OPTIONAL BYTE_ARRAY host (STRING);
+    REQUIRED BYTE_ARRAY path (STRING);
+    OPTIONAL BYTE_ARRAY query (STRING);
+    REQUIRED BYTE_ARRAY type (STRING);
+    OPTIONAL BYTE_ARRAY ext (STRING);
+    REQUIRED BYTE_ARRAY asset (STRING);
+    OPTIONAL INT32 status;
+    OPTIONAL INT64 request_size;
+    OPTIONAL INT64 response_size;
+    OPTIONAL BOOLEAN cache_hit;
+    OPTIONAL INT64 duration_ms;
+    REQUIRED BOOLEAN unoptimized;
+    OPTIONAL BYTE_ARRAY referer (STRING);
+    OPTIONAL BYTE_ARRAY user_agent (STRING);
+}
+";
+
+/// Requests buffered per row group, bounding the memory used while writing.
+const ROW_GROUP_SIZE: usize = 100_000;
+
+/// Writes every request that passes the filters to a Parquet file, one row per log record.
+pub fn write_parquet(path: &Path, app: &App) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(file, schema, properties)?;
+    let filter = app.request_filter();
+    let requests: Vec<&Request> = app
+        .requests
+        .iter()
+        .filter(|request| filter.includes(request))
+        .collect();
+    for chunk in requests.chunks(ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group()?;
+        write_row_group(&mut row_group, chunk)?;
+        row_group.close()?;
+    }
+    writer
+        .close()
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+fn write_row_group(
+    row_group: &mut SerializedRowGroupWriter<File>,
+    requests: &[&Request],
+) -> Result<()> {
+    let text = |value: &str| ByteArray::from(value);
+    let number = |value: u64| value as i64;
+    write_column::<Int64Type>(
+        row_group,
+        requests.iter().map(|request| {
+            request
+                .record
+                .timestamp
+                .map(|timestamp| timestamp.as_millisecond())
+        }),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.url.host_str().map(text)),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests.iter().map(|request| Some(text(request.path()))),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests.iter().map(|request| request.url.query().map(text)),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| Some(text(detect_request_type(request.path()).name()))),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| extract_extension(request.path()).map(|ext| text(&ext))),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| Some(text(&request.stats_key()))),
+    )?;
+    write_column::<Int32Type>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.status.map(i32::from)),
+    )?;
+    write_column::<Int64Type>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.request_size.map(number)),
+    )?;
+    write_column::<Int64Type>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.response_size.map(number)),
+    )?;
+    write_column::<BoolType>(
+        row_group,
+        requests.iter().map(|request| request.record.cache_hit),
+    )?;
+    write_column::<Int64Type>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.duration_ms.map(number)),
+    )?;
+    write_column::<BoolType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| Some(is_unoptimized(&request.url))),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.referer.as_deref().map(text)),
+    )?;
+    write_column::<ByteArrayType>(
+        row_group,
+        requests
+            .iter()
+            .map(|request| request.record.user_agent.as_deref().map(text)),
+    )
+}
+
+/// Writes the next column of the row group: the values present, and a definition level per
+/// row marking which rows have one.
+fn write_column<T: DataType>(
+    row_group: &mut SerializedRowGroupWriter<File>,
+    values: impl Iterator<Item = Option<T::T>>,
+) -> Result<()> {
+    let mut column = row_group
+        .next_column()?
+        .context("more columns written than the schema has")?;
+    let values: Vec<Option<T::T>> = values.collect();
+    let levels: Vec<i16> = values
+        .iter()
+        .map(|value| i16::from(value.is_some()))
+        .collect();
+    let present: Vec<T::T> = values.into_iter().flatten().collect();
+    column
+        .typed::<T>()
+        .write_batch(&present, Some(&levels), None)?;
+    column.close()?;
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use crate::{
+    IdTruncation, SortField, StartView, cli,
+    ingest::{ParamFilter, Request},
+    keymap::KeyList,
+    record::{InputFormat, Schema},
+    theme::ThemeName,
+    units::{self, ByteUnits},
+};
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, de::Error};
+use std::{collections::BTreeMap, env, fs, io, path::PathBuf};
+use url::Url;
+
+/// Settings read from `~/.config/sanity-log-explorer/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Column the TUI starts sorted by, named as for `--sort`.
+    #[serde(deserialize_with = "value_enum")]
+    pub sort: Option<SortField>,
+    /// View the TUI opens on, named as for `--view`.
+    #[serde(deserialize_with = "value_enum")]
+    pub view: Option<StartView>,
+    /// Colors the TUI draws with, named as for `--theme`.
+    #[serde(deserialize_with = "value_enum")]
+    pub theme: Option<ThemeName>,
+    /// Units sizes are shown and read in, `binary` (KiB, MiB) or `decimal` (KB, MB).
+    #[serde(deserialize_with = "value_enum")]
+    pub units: Option<ByteUnits>,
+    /// Written between groups of three digits, such as `.` for `1.234.567`. Taken from the
+    /// locale when not set.
+    pub thousands_separator: Option<char>,
+    /// Written before the fraction of a number, such as `,` for `2,54 MiB`. Taken from the
+    /// locale when not set.
+    pub decimal_separator: Option<char>,
+    /// Where IDs too long for their column are cut, `end` or `middle`.
+    #[serde(deserialize_with = "value_enum")]
+    pub truncate_ids: Option<IdTruncation>,
+    /// Sanity Studio that document lookups open in, as for `--studio-url`.
+    #[serde(deserialize_with = "url")]
+    pub studio_url: Option<Url>,
+    /// What a GB of bandwidth costs, as for `--price-per-gb`.
+    pub price_per_gb: Option<f64>,
+    /// Format of the input logs, as for `--format`.
+    #[serde(deserialize_with = "value_enum")]
+    pub format: Option<InputFormat>,
+    /// Field names used by JSON records, as for `--schema`.
+    #[serde(deserialize_with = "value_enum")]
+    pub schema: Option<Schema>,
+    /// Where the fields of a record are found.
+    pub fields: FieldNames,
+    /// Keys for TUI actions, replacing the defaults, such as `down = ["n", "Down"]`.
+    pub keys: BTreeMap<String, KeyList>,
+    /// Named filter sets, applied with `--filter-preset` or cycled through with `P`.
+    pub filters: BTreeMap<String, FilterPreset>,
+}
+
+/// Just how numbers are written, read ahead of the rest so the sizes in the file are read
+/// with its decimal separator.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct NumberFormat {
+    #[serde(deserialize_with = "value_enum")]
+    units: Option<ByteUnits>,
+    thousands_separator: Option<char>,
+    decimal_separator: Option<char>,
+}
+
+impl Config {
+    /// Reads the config file, or the defaults when there isn't one. Its `units` and separators
+    /// are used from then on, and its decimal separator also reads its own sizes and those on
+    /// the command line.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        let invalid = || format!("invalid config in {}", path.display());
+        let NumberFormat {
+            units,
+            thousands_separator,
+            decimal_separator,
+        } = toml::from_str(&text).with_context(invalid)?;
+        units::set(units.unwrap_or_default());
+        units::set_separators(thousands_separator, decimal_separator);
+        let separators = units::separators();
+        if separators.thousands == separators.decimal {
+            bail!(
+                "{}: thousands_separator and decimal_separator are both `{}`",
+                invalid(),
+                separators.decimal
+            );
+        }
+        toml::from_str(&text).with_context(invalid)
+    }
+}
+
+/// JSON fields and CSV columns overriding the schema's, as with `--url-field` or
+/// `--url-column`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldNames {
+    pub url: Option<String>,
+    pub request_size: Option<String>,
+    pub response_size: Option<String>,
+    pub timestamp: Option<String>,
+    pub duration: Option<String>,
+    pub url_column: Option<String>,
+    pub size_column: Option<String>,
+}
+
+/// `$XDG_CONFIG_HOME/sanity-log-explorer/config.toml`, falling back to `~/.config`.
+fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("sanity-log-explorer").join("config.toml"))
+}
+
+/// A named slice of the traffic. Every criterion given must hold for a request or row to be
+/// shown.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterPreset {
+    /// Only requests whose referer matches this regular expression.
+    #[serde(deserialize_with = "regex")]
+    pub referer: Option<Regex>,
+    /// Only requests whose URL path matches this regular expression.
+    #[serde(deserialize_with = "regex")]
+    pub path: Option<Regex>,
+    /// Leave out requests whose URL path matches any of these regular expressions.
+    #[serde(deserialize_with = "regexes")]
+    pub exclude: Vec<Regex>,
+    /// Only requests whose query parameters meet all of these, written like `--param`.
+    #[serde(deserialize_with = "params")]
+    pub params: Vec<ParamFilter>,
+    /// Only rows of these types; every type when empty.
+    pub types: Vec<PresetType>,
+    /// Hide rows that used less bandwidth than this, such as `10MB`.
+    #[serde(deserialize_with = "size")]
+    pub min_bandwidth: Option<u64>,
+    /// Text for the `/` filter bar.
+    pub filter: Option<String>,
+}
+
+impl FilterPreset {
+    /// Whether a request passes the preset's request criteria. Types, minimum bandwidth, and the
+    /// filter text apply to rows instead.
+    pub fn includes(&self, request: &Request) -> bool {
+        let path = request.url.path();
+        let referer = request.record.referer.as_deref().unwrap_or_default();
+        self.referer
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(referer))
+            && self
+                .path
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(path))
+            && !self.exclude.iter().any(|pattern| pattern.is_match(path))
+            && self.params.iter().all(|param| param.matches(&request.url))
+    }
+}
+
+/// Row types a preset can keep, matching the `I`, `F`, `Q`, and `O` toggles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetType {
+    Image,
+    File,
+    Query,
+    Other,
+}
+
+/// A value named as on the command line, such as `avg-size` for a sort field.
+pub fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let Some(name) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    T::from_str(&name, true).map(Some).map_err(|_| {
+        let names: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| value.get_name().to_string())
+            .collect();
+        D::Error::custom(format!("unknown value `{name}` (use {})", names.join(", ")))
+    })
+}
+
+fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|pattern| Regex::new(&pattern).map_err(D::Error::custom))
+        .transpose()
+}
+
+fn url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Url>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|url| Url::parse(&url).map_err(D::Error::custom))
+        .transpose()
+}
+
+fn regexes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Regex>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(D::Error::custom))
+        .collect()
+}
+
+fn params<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ParamFilter>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|param| param.parse().map_err(D::Error::custom))
+        .collect()
+}
+
+fn size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|size| cli::parse_size(&size).map_err(D::Error::custom))
+        .transpose()
+}
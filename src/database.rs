@@ -0,0 +1,139 @@
+use crate::{App, detect_request_type, extract_extension, image::is_unoptimized};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+/// Tables are dropped and created again on every export, so other tables in the database are
+/// kept.
+const SCHEMA: &str = "
+DROP TABLE IF EXISTS requests;
+DROP TABLE IF EXISTS assets;
+
+CREATE TABLE requests (
+    id INTEGER PRIMARY KEY,
+    timestamp TEXT,
+    host TEXT,
+    path TEXT NOT NULL,
+    query TEXT,
+    type TEXT NOT NULL,
+    ext TEXT,
+    asset TEXT NOT NULL,
+    status INTEGER,
+    request_size INTEGER,
+    response_size INTEGER,
+    cache_hit INTEGER,
+    duration_ms INTEGER,
+    unoptimized INTEGER NOT NULL,
+    referer TEXT,
+    user_agent TEXT,
+    client_ip TEXT,
+    request_id TEXT,
+    input TEXT NOT NULL,
+    line_number INTEGER NOT NULL
+);
+
+CREATE TABLE assets (
+    asset TEXT NOT NULL,
+    type TEXT NOT NULL,
+    label TEXT NOT NULL,
+    ext TEXT,
+    requests INTEGER NOT NULL,
+    bandwidth INTEGER NOT NULL,
+    avg_size INTEGER NOT NULL,
+    unoptimized_bandwidth INTEGER NOT NULL,
+    variants INTEGER NOT NULL,
+    cache_lookups INTEGER NOT NULL,
+    cache_hits INTEGER NOT NULL,
+    client_errors INTEGER NOT NULL,
+    server_errors INTEGER NOT NULL,
+    avg_latency_ms INTEGER
+);
+";
+
+/// Created after the rows are inserted, which is faster than keeping them up to date.
+const INDEXES: &str = "
+CREATE INDEX requests_timestamp ON requests (timestamp);
+CREATE INDEX requests_path ON requests (path);
+CREATE INDEX requests_type ON requests (type);
+CREATE INDEX requests_asset ON requests (asset);
+CREATE INDEX requests_status ON requests (status);
+CREATE INDEX requests_referer ON requests (referer);
+CREATE INDEX assets_asset ON assets (asset);
+CREATE INDEX assets_bandwidth ON assets (bandwidth);
+";
+
+/// Writes every request that passes the filters to a `requests` table, one row per log
+/// record, and the By Asset rows to an `assets` table joined to it on `asset`.
+pub fn write_sqlite(path: &Path, app: &App) -> Result<()> {
+    let mut db =
+        Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    db.execute_batch(SCHEMA)?;
+    let tx = db.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO requests (timestamp, host, path, query, type, ext, asset, status, \
+             request_size, response_size, cache_hit, duration_ms, unoptimized, referer, \
+             user_agent, client_ip, request_id, input, line_number) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, \
+             ?17, ?18, ?19)",
+        )?;
+        let filter = app.request_filter();
+        for request in app
+            .requests
+            .iter()
+            .filter(|request| filter.includes(request))
+        {
+            let record = &request.record;
+            let path = request.path();
+            insert.execute(params![
+                record.timestamp.map(|timestamp| timestamp.to_string()),
+                request.url.host_str(),
+                path,
+                request.url.query(),
+                detect_request_type(path).name(),
+                extract_extension(path),
+                request.stats_key(),
+                record.status,
+                record.request_size,
+                record.response_size,
+                record.cache_hit,
+                record.duration_ms,
+                is_unoptimized(&request.url),
+                record.referer,
+                record.user_agent,
+                record.client_ip,
+                record.request_id,
+                app.inputs[request.input].to_string(),
+                request.line_number,
+            ])?;
+        }
+
+        let mut insert = tx.prepare(
+            "INSERT INTO assets (asset, type, label, ext, requests, bandwidth, avg_size, \
+             unoptimized_bandwidth, variants, cache_lookups, cache_hits, client_errors, \
+             server_errors, avg_latency_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        )?;
+        for row in &app.items {
+            insert.execute(params![
+                row.key.as_deref().unwrap_or(&row.label),
+                row.req_type.name(),
+                row.label,
+                Some(row.ext.trim_start_matches('.')).filter(|ext| !ext.is_empty()),
+                row.request_count,
+                row.bandwidth_sum,
+                row.avg_size(),
+                row.unoptimized_bandwidth,
+                row.variant_count,
+                row.cache_lookups,
+                row.cache_hits,
+                row.client_errors,
+                row.server_errors,
+                row.avg_latency(),
+            ])?;
+        }
+    }
+    tx.execute_batch(INDEXES)?;
+    tx.commit()
+        .with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,42 @@
+use sketches_ddsketch::{Config, DDSketch};
+use std::fmt;
+
+/// Values are placed in buckets this far apart (2%), which bounds the error of percentiles.
+const RELATIVE_ACCURACY: f64 = 0.02;
+const MAX_BINS: u32 = 2048;
+
+/// A mergeable sketch of values such as response sizes, answering percentile queries without
+/// keeping every value around.
+#[derive(Clone)]
+pub struct Distribution(DDSketch);
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Distribution(DDSketch::new(Config::new(RELATIVE_ACCURACY, MAX_BINS, 1.0)))
+    }
+}
+
+impl fmt::Debug for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Distribution")
+            .field("count", &self.0.count())
+            .finish()
+    }
+}
+
+impl Distribution {
+    pub fn add(&mut self, value: u64) {
+        self.0.add(value as f64);
+    }
+
+    pub fn merge(&mut self, other: &Distribution) {
+        // Every sketch is built with the same config, which is all that merging requires.
+        let _ = self.0.merge(&other.0);
+    }
+
+    /// The value below which `quantile` (0.0–1.0) of the values fall, or `None` when empty.
+    pub fn quantile(&self, quantile: f64) -> Option<u64> {
+        let value = self.0.quantile(quantile).ok().flatten()?;
+        Some(value.round() as u64)
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{App, Column, DisplayRow, column_text, type_label};
+use std::io::{self, Write};
+
+/// Formats the table can be written out in with `--export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Files the parsed records can be written to with the `export` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RecordFormat {
+    /// A SQLite database with a `requests` table of the records and an `assets` table of the
+    /// By Asset rows
+    Sqlite,
+    /// An Apache Parquet file with a row per record, for DuckDB, pandas, or Polars
+    Parquet,
+}
+
+/// Writes the rows being shown, in table order, with every column's full value: sizes in
+/// bytes, latencies in milliseconds, and rates as percentages rather than abbreviated text.
+pub fn write_csv(mut out: impl Write, app: &App) -> io::Result<()> {
+    let columns = app.export_columns();
+    write_csv_record(&mut out, columns.iter().map(|column| heading(*column, app)))?;
+    for row in &app.items {
+        write_csv_record(&mut out, columns.iter().map(|column| value(row, *column)))?;
+    }
+    out.flush()
+}
+
+/// Writes the rows being shown as tab-separated values, formatted as in the table: sizes as
+/// `2.54 MiB` and rates as `12.5%`, under the same headings.
+pub fn write_tsv(mut out: impl Write, app: &App) -> io::Result<()> {
+    let columns = app.export_columns();
+    let headings = columns.iter().map(|column| match column {
+        Column::Id => app.id_title(),
+        column => column.title(),
+    });
+    write_tsv_record(&mut out, headings.map(str::to_string))?;
+    for row in &app.items {
+        write_tsv_record(
+            &mut out,
+            columns.iter().map(|column| column_text(row, *column)),
+        )?;
+    }
+    out.flush()
+}
+
+/// Tabs and line breaks inside a field become spaces, as TSV has no quoting.
+fn write_tsv_record(out: &mut impl Write, fields: impl Iterator<Item = String>) -> io::Result<()> {
+    let fields: Vec<String> = fields
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
+        .collect();
+    writeln!(out, "{}", fields.join("\t"))
+}
+
+fn write_csv_record(out: &mut impl Write, fields: impl Iterator<Item = String>) -> io::Result<()> {
+    let fields: Vec<String> = fields.map(|field| csv_field(&field)).collect();
+    writeln!(out, "{}", fields.join(","))
+}
+
+/// Quotes a field when it holds a comma, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn heading(column: Column, app: &App) -> String {
+    let heading = match column {
+        Column::Type => "Type",
+        Column::Id => app.id_title(),
+        Column::Ext => "Ext",
+        Column::Trend => "Trend",
+        Column::Requests => "Requests",
+        Column::AvgSize => "Avg size (bytes)",
+        Column::Bandwidth => "Bandwidth (bytes)",
+        Column::Unoptimized => "Unoptimized (bytes)",
+        Column::Variants => "Variants",
+        Column::CacheHit => "Cache hit %",
+        Column::P50 => "p50 size (bytes)",
+        Column::P95 => "p95 size (bytes)",
+        Column::P99 => "p99 size (bytes)",
+        Column::Latency => "Latency (ms)",
+        Column::LatencyP95 => "p95 latency (ms)",
+        Column::RequestsDelta => "Requests change",
+        Column::BandwidthDelta => "Bandwidth change (bytes)",
+        Column::Change => "Change %",
+        Column::RequestSize => "Request size (bytes)",
+        Column::ClientErrors => "4xx",
+        Column::ServerErrors => "5xx",
+        Column::ErrorRate => "Error %",
+    };
+    heading.to_string()
+}
+
+fn value(row: &DisplayRow, column: Column) -> String {
+    let number = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    let percent = |ratio: Option<f64>| {
+        ratio
+            .filter(|ratio| ratio.is_finite())
+            .map(|ratio| format!("{:.2}", ratio * 100.0))
+            .unwrap_or_default()
+    };
+    let percentile = |index: usize| number(row.size_percentiles.map(|values| values[index]));
+    match column {
+        Column::Type => type_label(row.req_type).to_string(),
+        Column::Id => row.label.clone(),
+        Column::Ext => row.ext.clone(),
+        Column::Trend => row
+            .trend
+            .iter()
+            .flatten()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Column::Requests => row.request_count.to_string(),
+        Column::AvgSize => row.avg_size().to_string(),
+        Column::Bandwidth => row.bandwidth_sum.to_string(),
+        Column::Unoptimized => row.unoptimized_bandwidth.to_string(),
+        Column::Variants => row.variant_count.to_string(),
+        Column::CacheHit => percent(row.cache_hit_rate()),
+        Column::P50 => percentile(0),
+        Column::P95 => percentile(1),
+        Column::P99 => percentile(2),
+        Column::Latency => number(row.avg_latency()),
+        Column::LatencyP95 => number(row.latency_p95),
+        Column::RequestsDelta => row
+            .baseline
+            .map(|baseline| (row.request_count as i64 - baseline.requests as i64).to_string())
+            .unwrap_or_default(),
+        Column::BandwidthDelta => row
+            .bandwidth_delta()
+            .map(|delta| delta.to_string())
+            .unwrap_or_default(),
+        Column::Change => percent(row.change()),
+        Column::RequestSize => row.request_size_sum.to_string(),
+        Column::ClientErrors => row.client_errors.to_string(),
+        Column::ServerErrors => row.server_errors.to_string(),
+        Column::ErrorRate => percent(row.error_rate()),
+    }
+}
@@ -0,0 +1,68 @@
+/// Points for each matched character, plus the bonuses below.
+const MATCH: i64 = 1;
+/// Bonus for a character right after the previous match, so runs rank above scattered hits.
+const CONSECUTIVE: i64 = 5;
+/// Bonus for a character that starts a word (the text's first, or after `/`, `-`, `.`, ...).
+const WORD_START: i64 = 3;
+/// Penalty for skipping over text between two matches.
+const GAP: i64 = 2;
+
+/// Scores how well `text` matches `pattern` when the pattern's characters appear in order but
+/// not necessarily next to each other, ignoring case. Returns `None` when they don't all appear,
+/// otherwise the score (higher is better) and the char indices of the best placement.
+pub fn score(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let bonus = |idx: usize| {
+        let word_start = idx == 0 || !text[idx - 1].is_alphanumeric();
+        MATCH + if word_start { WORD_START } else { 0 }
+    };
+    let matches = |idx: usize, wanted: char| text[idx].to_lowercase().eq([wanted]);
+
+    // best[i][j]: the highest score placing pattern[..=i] with pattern[i] on text[j], and where
+    // pattern[i - 1] went to get it.
+    let mut best: Vec<Vec<Option<(i64, usize)>>> = vec![vec![None; text.len()]; pattern.len()];
+    for (idx, slot) in best[0].iter_mut().enumerate() {
+        if matches(idx, pattern[0]) {
+            *slot = Some((bonus(idx), 0));
+        }
+    }
+    for i in 1..pattern.len() {
+        // Best placement of pattern[i - 1] at least two characters back, for matches after a gap.
+        let mut before_gap: Option<(i64, usize)> = None;
+        for j in i..text.len() {
+            if j >= 2
+                && let Some((score, _)) = best[i - 1][j - 2]
+                && before_gap.is_none_or(|(best, _)| score > best)
+            {
+                before_gap = Some((score, j - 2));
+            }
+            if !matches(j, pattern[i]) {
+                continue;
+            }
+            let consecutive = best[i - 1][j - 1].map(|(score, _)| (score + CONSECUTIVE, j - 1));
+            let after_gap = before_gap.map(|(score, from)| (score - GAP, from));
+            best[i][j] = match (consecutive, after_gap) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            }
+            .map(|(score, from)| (score + bonus(j), from));
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut idx, (score, _)) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, slot)| slot.map(|slot| (idx, slot)))
+        .max_by_key(|&(idx, (score, _))| (score, std::cmp::Reverse(idx)))?;
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..=last).rev() {
+        positions[i] = idx;
+        idx = best[i][idx].map_or(0, |(_, from)| from);
+    }
+    Some((score, positions))
+}
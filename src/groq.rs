@@ -0,0 +1,145 @@
+use url::Url;
+
+/// The normalized GROQ query of a `/:version/data/query/:dataset` request, if it was sent in
+/// the URL. Parameters (`$name=...`) are ignored so that one query run with different values
+/// counts as one.
+pub fn query_text(url: &Url) -> Option<String> {
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+    let (_version, data, query) = (segments.next()?, segments.next()?, segments.next()?);
+    if data != "data" || query != "query" {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(name, _)| name == "query")
+        .map(|(_, text)| normalize(&text))
+}
+
+/// Drops whitespace outside string literals, keeping a single space only where it separates
+/// two words (`order(_createdAt desc)`), so differently formatted copies of a query compare
+/// equal.
+pub fn normalize(query: &str) -> String {
+    let mut out = String::with_capacity(query.len());
+    let mut quote = None;
+    let mut escaped = false;
+    let mut pending_space = false;
+    for ch in query.chars() {
+        if let Some(open) = quote {
+            out.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        if ch.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && out.ends_with(is_word_char) && is_word_char(ch) {
+            out.push(' ');
+        }
+        pending_space = false;
+        if ch == '"' || ch == '\'' {
+            quote = Some(ch);
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || matches!(ch, '_' | '$' | '.' | '@' | '^')
+}
+
+/// Lays a query out over several lines: projections are broken up one attribute per line and
+/// indented, and operators get spaces around them.
+pub fn pretty(query: &str) -> String {
+    let query = normalize(query);
+    let mut out = String::with_capacity(query.len() * 2);
+    let mut open: Vec<char> = Vec::new();
+    let mut chars = query.chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    };
+    let braces = |open: &[char]| open.iter().filter(|&&ch| ch == '{').count();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' | '\'' => {
+                out.push(ch);
+                let mut escaped = false;
+                for inner in chars.by_ref() {
+                    out.push(inner);
+                    match inner {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        _ if inner == ch => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push_str("{}");
+            }
+            '{' => {
+                open.push(ch);
+                out.push(ch);
+                newline(&mut out, braces(&open));
+            }
+            '}' => {
+                open.pop();
+                newline(&mut out, braces(&open));
+                out.push(ch);
+            }
+            '[' | '(' => {
+                open.push(ch);
+                out.push(ch);
+            }
+            ']' | ')' => {
+                open.pop();
+                out.push(ch);
+            }
+            ',' if open.last() == Some(&'{') => {
+                out.push(ch);
+                newline(&mut out, braces(&open));
+            }
+            ',' => out.push_str(", "),
+            ':' if open.last() == Some(&'{') => out.push_str(": "),
+            '|' | '&' | '=' | '!' | '<' | '>' | '-' => {
+                let next = chars.peek().copied();
+                let operator = match (ch, next) {
+                    ('|', Some('|')) | ('&', Some('&')) | ('=', Some('=')) => {
+                        chars.next();
+                        format!("{ch}{ch}")
+                    }
+                    ('=' | '-', Some('>')) | ('!' | '<' | '>', Some('=')) => {
+                        chars.next();
+                        format!("{ch}{}", next.unwrap_or_default())
+                    }
+                    ('|' | '<' | '>', _) => ch.to_string(),
+                    _ => {
+                        out.push(ch);
+                        continue;
+                    }
+                };
+                out.push(' ');
+                out.push_str(&operator);
+                out.push(' ');
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// The `$name=value` parameters sent alongside a query, as `($name, value)` pairs.
+pub fn params(url: &Url) -> Vec<(String, String)> {
+    url.query_pairs()
+        .filter(|(name, _)| name.starts_with('$'))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect()
+}
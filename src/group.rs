@@ -0,0 +1,250 @@
+use crate::{image, ingest::Request};
+use anyhow::{Context, Result};
+use maxminddb::{Reader, geoip2};
+use publicsuffix::{List, Psl};
+use std::{
+    net::IpAddr,
+    path::Path,
+    sync::{LazyLock, OnceLock},
+};
+use url::Url;
+
+/// Label for requests that don't carry the attribute being grouped by.
+pub const UNKNOWN_GROUP: &str = "(none)";
+
+/// Bundled copy of https://publicsuffix.org/list/, parsed on first use.
+static PUBLIC_SUFFIXES: LazyLock<List> = LazyLock::new(|| {
+    include_str!("../data/public_suffix_list.dat")
+        .parse()
+        .unwrap_or_default()
+});
+
+/// Country database passed with `--geoip`.
+static GEOIP: OnceLock<Reader<Vec<u8>>> = OnceLock::new();
+
+/// Opens a MaxMind database so the By Client view can group IPs by country.
+pub fn load_geoip(path: &Path) -> Result<()> {
+    let reader = Reader::open_readfile(path)
+        .with_context(|| format!("failed to open GeoIP database {}", path.display()))?;
+    let _ = GEOIP.set(reader);
+    Ok(())
+}
+
+/// Request attributes that the grouped views break traffic down by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Dataset,
+    Project,
+    Host,
+    Status,
+    Referer,
+    Domain,
+    Client,
+    Transform,
+    Write,
+}
+
+impl Dimension {
+    pub fn title(self) -> &'static str {
+        match self {
+            Dimension::Dataset => "By Dataset",
+            Dimension::Project => "By Project",
+            Dimension::Host => "By Host",
+            Dimension::Status => "By Status",
+            Dimension::Referer => "By Referer",
+            Dimension::Domain => "By Domain",
+            Dimension::Client => "By Client",
+            Dimension::Transform => "Transforms",
+            Dimension::Write => "Writes",
+        }
+    }
+
+    /// Heading of the table column holding the group name.
+    pub fn label(self) -> &'static str {
+        match self {
+            Dimension::Dataset => "Dataset",
+            Dimension::Project => "Project",
+            Dimension::Host => "Host",
+            Dimension::Status => "Status",
+            Dimension::Referer => "Referer",
+            Dimension::Domain => "Referring domain",
+            Dimension::Client => "Client IP",
+            Dimension::Transform => "Image / transform",
+            Dimension::Write => "Dataset / client",
+        }
+    }
+
+    /// The group a request falls under.
+    pub fn key(self, request: &Request) -> String {
+        let key = match self {
+            Dimension::Dataset => dataset(request.path()).map(str::to_string),
+            Dimension::Project => project(request).map(str::to_string),
+            Dimension::Host => request.url.host_str().map(str::to_string),
+            Dimension::Status => request.record.status.map(|status| status.to_string()),
+            Dimension::Referer => request.record.referer.clone(),
+            Dimension::Domain => request.record.referer.as_deref().map(referring_page),
+            Dimension::Client => request.record.client_ip.clone(),
+            Dimension::Transform => Some(format!(
+                "{}?{}",
+                image::file_name(&request.url),
+                image::transform_signature(&request.url)
+            )),
+            Dimension::Write => Some(format!(
+                "{}/{}",
+                dataset(request.path()).unwrap_or(UNKNOWN_GROUP),
+                request.record.client_ip.as_deref().unwrap_or(UNKNOWN_GROUP)
+            )),
+        };
+        key.unwrap_or_else(|| UNKNOWN_GROUP.to_string())
+    }
+
+    /// Whether a request belongs in this view at all.
+    pub fn includes(self, request: &Request) -> bool {
+        match self {
+            Dimension::Transform => image::is_image(&request.url),
+            Dimension::Write => is_write(request.path()),
+            _ => true,
+        }
+    }
+
+    /// Whether groups roll up into parents, shown as expandable sections.
+    pub fn is_nested(self) -> bool {
+        match self {
+            Dimension::Status | Dimension::Domain | Dimension::Transform | Dimension::Write => true,
+            Dimension::Client => GEOIP.get().is_some(),
+            Dimension::Dataset | Dimension::Project | Dimension::Host | Dimension::Referer => false,
+        }
+    }
+
+    /// For nested dimensions, the parent that group `key` rolls up into.
+    pub fn parent(self, key: &str) -> String {
+        match self {
+            Dimension::Status => status_class(key),
+            Dimension::Domain => registrable_domain(key.split('/').next().unwrap_or(key)),
+            Dimension::Client => country(key),
+            Dimension::Transform => key
+                .split_once('?')
+                .map_or(key, |(file, _)| file)
+                .to_string(),
+            Dimension::Write => key
+                .split_once('/')
+                .map_or(key, |(dataset, _)| dataset)
+                .to_string(),
+            Dimension::Dataset | Dimension::Project | Dimension::Host | Dimension::Referer => {
+                key.to_string()
+            }
+        }
+    }
+
+    /// How a group is labelled beneath its parent.
+    pub fn child_label(self, key: &str) -> &str {
+        match self {
+            Dimension::Transform => key.split_once('?').map_or(key, |(_, signature)| signature),
+            Dimension::Write => key.split_once('/').map_or(key, |(_, client)| client),
+            _ => key,
+        }
+    }
+
+    /// What Enter opens for a group, when it is better than a sample request URL.
+    pub fn link(self, key: &str) -> Option<String> {
+        match self {
+            Dimension::Referer if key.starts_with("http") => Some(key.to_string()),
+            Dimension::Domain if key != UNKNOWN_GROUP => Some(format!("https://{key}")),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the dataset from asset paths (`/images/:projectId/:dataset/...`) and API paths
+/// (`/:version/data/query/:dataset`, `/:version/assets/images/:dataset`, ...).
+pub fn dataset(path: &str) -> Option<&str> {
+    let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match parts.as_slice() {
+        ["images" | "files", _project, dataset, ..] => Some(*dataset),
+        [_version, "data" | "assets", _endpoint, dataset, ..] => Some(*dataset),
+        _ => None,
+    }
+}
+
+/// Whether a request writes to a dataset: mutations (`/:version/data/mutate/:dataset`) and asset
+/// uploads (`/:version/assets/images/:dataset`, `/:version/assets/files/:dataset`).
+fn is_write(path: &str) -> bool {
+    let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    matches!(
+        parts.as_slice(),
+        [_version, "data", "mutate", ..] | [_version, "assets", "images" | "files", ..]
+    )
+}
+
+/// Reads the project ID from API hosts (`:projectId.api.sanity.io`,
+/// `:projectId.apicdn.sanity.io`) or, for CDN assets, from the path.
+fn project(request: &Request) -> Option<&str> {
+    let host = request.url.host_str().unwrap_or_default();
+    if let Some(subdomain) = host
+        .strip_suffix(".api.sanity.io")
+        .or_else(|| host.strip_suffix(".apicdn.sanity.io"))
+    {
+        return Some(subdomain);
+    }
+    let mut parts = request.path().split('/').filter(|s| !s.is_empty());
+    match (parts.next(), parts.next()) {
+        (Some("images" | "files"), Some(project)) => Some(project),
+        _ => None,
+    }
+}
+
+fn status_class(status: &str) -> String {
+    let label = match status.parse::<u16>().map(|code| code / 100) {
+        Ok(1) => "1xx Informational",
+        Ok(2) => "2xx Success",
+        Ok(3) => "3xx Redirection",
+        Ok(4) => "4xx Client error",
+        Ok(5) => "5xx Server error",
+        _ => UNKNOWN_GROUP,
+    };
+    label.to_string()
+}
+
+/// A referer without its scheme, query string, or fragment (`example.com/blog/post`).
+fn referring_page(referer: &str) -> String {
+    match Url::parse(referer) {
+        Ok(url) if url.host_str().is_some() => {
+            format!("{}{}", url.host_str().unwrap_or_default(), url.path())
+        }
+        _ => referer.to_string(),
+    }
+}
+
+/// The domain a host was registered under, so `www.example.co.uk` and `shop.example.co.uk`
+/// both roll up into `example.co.uk`. IP addresses and unlisted hosts are returned as is.
+fn registrable_domain(host: &str) -> String {
+    PUBLIC_SUFFIXES
+        .domain(host.as_bytes())
+        .and_then(|domain| std::str::from_utf8(domain.as_bytes()).ok())
+        .unwrap_or(host)
+        .to_string()
+}
+
+/// The country an IP address is registered in, as `US United States`.
+fn country(ip: &str) -> String {
+    if ip == UNKNOWN_GROUP {
+        return ip.to_string();
+    }
+    let country = GEOIP
+        .get()
+        .zip(ip.parse::<IpAddr>().ok())
+        .and_then(|(reader, ip)| reader.lookup::<geoip2::Country>(ip).ok())
+        .and_then(|record| record.country);
+    let Some(country) = country else {
+        return "Unknown country".to_string();
+    };
+    let name = country
+        .names
+        .as_ref()
+        .and_then(|names| names.get("en").copied());
+    match (country.iso_code, name) {
+        (Some(code), Some(name)) => format!("{code} {name}"),
+        (Some(label), None) | (None, Some(label)) => label.to_string(),
+        (None, None) => "Unknown country".to_string(),
+    }
+}
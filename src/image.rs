@@ -0,0 +1,52 @@
+use url::Url;
+
+/// Image pipeline parameters that change the bytes served, in the order signatures list them.
+/// See https://www.sanity.io/docs/image-urls.
+const TRANSFORM_PARAMS: [&str; 9] = ["w", "h", "fm", "q", "fit", "dpr", "auto", "crop", "rect"];
+
+/// Label for image requests without any transform parameters.
+pub const ORIGINAL: &str = "(original)";
+
+pub fn is_image(url: &Url) -> bool {
+    url.path().starts_with("/images/")
+}
+
+/// The image's file name (`:id-:dimensions.:ext`), which identifies the asset.
+pub fn file_name(url: &Url) -> &str {
+    url.path().rsplit('/').next().unwrap_or_default()
+}
+
+/// Whether a JPEG or PNG image was served in its original format. Adding `auto=format` lets
+/// the CDN send WebP or AVIF to browsers that accept them, which is usually far smaller.
+pub fn is_unoptimized(url: &Url) -> bool {
+    let ext = file_name(url)
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    if !is_image(url) || !matches!(ext.as_deref(), Some("jpg" | "jpeg" | "png")) {
+        return false;
+    }
+    !url.query_pairs().any(|(name, value)| {
+        (name == "auto" && value == "format")
+            || (name == "fm" && matches!(&*value, "webp" | "avif"))
+    })
+}
+
+/// The transform parameters of an image request in a fixed order, such as `w=800 h=600 fm=webp`.
+/// Other query parameters are ignored.
+pub fn transform_signature(url: &Url) -> String {
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    let signature: Vec<String> = TRANSFORM_PARAMS
+        .iter()
+        .filter_map(|param| {
+            pairs
+                .iter()
+                .find(|(name, _)| name == param)
+                .map(|(name, value)| format!("{name}={value}"))
+        })
+        .collect();
+    if signature.is_empty() {
+        ORIGINAL.to_string()
+    } else {
+        signature.join(" ")
+    }
+}
@@ -0,0 +1,954 @@
+use crate::{
+    distribution::Distribution,
+    groq, group, image,
+    record::{
+        CsvColumns, CsvLayout, InputFormat, JsonFields, LineParser, ParseResult, Record, SkipReason,
+    },
+    source::{self, Input},
+};
+use anyhow::{Context, Result};
+use jiff::Timestamp;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde_json::value::RawValue;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{BufRead, Read},
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+use url::Url;
+
+const BATCH_LINES: usize = 16 * 1024;
+const MAPPED_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+pub const MAX_PARSE_ERRORS: usize = 100;
+const ERROR_SNIPPET_CHARS: usize = 160;
+const DEDUPE_SHARDS: usize = 16;
+/// Relative URLs (e.g. from proxy access logs) are resolved against the Sanity CDN.
+const CDN_BASE_URL: &str = "https://cdn.sanity.io/";
+
+/// User agents of crawlers, link previewers, monitoring, and HTTP libraries, left out with
+/// `--no-bots`.
+pub const BOT_USER_AGENTS: &str = concat!(
+    r"(?i)bot\b|bot/|crawl|spider|slurp|facebookexternalhit|preview|headless|lighthouse|",
+    r"pingdom|uptime|monitor|curl/|wget/|python-requests|python-urllib|go-http-client|",
+    r"axios/|node-fetch|undici|okhttp|java/|libwww|httpclient|scrapy",
+);
+
+#[derive(Debug, Clone)]
+pub struct PathStats {
+    pub path: String,
+    pub sample_url: String,
+    pub request_count: u64,
+    pub request_size_sum: u64,
+    pub bandwidth_sum: u64,
+    pub status_counts: BTreeMap<u16, u64>,
+    /// Requests with a known cache status, and how many of those were hits.
+    pub cache_lookups: u64,
+    pub cache_hits: u64,
+    /// Bandwidth of JPEG/PNG images served without `auto=format` or a modern `fm`.
+    pub unoptimized_bandwidth: u64,
+    pub response_sizes: Distribution,
+    /// Requests with a logged duration, their total, and their distribution in milliseconds.
+    pub timed_requests: u64,
+    pub duration_sum: u64,
+    pub durations: Distribution,
+    /// Distinct [`Request::variant_key`]s among the image and file requests counted.
+    pub variants: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadSummary {
+    pub file_count: usize,
+    pub line_count: u64,
+    pub duplicates: u64,
+    pub filtered_out: u64,
+    pub time_span: Option<TimeSpan>,
+}
+
+/// Earliest and latest record timestamps seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSpan {
+    pub first: Timestamp,
+    pub last: Timestamp,
+}
+
+impl TimeSpan {
+    pub fn widen(span: &mut Option<TimeSpan>, other: TimeSpan) {
+        *span = Some(match *span {
+            Some(span) => TimeSpan {
+                first: span.first.min(other.first),
+                last: span.last.max(other.last),
+            },
+            None => other,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadProgress {
+    pub lines: u64,
+    pub bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub source: String,
+    pub line_number: u64,
+    pub reason: SkipReason,
+    pub message: String,
+    pub snippet: String,
+}
+
+/// Parsed requests for a run of lines, including what had to be skipped.
+#[derive(Debug, Default)]
+pub struct Batch {
+    pub requests: Vec<Request>,
+    pub lines: u64,
+    pub skipped: BTreeMap<SkipReason, u64>,
+    pub errors: Vec<ParseError>,
+    /// Records dropped by `--dedupe` because their request ID was already seen.
+    pub duplicates: u64,
+    /// Records left out because they fall outside `--since`/`--until`.
+    pub filtered_out: u64,
+    pub time_span: Option<TimeSpan>,
+}
+
+impl Batch {
+    fn merge(&mut self, other: Batch) {
+        self.requests.extend(other.requests);
+        self.lines += other.lines;
+        self.duplicates += other.duplicates;
+        self.filtered_out += other.filtered_out;
+        if let Some(span) = other.time_span {
+            TimeSpan::widen(&mut self.time_span, span);
+        }
+        for (reason, count) in other.skipped {
+            *self.skipped.entry(reason).or_default() += count;
+        }
+        let room = MAX_PARSE_ERRORS.saturating_sub(self.errors.len());
+        self.errors.extend(other.errors.into_iter().take(room));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Abort on the first malformed record instead of skipping it.
+    pub strict: bool,
+    /// Count each request ID only once.
+    pub dedupe: bool,
+    /// Only keep records inside this range (`--since`/`--until`).
+    pub range: TimeRange,
+    /// Only keep requests whose URL path matches (`--filter-regex`).
+    pub path_filter: Option<Regex>,
+    /// Drop requests whose URL path matches any of these (`--exclude`).
+    pub exclude: Vec<Regex>,
+    /// Only keep requests whose query parameters meet all of these (`--param`).
+    pub params: Vec<ParamFilter>,
+    /// Only keep requests for this dataset, as read from the URL path (`--dataset`).
+    pub dataset: Option<String>,
+    /// Only keep requests whose user agent matches (`--ua`).
+    pub user_agent: Option<Regex>,
+    /// Drop requests whose user agent matches any of these (`--exclude-ua`, `--no-bots`).
+    pub exclude_user_agents: Vec<Regex>,
+    pub format: InputFormat,
+    pub json_fields: JsonFields,
+    pub csv_columns: CsvColumns,
+}
+
+pub enum LoadEvent {
+    Progress(LoadProgress),
+    Batch(Batch, LoadProgress),
+    Finished(Result<LoadSummary>),
+}
+
+/// Loads the inputs on a background thread, streaming aggregated batches as they complete.
+pub fn spawn_loader(inputs: Vec<Input>, options: LoadOptions) -> Receiver<LoadEvent> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = load_inputs(&inputs, &options, &tx);
+        let _ = tx.send(LoadEvent::Finished(result));
+    });
+    rx
+}
+
+fn load_inputs(
+    inputs: &[Input],
+    options: &LoadOptions,
+    tx: &Sender<LoadEvent>,
+) -> Result<LoadSummary> {
+    let bytes_read = Arc::new(AtomicU64::new(0));
+    let mut progress = LoadProgress {
+        total_bytes: inputs.iter().map(Input::size).sum(),
+        ..LoadProgress::default()
+    };
+    let mut summary = LoadSummary::default();
+    let _ = tx.send(LoadEvent::Progress(progress));
+    let deduper = options.dedupe.then(Deduper::new);
+    let parser = |line| Parser {
+        line,
+        dedupe: deduper.as_ref(),
+        range: options.range,
+        path_filter: options.path_filter.as_ref(),
+        exclude: &options.exclude,
+        params: &options.params,
+        dataset: options.dataset.as_deref(),
+        user_agent: options.user_agent.as_ref(),
+        exclude_user_agents: &options.exclude_user_agents,
+    };
+    let json = parser(LineParser::Json(Box::new(options.json_fields.clone())));
+
+    for (index, input) in inputs.iter().enumerate() {
+        let source = input.to_string();
+        // Sending fails once the receiver is dropped (e.g. on reload), which stops the load.
+        let on_batch = |mut batch: Batch| {
+            progress.lines += batch.lines;
+            progress.bytes = bytes_read.load(Ordering::Relaxed);
+            summary.duplicates += batch.duplicates;
+            summary.filtered_out += batch.filtered_out;
+            if let Some(span) = batch.time_span {
+                TimeSpan::widen(&mut summary.time_span, span);
+            }
+            for error in &mut batch.errors {
+                error.source.clone_from(&source);
+            }
+            for request in &mut batch.requests {
+                request.input = index;
+            }
+            if options.strict
+                && let Some(error) = batch.errors.first()
+            {
+                anyhow::bail!(
+                    "{}:{}: {}: {}\n    {}",
+                    error.source,
+                    error.line_number,
+                    error.reason.label(),
+                    error.message,
+                    error.snippet
+                );
+            }
+            tx.send(LoadEvent::Batch(batch, progress))
+                .context("load cancelled")
+        };
+        let mapped = match options.format {
+            InputFormat::Ndjson => input
+                .map()
+                .with_context(|| format!("failed to load {input}"))?,
+            InputFormat::Csv | InputFormat::Combined => None,
+        };
+        if let Some(mapped) = mapped {
+            if starts_with_array(&mapped) {
+                bytes_read.fetch_add(mapped.len() as u64, Ordering::Relaxed);
+                ingest_array(&mapped[..], &json, on_batch)
+                    .with_context(|| format!("failed to load {input}"))?;
+            } else {
+                ingest_mapped(&mapped, &json, &bytes_read, on_batch)?;
+            }
+        } else {
+            let mut reader = input
+                .open(bytes_read.clone())
+                .with_context(|| format!("failed to load {input}"))?;
+            match options.format {
+                InputFormat::Ndjson if starts_with_array(reader.fill_buf()?) => {
+                    ingest_array(reader, &json, on_batch)
+                }
+                InputFormat::Ndjson => ingest_lines(reader, &json, 1, on_batch),
+                InputFormat::Combined => {
+                    ingest_lines(reader, &parser(LineParser::Combined), 1, on_batch)
+                }
+                InputFormat::Csv => {
+                    let mut header = Vec::new();
+                    reader.read_until(b'\n', &mut header)?;
+                    CsvLayout::from_header(&into_string(header), &options.csv_columns).and_then(
+                        |layout| {
+                            ingest_lines(reader, &parser(LineParser::Csv(layout)), 2, on_batch)
+                        },
+                    )
+                }
+            }
+            .with_context(|| format!("failed to load {input}"))?;
+        }
+        summary.file_count += 1;
+    }
+
+    summary.line_count = progress.lines;
+    Ok(summary)
+}
+
+fn ingest_lines(
+    reader: impl BufRead,
+    parser: &Parser,
+    mut first_line: u64,
+    mut on_batch: impl FnMut(Batch) -> Result<()>,
+) -> Result<()> {
+    let mut batch = Vec::with_capacity(BATCH_LINES);
+    for line in reader.split(b'\n') {
+        batch.push(into_string(line?));
+        if batch.len() == BATCH_LINES {
+            on_batch(parse_batch(&batch, parser, first_line))?;
+            first_line += batch.len() as u64;
+            batch.clear();
+        }
+    }
+    on_batch(parse_batch(&batch, parser, first_line))
+}
+
+/// Whether the input is a single JSON array of records rather than NDJSON.
+fn starts_with_array(bytes: &[u8]) -> bool {
+    bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[')
+}
+
+/// Streams the elements of a top-level JSON array through the same batch parsing as NDJSON
+/// lines. Element positions (1-based) stand in for line numbers in parse errors.
+fn ingest_array(
+    reader: impl Read,
+    parser: &Parser,
+    on_batch: impl FnMut(Batch) -> Result<()>,
+) -> Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let mut visitor = ArrayVisitor {
+        parser,
+        on_batch,
+        failure: None,
+    };
+    match deserializer.deserialize_seq(&mut visitor) {
+        Ok(()) => Ok(deserializer.end()?),
+        Err(err) => Err(visitor.failure.take().unwrap_or_else(|| err.into())),
+    }
+}
+
+struct ArrayVisitor<'a, F> {
+    parser: &'a Parser<'a>,
+    on_batch: F,
+    failure: Option<anyhow::Error>,
+}
+
+impl<F: FnMut(Batch) -> Result<()>> ArrayVisitor<'_, F> {
+    fn flush<E: de::Error>(&mut self, batch: &[String], first: u64) -> Result<(), E> {
+        (self.on_batch)(parse_batch(batch, self.parser, first)).map_err(|err| {
+            self.failure = Some(err);
+            E::custom("load aborted")
+        })
+    }
+}
+
+impl<'de, F: FnMut(Batch) -> Result<()>> Visitor<'de> for &mut ArrayVisitor<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of log records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut batch = Vec::with_capacity(BATCH_LINES);
+        let mut first = 1;
+        while let Some(element) = seq.next_element::<Box<RawValue>>()? {
+            batch.push(element.get().to_string());
+            if batch.len() == BATCH_LINES {
+                self.flush(&batch, first)?;
+                first += batch.len() as u64;
+                batch.clear();
+            }
+        }
+        self.flush(&batch, first)
+    }
+}
+
+/// Reads the given lines (or array elements, for JSON array inputs) of a local input again, as
+/// `(line number, text)` pairs. Remote inputs aren't downloaded a second time.
+pub fn read_raw_lines(
+    input: &Input,
+    format: InputFormat,
+    line_numbers: &[u64],
+) -> Result<Vec<(u64, String)>> {
+    match input {
+        Input::Path(path) if !source::is_remote(path) => {}
+        _ => anyhow::bail!("raw records are only re-read from local files, not {input}"),
+    }
+    let wanted: HashSet<u64> = line_numbers.iter().copied().collect();
+    let last = line_numbers.iter().copied().max().unwrap_or(0);
+    let mut reader = input
+        .open(Arc::new(AtomicU64::new(0)))
+        .with_context(|| format!("failed to open {input}"))?;
+    let mut found = Vec::new();
+    if format == InputFormat::Ndjson && starts_with_array(reader.fill_buf()?) {
+        let elements: Vec<Box<RawValue>> = serde_json::from_reader(reader)?;
+        for (idx, element) in elements.into_iter().enumerate() {
+            if wanted.contains(&(idx as u64 + 1)) {
+                found.push((idx as u64 + 1, element.get().to_string()));
+            }
+        }
+        return Ok(found);
+    }
+    for (idx, line) in reader.split(b'\n').enumerate() {
+        let line_number = idx as u64 + 1;
+        if line_number > last {
+            break;
+        }
+        let line = line?;
+        if wanted.contains(&line_number) {
+            found.push((line_number, into_string(line)));
+        }
+    }
+    Ok(found)
+}
+
+fn into_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
+/// Parses a memory-mapped file in newline-aligned chunks, borrowing each line from the mapping.
+fn ingest_mapped(
+    data: &[u8],
+    parser: &Parser,
+    bytes_read: &AtomicU64,
+    mut on_batch: impl FnMut(Batch) -> Result<()>,
+) -> Result<()> {
+    let mut offset = 0;
+    let mut first_line = 1;
+    while offset < data.len() {
+        let mut end = (offset + MAPPED_CHUNK_BYTES).min(data.len());
+        end = match data[end..].iter().position(|&b| b == b'\n') {
+            Some(newline) => end + newline + 1,
+            None => data.len(),
+        };
+        let chunk = &data[offset..end];
+        let lines: Vec<Cow<str>> = chunk
+            .strip_suffix(b"\n")
+            .unwrap_or(chunk)
+            .split(|&b| b == b'\n')
+            .map(String::from_utf8_lossy)
+            .collect();
+        bytes_read.fetch_add((end - offset) as u64, Ordering::Relaxed);
+        on_batch(parse_batch(&lines, parser, first_line))?;
+        first_line += lines.len() as u64;
+        offset = end;
+    }
+    Ok(())
+}
+
+/// Parses a run of physical lines starting at `first_line`, ignoring blank ones.
+fn parse_batch<S: AsRef<str> + Sync>(lines: &[S], parser: &Parser, first_line: u64) -> Batch {
+    lines
+        .par_iter()
+        .enumerate()
+        .fold(Batch::default, |mut batch, (idx, line)| {
+            let line = line.as_ref();
+            if line.trim().is_empty() {
+                return batch;
+            }
+            batch.lines += 1;
+            let line_number = first_line + idx as u64;
+            if let Err((reason, message)) = ingest_line(line, line_number, parser, &mut batch) {
+                *batch.skipped.entry(reason).or_default() += 1;
+                if batch.errors.len() < MAX_PARSE_ERRORS {
+                    batch.errors.push(ParseError {
+                        source: String::new(),
+                        line_number,
+                        reason,
+                        message,
+                        snippet: line.chars().take(ERROR_SNIPPET_CHARS).collect(),
+                    });
+                }
+            }
+            batch
+        })
+        .reduce(Batch::default, |mut left, right| {
+            left.merge(right);
+            left
+        })
+}
+
+pub fn merge_stats(into: &mut HashMap<String, PathStats>, from: HashMap<String, PathStats>) {
+    for (path, stats) in from {
+        match into.get_mut(&path) {
+            Some(entry) => {
+                entry.request_count += stats.request_count;
+                entry.request_size_sum += stats.request_size_sum;
+                entry.bandwidth_sum += stats.bandwidth_sum;
+                entry.cache_lookups += stats.cache_lookups;
+                entry.cache_hits += stats.cache_hits;
+                entry.unoptimized_bandwidth += stats.unoptimized_bandwidth;
+                entry.response_sizes.merge(&stats.response_sizes);
+                entry.timed_requests += stats.timed_requests;
+                entry.duration_sum += stats.duration_sum;
+                entry.durations.merge(&stats.durations);
+                entry.variants.extend(stats.variants);
+                for (status, count) in stats.status_counts {
+                    *entry.status_counts.entry(status).or_default() += count;
+                }
+            }
+            None => {
+                into.insert(path, stats);
+            }
+        }
+    }
+}
+
+/// A half-open range of time; records without a timestamp only fall inside an unbounded one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub since: Option<Timestamp>,
+    pub until: Option<Timestamp>,
+}
+
+impl TimeRange {
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, timestamp: Option<Timestamp>) -> bool {
+        match timestamp {
+            Some(timestamp) => {
+                self.since.is_none_or(|since| timestamp >= since)
+                    && self.until.is_none_or(|until| timestamp < until)
+            }
+            None => self.is_unbounded(),
+        }
+    }
+}
+
+/// A condition on a URL query parameter, written `w` (set), `auto=format` (set to that value),
+/// or either prefixed with `!` to negate it (`!auto=format` also holds when `auto` is missing).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamFilter {
+    name: String,
+    value: Option<String>,
+    negate: bool,
+}
+
+impl ParamFilter {
+    pub fn matches(&self, url: &Url) -> bool {
+        let found = url.query_pairs().any(|(name, value)| {
+            name == self.name.as_str() && self.value.as_deref().is_none_or(|wanted| value == wanted)
+        });
+        found != self.negate
+    }
+}
+
+impl FromStr for ParamFilter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (negate, condition) = match text.strip_prefix('!') {
+            Some(condition) => (true, condition),
+            None => (false, text),
+        };
+        let (name, value) = match condition.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (condition, None),
+        };
+        if name.is_empty() {
+            return Err(format!(
+                "expected a parameter like `w`, `auto=format`, or `!auto`, got `{text}`"
+            ));
+        }
+        Ok(ParamFilter {
+            name: name.to_string(),
+            value,
+            negate,
+        })
+    }
+}
+
+impl fmt::Display for ParamFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negate {
+            f.write_str("!")?;
+        }
+        f.write_str(&self.name)?;
+        match &self.value {
+            Some(value) => write!(f, "={value}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A record with its URL resolved, kept in memory so views can re-aggregate it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub url: Url,
+    pub record: Record,
+    /// Position of the input the record was read from, and its line (or array element) number
+    /// there, so [`read_raw_lines`] can fetch it again.
+    pub input: usize,
+    pub line_number: u64,
+}
+
+impl Request {
+    pub fn path(&self) -> &str {
+        match self.url.path() {
+            "" => "/",
+            path => path,
+        }
+    }
+
+    /// What the request is aggregated under in [`aggregate`]: `/images/:id` or `/files/:id` for
+    /// assets, so every path and transform of one asset shares a row, and the
+    /// [`Request::path_key`] for everything else.
+    pub fn stats_key(&self) -> String {
+        match asset_id(self.path()) {
+            Some((kind, id)) => format!("/{kind}/{id}"),
+            None => self.path_key(),
+        }
+    }
+
+    /// The path, followed by the normalized query for GROQ queries so that each distinct query
+    /// gets its own row.
+    pub fn path_key(&self) -> String {
+        match groq::query_text(&self.url) {
+            Some(query) => format!("{}?query={query}", self.path()),
+            None => self.path().to_string(),
+        }
+    }
+
+    /// Whether the request is for a CDN image or file.
+    pub fn is_asset(&self) -> bool {
+        asset_id(self.path()).is_some()
+    }
+
+    /// How an asset's requests are told apart: images by their transform parameters, files by
+    /// their query string.
+    pub fn variant_key(&self) -> String {
+        if image::is_image(&self.url) {
+            return image::transform_signature(&self.url);
+        }
+        match self.url.query() {
+            Some(query) if !query.is_empty() => format!("?{query}"),
+            _ => "(no query)".to_string(),
+        }
+    }
+}
+
+/// The kind (`images` or `files`) and ID of a CDN asset path
+/// (`/images/:projectId/:dataset/:id-:dimensions.:ext`, optionally followed by a vanity file
+/// name). The ID is the hash before the dimensions and extension.
+fn asset_id(path: &str) -> Option<(&str, &str)> {
+    let mut parts = path.split('/').filter(|s| !s.is_empty());
+    let kind = parts
+        .next()
+        .filter(|kind| matches!(*kind, "images" | "files"))?;
+    let file = parts.nth(2)?;
+    let stem = file.split_once('.').map_or(file, |(stem, _)| stem);
+    let id = match kind {
+        "images" => stem.split('-').next().unwrap_or(stem),
+        _ => stem,
+    };
+    Some((kind, id)).filter(|(_, id)| !id.is_empty())
+}
+
+/// The IDs a document lookup such as `/v2021-10-21/data/doc/production/abc,def` asked for,
+/// separated by commas.
+pub fn looked_up_document_ids(path: &str) -> Option<&str> {
+    let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match parts.as_slice() {
+        [_, "data", "doc", _, ids, ..] => Some(ids),
+        _ => None,
+    }
+}
+
+/// The `_id` of the asset document behind a CDN asset path, such as
+/// `image-a1b2c3-1200x800-webp`, for looking the asset up in the Studio or with GROQ.
+pub fn asset_document_id(path: &str) -> Option<String> {
+    let mut parts = path.split('/').filter(|s| !s.is_empty());
+    let kind = match parts.next()? {
+        "images" => "image",
+        "files" => "file",
+        _ => return None,
+    };
+    let file = parts.nth(2)?;
+    Some(format!("{kind}-{}", file.replace('.', "-")))
+}
+
+/// Aggregates the requests matching `include` by [`Request::stats_key`], in parallel.
+pub fn aggregate(
+    requests: &[Request],
+    include: impl Fn(&Request) -> bool + Sync,
+) -> HashMap<String, PathStats> {
+    aggregate_by(requests, include, Request::stats_key)
+}
+
+/// Aggregates the requests matching `include` by an arbitrary key, in parallel. The key is
+/// stored in [`PathStats::path`].
+pub fn aggregate_by(
+    requests: &[Request],
+    include: impl Fn(&Request) -> bool + Sync,
+    key: impl Fn(&Request) -> String + Sync,
+) -> HashMap<String, PathStats> {
+    requests
+        .par_iter()
+        .filter(|request| include(request))
+        .fold(HashMap::new, |mut map, request| {
+            add_request(&mut map, key(request), request);
+            map
+        })
+        .reduce(HashMap::new, |mut left, right| {
+            merge_stats(&mut left, right);
+            left
+        })
+}
+
+fn add_request(map: &mut HashMap<String, PathStats>, key: String, request: &Request) {
+    let record = &request.record;
+    let entry = map.entry(key).or_insert_with_key(|key| PathStats {
+        path: key.clone(),
+        sample_url: request.url.to_string(),
+        request_count: 0,
+        request_size_sum: 0,
+        bandwidth_sum: 0,
+        status_counts: BTreeMap::new(),
+        cache_lookups: 0,
+        cache_hits: 0,
+        unoptimized_bandwidth: 0,
+        response_sizes: Distribution::default(),
+        timed_requests: 0,
+        duration_sum: 0,
+        durations: Distribution::default(),
+        variants: HashSet::new(),
+    });
+
+    entry.request_count += 1;
+
+    if request.is_asset() {
+        entry.variants.insert(request.variant_key());
+    }
+
+    if let Some(status) = record.status {
+        *entry.status_counts.entry(status).or_default() += 1;
+    }
+
+    if let Some(hit) = record.cache_hit {
+        entry.cache_lookups += 1;
+        entry.cache_hits += u64::from(hit);
+    }
+
+    if let Some(duration) = record.duration_ms {
+        entry.timed_requests += 1;
+        entry.duration_sum += duration;
+        entry.durations.add(duration);
+    }
+
+    if let Some(req) = record.request_size {
+        entry.request_size_sum += req;
+    }
+
+    if let Some(resp) = record.response_size {
+        entry.bandwidth_sum += resp;
+        entry.response_sizes.add(resp);
+        if image::is_unoptimized(&request.url) {
+            entry.unoptimized_bandwidth += resp;
+        }
+    }
+}
+
+/// Requests and bandwidth within one time bucket.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrafficBucket {
+    pub requests: u64,
+    pub bandwidth: u64,
+}
+
+pub const HOUR_SECONDS: i64 = 60 * 60;
+
+/// Buckets the requests matching `include` by the UTC hour they were made in, keyed by the
+/// hour's start in Unix seconds. Requests without a timestamp are left out.
+pub fn bucket_by_hour(
+    requests: &[Request],
+    include: impl Fn(&Request) -> bool + Sync,
+) -> BTreeMap<i64, TrafficBucket> {
+    requests
+        .par_iter()
+        .filter(|request| include(request))
+        .fold(BTreeMap::new, |mut buckets, request| {
+            if let Some(timestamp) = request.record.timestamp {
+                let second = timestamp.as_second();
+                let bucket: &mut TrafficBucket = buckets
+                    .entry(second - second.rem_euclid(HOUR_SECONDS))
+                    .or_default();
+                bucket.requests += 1;
+                bucket.bandwidth += request.record.response_size.unwrap_or(0);
+            }
+            buckets
+        })
+        .reduce(BTreeMap::new, |mut left, right| {
+            merge_buckets(&mut left, right);
+            left
+        })
+}
+
+pub fn merge_buckets(into: &mut BTreeMap<i64, TrafficBucket>, from: BTreeMap<i64, TrafficBucket>) {
+    for (start, bucket) in from {
+        let entry = into.entry(start).or_default();
+        entry.requests += bucket.requests;
+        entry.bandwidth += bucket.bandwidth;
+    }
+}
+
+/// Counts the requests matching `include` per [`Request::stats_key`], split into `bins` equal
+/// slices of the `start..end` range (Unix seconds).
+pub fn path_trends(
+    requests: &[Request],
+    include: impl Fn(&Request) -> bool + Sync,
+    start: i64,
+    end: i64,
+    bins: usize,
+) -> HashMap<String, Vec<u64>> {
+    let span = (end - start).max(1);
+    requests
+        .par_iter()
+        .filter(|request| include(request))
+        .fold(
+            HashMap::new,
+            |mut trends: HashMap<String, Vec<u64>>, request| {
+                if let Some(timestamp) = request.record.timestamp {
+                    let offset = (timestamp.as_second() - start).clamp(0, span - 1);
+                    let bin = (offset as i128 * bins as i128 / span as i128) as usize;
+                    let trend = trends
+                        .entry(request.stats_key())
+                        .or_insert_with(|| vec![0; bins]);
+                    trend[bin] += 1;
+                }
+                trends
+            },
+        )
+        .reduce(HashMap::new, |mut left, right| {
+            for (path, trend) in right {
+                match left.get_mut(&path) {
+                    Some(existing) => {
+                        for (into, count) in existing.iter_mut().zip(trend) {
+                            *into += count;
+                        }
+                    }
+                    None => {
+                        left.insert(path, trend);
+                    }
+                }
+            }
+            left
+        })
+}
+
+/// How the lines of one input are parsed.
+struct Parser<'a> {
+    line: LineParser,
+    dedupe: Option<&'a Deduper>,
+    range: TimeRange,
+    path_filter: Option<&'a Regex>,
+    exclude: &'a [Regex],
+    params: &'a [ParamFilter],
+    dataset: Option<&'a str>,
+    user_agent: Option<&'a Regex>,
+    exclude_user_agents: &'a [Regex],
+}
+
+impl Parser<'_> {
+    /// Whether a request's user agent passes `--ua` and `--exclude-ua`. Requests without one
+    /// only fail `--ua`.
+    fn keeps_user_agent(&self, agent: Option<&str>) -> bool {
+        match agent {
+            Some(agent) => {
+                self.user_agent
+                    .is_none_or(|pattern| pattern.is_match(agent))
+                    && !self
+                        .exclude_user_agents
+                        .iter()
+                        .any(|pattern| pattern.is_match(agent))
+            }
+            None => self.user_agent.is_none(),
+        }
+    }
+}
+
+/// Request IDs seen so far in a load, sharded so parallel batches rarely contend.
+struct Deduper {
+    shards: Vec<Mutex<HashSet<u64>>>,
+}
+
+impl Deduper {
+    fn new() -> Self {
+        Self {
+            shards: (0..DEDUPE_SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Returns whether `id` is being seen for the first time.
+    fn insert(&self, id: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let key = hasher.finish();
+        let shard = &self.shards[key as usize % DEDUPE_SHARDS];
+        shard
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(key)
+    }
+}
+
+fn ingest_line(
+    line: &str,
+    line_number: u64,
+    parser: &Parser,
+    batch: &mut Batch,
+) -> ParseResult<()> {
+    let record = parser.line.parse(line)?;
+    let url = if record.url.starts_with('/') {
+        Url::parse(CDN_BASE_URL).and_then(|base| base.join(&record.url))
+    } else {
+        Url::parse(&record.url)
+    }
+    .map_err(|err| (SkipReason::InvalidUrl, err.to_string()))?;
+
+    if !parser.range.contains(record.timestamp)
+        || parser
+            .path_filter
+            .is_some_and(|filter| !filter.is_match(url.path()))
+        || parser
+            .exclude
+            .iter()
+            .any(|pattern| pattern.is_match(url.path()))
+        || !parser.params.iter().all(|param| param.matches(&url))
+        || parser
+            .dataset
+            .is_some_and(|dataset| group::dataset(url.path()) != Some(dataset))
+        || !parser.keeps_user_agent(record.user_agent.as_deref())
+    {
+        batch.filtered_out += 1;
+        return Ok(());
+    }
+
+    if let (Some(dedupe), Some(id)) = (parser.dedupe, &record.request_id)
+        && !dedupe.insert(id)
+    {
+        batch.duplicates += 1;
+        return Ok(());
+    }
+
+    if let Some(timestamp) = record.timestamp {
+        TimeSpan::widen(
+            &mut batch.time_span,
+            TimeSpan {
+                first: timestamp,
+                last: timestamp,
+            },
+        );
+    }
+
+    batch.requests.push(Request {
+        url,
+        record,
+        input: 0,
+        line_number,
+    });
+    Ok(())
+}
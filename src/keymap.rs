@@ -0,0 +1,562 @@
+use crate::{RequestType, SortField};
+use anyhow::{Context, Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Something a key does in the TUI outside of typing into a prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Help,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    /// Prompts for a row number to select.
+    GoToRow,
+    /// Marks or unmarks the selected row, to total the marked rows.
+    Mark,
+    /// Pins the selected row to the top of the table, or unpins it.
+    Pin,
+    /// Selects the first row.
+    Top,
+    /// Selects the last row.
+    Bottom,
+    PreviousView,
+    NextView,
+    CycleView,
+    /// Closes a popup or drill-down, or clears the filter bar.
+    Back,
+    /// Opens the selected row's variants, or its URL in the browser.
+    Open,
+    Filter,
+    Find,
+    NextPreset,
+    Focus,
+    RemoveFilter,
+    MinBandwidth,
+    Referer,
+    ToggleType(RequestType),
+    CopyUrl,
+    CopyId,
+    CopyTable,
+    Sort(SortField),
+    /// Waits for a sort key, to break ties in the sort with that field.
+    ThenSort,
+    Reload,
+    Errors,
+    TimeRange,
+    Granularity,
+    Trends,
+    Percentiles,
+    /// Opens the menu of columns to show or hide.
+    Columns,
+    /// Switches sizes between binary and decimal units.
+    Units,
+    /// Switches between exact and abbreviated counts and sizes.
+    ExactNumbers,
+    /// Scrolls the ID column's text to read the start or end of long IDs.
+    ScrollLeft,
+    ScrollRight,
+    Histogram,
+    RawRecords,
+    SaveRecords,
+    ExportCsv,
+    Screenshot,
+}
+
+/// Every action, with its name in the config file's `[keys]` table and its default keys.
+const BINDINGS: [(&str, Action, &[Binding]); 64] = [
+    ("quit", Action::Quit, &[Binding::char('q')]),
+    ("help", Action::Help, &[Binding::char('?')]),
+    (
+        "up",
+        Action::Up,
+        &[Binding::code(KeyCode::Up), Binding::char('k')],
+    ),
+    (
+        "down",
+        Action::Down,
+        &[Binding::code(KeyCode::Down), Binding::char('j')],
+    ),
+    ("page-up", Action::PageUp, &[Binding::code(KeyCode::PageUp)]),
+    (
+        "page-down",
+        Action::PageDown,
+        &[Binding::code(KeyCode::PageDown)],
+    ),
+    ("half-page-up", Action::HalfPageUp, &[Binding::ctrl('u')]),
+    (
+        "half-page-down",
+        Action::HalfPageDown,
+        &[Binding::ctrl('d')],
+    ),
+    ("go-to-row", Action::GoToRow, &[Binding::char(':')]),
+    ("mark", Action::Mark, &[Binding::char(' ')]),
+    ("pin", Action::Pin, &[Binding::char('B')]),
+    (
+        "top",
+        Action::Top,
+        &[Binding::code(KeyCode::Home), Binding::twice('g')],
+    ),
+    (
+        "bottom",
+        Action::Bottom,
+        &[Binding::code(KeyCode::End), Binding::char('G')],
+    ),
+    (
+        "previous-view",
+        Action::PreviousView,
+        &[Binding::code(KeyCode::Left), Binding::char('h')],
+    ),
+    (
+        "next-view",
+        Action::NextView,
+        &[Binding::code(KeyCode::Right), Binding::char('l')],
+    ),
+    (
+        "cycle-view",
+        Action::CycleView,
+        &[Binding::code(KeyCode::Tab)],
+    ),
+    ("back", Action::Back, &[Binding::code(KeyCode::Esc)]),
+    ("open", Action::Open, &[Binding::code(KeyCode::Enter)]),
+    ("filter", Action::Filter, &[Binding::char('/')]),
+    ("find", Action::Find, &[Binding::char('f')]),
+    ("next-preset", Action::NextPreset, &[Binding::char('P')]),
+    ("focus", Action::Focus, &[Binding::char('=')]),
+    ("remove-filter", Action::RemoveFilter, &[Binding::char('-')]),
+    ("min-bandwidth", Action::MinBandwidth, &[Binding::char('m')]),
+    ("referer", Action::Referer, &[Binding::char('x')]),
+    (
+        "toggle-images",
+        Action::ToggleType(RequestType::Image),
+        &[Binding::char('I')],
+    ),
+    (
+        "toggle-files",
+        Action::ToggleType(RequestType::File),
+        &[Binding::char('F')],
+    ),
+    (
+        "toggle-queries",
+        Action::ToggleType(RequestType::Query),
+        &[Binding::char('Q')],
+    ),
+    (
+        "toggle-other",
+        Action::ToggleType(RequestType::Other),
+        &[Binding::char('O')],
+    ),
+    ("copy-url", Action::CopyUrl, &[Binding::char('y')]),
+    ("copy-id", Action::CopyId, &[Binding::char('Y')]),
+    ("copy-table", Action::CopyTable, &[Binding::char('C')]),
+    (
+        "sort-id",
+        Action::Sort(SortField::Path),
+        &[Binding::char('d')],
+    ),
+    (
+        "sort-ext",
+        Action::Sort(SortField::Ext),
+        &[Binding::char('e')],
+    ),
+    (
+        "sort-requests",
+        Action::Sort(SortField::Requests),
+        &[Binding::char('r')],
+    ),
+    (
+        "sort-avg-size",
+        Action::Sort(SortField::AvgRequestSize),
+        &[Binding::char('s')],
+    ),
+    (
+        "sort-bandwidth",
+        Action::Sort(SortField::Bandwidth),
+        &[Binding::char('b')],
+    ),
+    (
+        "sort-unoptimized",
+        Action::Sort(SortField::Unoptimized),
+        &[Binding::char('o')],
+    ),
+    (
+        "sort-variants",
+        Action::Sort(SortField::Variants),
+        &[Binding::char('a')],
+    ),
+    (
+        "sort-cache-hit",
+        Action::Sort(SortField::CacheHit),
+        &[Binding::char('T')],
+    ),
+    (
+        "sort-latency",
+        Action::Sort(SortField::Latency),
+        &[Binding::char('n')],
+    ),
+    (
+        "sort-request-size",
+        Action::Sort(SortField::RequestSize),
+        &[Binding::char('z')],
+    ),
+    (
+        "sort-client-errors",
+        Action::Sort(SortField::ClientErrors),
+        &[Binding::char('4')],
+    ),
+    (
+        "sort-server-errors",
+        Action::Sort(SortField::ServerErrors),
+        &[Binding::char('5')],
+    ),
+    (
+        "sort-error-rate",
+        Action::Sort(SortField::ErrorRate),
+        &[Binding::char('%')],
+    ),
+    (
+        "sort-bandwidth-change",
+        Action::Sort(SortField::BandwidthDelta),
+        &[Binding::char('w')],
+    ),
+    (
+        "sort-change",
+        Action::Sort(SortField::Change),
+        &[Binding::char('N')],
+    ),
+    ("then-sort", Action::ThenSort, &[Binding::char(',')]),
+    ("reload", Action::Reload, &[Binding::char('R')]),
+    ("errors", Action::Errors, &[Binding::char('E')]),
+    ("time-range", Action::TimeRange, &[Binding::char('t')]),
+    ("granularity", Action::Granularity, &[Binding::char('H')]),
+    ("trends", Action::Trends, &[Binding::char('v')]),
+    ("percentiles", Action::Percentiles, &[Binding::char('p')]),
+    ("columns", Action::Columns, &[Binding::char('c')]),
+    ("units", Action::Units, &[Binding::char('u')]),
+    ("exact-numbers", Action::ExactNumbers, &[Binding::char('#')]),
+    ("scroll-left", Action::ScrollLeft, &[Binding::char('<')]),
+    ("scroll-right", Action::ScrollRight, &[Binding::char('>')]),
+    ("histogram", Action::Histogram, &[Binding::char('D')]),
+    ("raw-records", Action::RawRecords, &[Binding::char('i')]),
+    ("save-records", Action::SaveRecords, &[Binding::char('L')]),
+    ("export-csv", Action::ExportCsv, &[Binding::char('X')]),
+    ("screenshot", Action::Screenshot, &[Binding::char('S')]),
+];
+
+/// Keys for an action in the config file: one, or a list of them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn names(&self) -> &[String] {
+        match self {
+            KeyList::One(name) => std::slice::from_ref(name),
+            KeyList::Many(names) => names,
+        }
+    }
+}
+
+/// A key with Ctrl or Alt held. Shift is left out, as it's already in the character typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const fn char(ch: char) -> Self {
+        Key::code(KeyCode::Char(ch))
+    }
+
+    const fn code(code: KeyCode) -> Self {
+        Key {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    fn of(event: KeyEvent) -> Self {
+        Key {
+            code: event.code,
+            modifiers: event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    /// Parses a key as written in the config file: a character such as `j` or `%`, a name
+    /// such as `Down`, `PageUp`, `Space`, or `F5`, or either after `Ctrl-` or `Alt-`.
+    fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(after) = strip_prefix_ignore_case(rest, "ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = after;
+            } else if let Some(after) = strip_prefix_ignore_case(rest, "alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = after;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(number @ 1..=12) => KeyCode::F(number),
+                    _ => bail!("unknown key `{text}`"),
+                },
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+
+    /// How the key is shown in the help and hints.
+    fn label(self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(ch) => ch.to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(number) => format!("F{number}"),
+            code => format!("{code:?}"),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt-");
+        }
+        label + &name
+    }
+}
+
+/// A key, or two pressed one after the other like Vim's `gg`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Binding {
+    first: Key,
+    then: Option<Key>,
+}
+
+impl Binding {
+    const fn char(ch: char) -> Self {
+        Binding::code(KeyCode::Char(ch))
+    }
+
+    const fn code(code: KeyCode) -> Self {
+        Binding {
+            first: Key::code(code),
+            then: None,
+        }
+    }
+
+    const fn ctrl(ch: char) -> Self {
+        Binding {
+            first: Key {
+                code: KeyCode::Char(ch),
+                modifiers: KeyModifiers::CONTROL,
+            },
+            then: None,
+        }
+    }
+
+    const fn twice(ch: char) -> Self {
+        Binding {
+            first: Key::char(ch),
+            then: Some(Key::char(ch)),
+        }
+    }
+
+    /// Parses one key, or two separated by a space such as `g g` or `Ctrl-x Ctrl-s`.
+    fn parse(text: &str) -> Result<Self> {
+        let mut keys = text.split_whitespace();
+        let (Some(first), then, None) = (keys.next(), keys.next(), keys.next()) else {
+            bail!("`{text}` should be one key, or two separated by a space");
+        };
+        Ok(Binding {
+            first: Key::parse(first)?,
+            then: then.map(Key::parse).transpose()?,
+        })
+    }
+
+    /// Whether pressing one would get in the way of the other: the same keys, or one key
+    /// that another starts with.
+    fn overlaps(self, other: Binding) -> bool {
+        self.first == other.first
+            && (self.then == other.then || self.then.is_none() || other.then.is_none())
+    }
+
+    /// How the keys are shown in the help and hints, such as `gg` or `Ctrl-x Ctrl-s`.
+    fn label(self) -> String {
+        match self.then {
+            None => self.first.label(),
+            Some(then) => {
+                let (first, then) = (self.first.label(), then.label());
+                if first.chars().count() == 1 && then.chars().count() == 1 {
+                    first + &then
+                } else {
+                    format!("{first} {then}")
+                }
+            }
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    (head.eq_ignore_ascii_case(prefix) && text.len() > prefix.len()).then(|| &text[prefix.len()..])
+}
+
+/// Which action each key triggers, from the defaults and the config file's `[keys]` table.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    actions: HashMap<Binding, Action>,
+    /// The keys bound to each action, in `BINDINGS` order.
+    keys: Vec<(Action, Vec<Binding>)>,
+    /// The first of two keys bound together, once pressed, until the next key press.
+    pending: Option<Key>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::from_keys(
+            BINDINGS
+                .iter()
+                .map(|(_, action, keys)| (*action, keys.to_vec()))
+                .collect(),
+        )
+    }
+}
+
+impl Keymap {
+    /// The default bindings with the config file's applied. An action given there keeps only
+    /// the keys listed for it, and those keys no longer do whatever they did by default.
+    pub fn new(overrides: &BTreeMap<String, KeyList>) -> Result<Self> {
+        let mut keys: Vec<(Action, Vec<Binding>)> = BINDINGS
+            .iter()
+            .map(|(_, action, keys)| (*action, keys.to_vec()))
+            .collect();
+        let mut assigned: Vec<(Binding, &str)> = Vec::new();
+        for (name, list) in overrides {
+            let index = BINDINGS
+                .iter()
+                .position(|(action_name, _, _)| action_name == name)
+                .with_context(|| format!("unknown action `{name}` in [keys]"))?;
+            let mut bound = Vec::new();
+            for text in list.names() {
+                let key = Binding::parse(text).with_context(|| format!("in [keys] {name}"))?;
+                if let Some((_, other)) = assigned.iter().find(|(other, _)| other.overlaps(key)) {
+                    bail!("`{text}` is bound to both {other} and {name} in [keys]");
+                }
+                assigned.push((key, name));
+                bound.push(key);
+            }
+            for (_, keys) in &mut keys {
+                keys.retain(|key| !bound.iter().any(|other| other.overlaps(*key)));
+            }
+            keys[index].1 = bound;
+        }
+        Ok(Keymap::from_keys(keys))
+    }
+
+    fn from_keys(keys: Vec<(Action, Vec<Binding>)>) -> Self {
+        let actions = keys
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(|key| (*key, *action)))
+            .collect();
+        Keymap {
+            actions,
+            keys,
+            pending: None,
+        }
+    }
+
+    /// What a key press does, if anything. The first of two keys bound together does nothing
+    /// until the second is pressed; any other key after it does what it would alone.
+    pub fn action(&mut self, event: KeyEvent) -> Option<Action> {
+        let key = Key::of(event);
+        if let Some(first) = self.pending.take()
+            && let Some(action) = self.actions.get(&Binding {
+                first,
+                then: Some(key),
+            })
+        {
+            return Some(*action);
+        }
+        if self
+            .actions
+            .keys()
+            .any(|binding| binding.first == key && binding.then.is_some())
+        {
+            self.pending = Some(key);
+            return None;
+        }
+        self.actions
+            .get(&Binding {
+                first: key,
+                then: None,
+            })
+            .copied()
+    }
+
+    /// The keys for an action as shown in the help, such as `↓/j`.
+    pub fn label(&self, action: Action) -> String {
+        let labels: Vec<String> = self.bound(action).iter().map(|key| key.label()).collect();
+        if labels.is_empty() {
+            "unbound".to_string()
+        } else {
+            labels.join("/")
+        }
+    }
+
+    /// The first plain character bound to an action, underlined in a column heading for the
+    /// sort keys.
+    pub fn char(&self, action: Action) -> Option<char> {
+        self.bound(action).iter().find_map(|key| match key {
+            Binding {
+                first:
+                    Key {
+                        code: KeyCode::Char(ch),
+                        modifiers: KeyModifiers::NONE,
+                    },
+                then: None,
+            } => Some(*ch),
+            _ => None,
+        })
+    }
+
+    fn bound(&self, action: Action) -> &[Binding] {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+}
@@ -1,50 +1,374 @@
+mod budget;
+mod cli;
+mod columnar;
+mod config;
+mod database;
+mod distribution;
+mod export;
+mod fuzzy;
+mod groq;
+mod group;
+mod image;
+mod ingest;
+mod keymap;
+mod record;
+mod report;
+mod screenshot;
+mod source;
+mod state;
+mod theme;
+mod units;
+
 use anyhow::{Context, Result};
+use base64::prelude::{BASE64_STANDARD, Engine};
+use budget::Budgets;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{Config, FilterPreset, PresetType};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use distribution::Distribution;
+use export::{ExportFormat, RecordFormat};
+use group::Dimension;
+use ingest::{
+    BOT_USER_AGENTS, HOUR_SECONDS, LoadEvent, LoadOptions, LoadProgress, LoadSummary,
+    MAX_PARSE_ERRORS, ParseError, PathStats, Request, TimeRange, TimeSpan, TrafficBucket,
+    aggregate, aggregate_by, asset_document_id, bucket_by_hour, looked_up_document_ids,
+    merge_buckets, merge_stats, path_trends, read_raw_lines, spawn_loader,
+};
+use jiff::{SignedDuration, Timestamp};
+use keymap::{Action, Keymap};
 use ratatui::{
-    prelude::{Alignment, Constraint, CrosstermBackend, Frame, Layout, Rect, Terminal},
-    style::{Color, Modifier, Style},
+    buffer::Buffer,
+    prelude::{Alignment, Constraint, CrosstermBackend, Direction, Frame, Layout, Rect, Terminal},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{
-        Block, Borders, Cell, Clear, List, ListItem, Padding, Paragraph, Row, Table, TableState,
-        Tabs, Wrap,
+        Bar, BarChart, BarGroup, Block, Borders, Cell, Clear, List, ListItem, Padding, Paragraph,
+        Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState, Tabs, Wrap,
     },
 };
-use serde_json::Value;
+use record::{CsvColumns, InputFormat, SkipReason};
+use regex::{Regex, RegexBuilder};
+use report::ReportFormat;
+use source::Input;
+use state::{SavedFilters, Session, State};
 use std::{
-    collections::HashMap,
-    env,
-    fs::File,
-    io::{self, BufRead, BufReader, Stderr},
-    time::Duration,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufWriter, Stderr, Write},
+    path::Path,
+    process,
+    sync::{OnceLock, mpsc::Receiver},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
+use theme::theme;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use units::ByteUnits;
 use url::Url;
 
-#[derive(Debug, Clone)]
-struct PathStats {
-    path: String,
-    sample_url: String,
-    request_count: u64,
-    request_size_sum: u64,
-    bandwidth_sum: u64,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 enum SortField {
+    #[value(name = "id")]
     Path,
     Ext,
     Requests,
+    #[value(name = "avg-size")]
     AvgRequestSize,
     Bandwidth,
+    Unoptimized,
+    Variants,
+    CacheHit,
+    Latency,
+    // Only `diff` has a baseline to compare with.
+    #[value(skip)]
+    BandwidthDelta,
+    #[value(skip)]
+    Change,
+    RequestSize,
+    ClientErrors,
+    ServerErrors,
+    ErrorRate,
+}
+
+impl SortField {
+    /// Names sort A to Z when first picked, and everything else largest first.
+    fn descending_by_default(self) -> bool {
+        !matches!(self, SortField::Path | SortField::Ext)
+    }
+}
+
+/// How rows are ordered: by one field, with ties broken by a second when one is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SortOrder {
+    field: SortField,
+    descending: bool,
+    then: Option<(SortField, bool)>,
+}
+
+impl SortOrder {
+    fn by(field: SortField, descending: bool) -> Self {
+        SortOrder {
+            field,
+            descending,
+            then: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ViewMode {
     Path,
     Type,
+    Time,
+    Heatmap,
+    Slow,
+    Errors,
+    Group(Dimension),
+}
+
+impl ViewMode {
+    const ALL: [ViewMode; 15] = [
+        ViewMode::Path,
+        ViewMode::Type,
+        ViewMode::Group(Dimension::Dataset),
+        ViewMode::Group(Dimension::Project),
+        ViewMode::Group(Dimension::Host),
+        ViewMode::Group(Dimension::Status),
+        ViewMode::Group(Dimension::Referer),
+        ViewMode::Group(Dimension::Domain),
+        ViewMode::Group(Dimension::Client),
+        ViewMode::Group(Dimension::Transform),
+        ViewMode::Group(Dimension::Write),
+        ViewMode::Slow,
+        ViewMode::Errors,
+        ViewMode::Time,
+        ViewMode::Heatmap,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            ViewMode::Path => "By Asset",
+            ViewMode::Type => "By Type",
+            ViewMode::Time => "Over Time",
+            ViewMode::Heatmap => "Heatmap",
+            ViewMode::Slow => "Slow Requests",
+            ViewMode::Errors => "Errors",
+            ViewMode::Group(dimension) => dimension.title(),
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0)
+    }
+}
+
+/// Views the TUI can open on, with `--view` or `view` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum StartView {
+    Asset,
+    Type,
+    Dataset,
+    Project,
+    Host,
+    Status,
+    Referer,
+    Domain,
+    Client,
+    Transform,
+    Write,
+    Slow,
+    Errors,
+    Time,
+    Heatmap,
+}
+
+impl StartView {
+    /// The start view that opens on `mode`.
+    fn of(mode: ViewMode) -> Option<Self> {
+        <Self as clap::ValueEnum>::value_variants()
+            .iter()
+            .copied()
+            .find(|view| view.mode() == mode)
+    }
+
+    fn mode(self) -> ViewMode {
+        match self {
+            StartView::Asset => ViewMode::Path,
+            StartView::Type => ViewMode::Type,
+            StartView::Dataset => ViewMode::Group(Dimension::Dataset),
+            StartView::Project => ViewMode::Group(Dimension::Project),
+            StartView::Host => ViewMode::Group(Dimension::Host),
+            StartView::Status => ViewMode::Group(Dimension::Status),
+            StartView::Referer => ViewMode::Group(Dimension::Referer),
+            StartView::Domain => ViewMode::Group(Dimension::Domain),
+            StartView::Client => ViewMode::Group(Dimension::Client),
+            StartView::Transform => ViewMode::Group(Dimension::Transform),
+            StartView::Write => ViewMode::Group(Dimension::Write),
+            StartView::Slow => ViewMode::Slow,
+            StartView::Errors => ViewMode::Errors,
+            StartView::Time => ViewMode::Time,
+            StartView::Heatmap => ViewMode::Heatmap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    fn seconds(self) -> i64 {
+        match self {
+            Granularity::Hour => HOUR_SECONDS,
+            Granularity::Day => 24 * HOUR_SECONDS,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+        }
+    }
+
+    fn format(self, start: i64) -> String {
+        let pattern = match self {
+            Granularity::Hour => "%Y-%m-%d %H:00",
+            Granularity::Day => "%Y-%m-%d",
+        };
+        Timestamp::from_second(start)
+            .map(|timestamp| timestamp.strftime(pattern).to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Columns of the asset and type tables, in display order. Named in kebab case in the state
+/// file, such as `avg-size`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+enum Column {
+    Type,
+    Id,
+    Ext,
+    Trend,
+    Requests,
+    AvgSize,
+    Bandwidth,
+    Unoptimized,
+    Variants,
+    CacheHit,
+    P50,
+    P95,
+    P99,
+    Latency,
+    LatencyP95,
+    RequestsDelta,
+    BandwidthDelta,
+    Change,
+    RequestSize,
+    ClientErrors,
+    ServerErrors,
+    ErrorRate,
+}
+
+impl Column {
+    /// Heading shown above the column. The ID column's depends on the view; see
+    /// [`App::id_title`].
+    fn title(self) -> &'static str {
+        match self {
+            Column::Type => "T",
+            Column::Id => "ID",
+            Column::Ext => "Ext",
+            Column::Trend => "Trend",
+            Column::Requests => "Requests",
+            Column::AvgSize => "Size (Avg)",
+            Column::Bandwidth => "Bandwidth",
+            Column::Unoptimized => "Unoptimized",
+            Column::Variants => "Variants",
+            Column::CacheHit => "Cache hit",
+            Column::P50 => "p50",
+            Column::P95 => "p95",
+            Column::P99 => "p99",
+            Column::Latency => "Latency",
+            Column::LatencyP95 => "p95 lat.",
+            Column::RequestsDelta => "Requests Δ",
+            Column::BandwidthDelta => "Bandwidth Δ",
+            Column::Change => "Change",
+            Column::RequestSize => "Req Size",
+            Column::ClientErrors => "4xx",
+            Column::ServerErrors => "5xx",
+            Column::ErrorRate => "Error %",
+        }
+    }
+
+    /// Heading for terminals narrower than [`NARROW_WIDTH`], where it's all that fits in the
+    /// column's [`Column::compact_width`].
+    fn short_title(self) -> &'static str {
+        match self {
+            Column::Requests => "Reqs",
+            Column::AvgSize => "Avg",
+            Column::Bandwidth => "BW",
+            Column::Unoptimized => "Unopt",
+            Column::Variants => "Vars",
+            Column::CacheHit => "Hit",
+            Column::LatencyP95 => "p95 l",
+            Column::RequestsDelta => "Reqs Δ",
+            Column::BandwidthDelta => "BW Δ",
+            Column::Change => "Chg",
+            Column::RequestSize => "Req",
+            Column::ErrorRate => "Err %",
+            column => column.title(),
+        }
+    }
+
+    /// Width under the short heading, just wide enough for the values and a sort arrow.
+    fn compact_width(self) -> u16 {
+        match self {
+            Column::Requests => 7,
+            Column::AvgSize | Column::Bandwidth | Column::Unoptimized => 11,
+            Column::Variants | Column::CacheHit | Column::Change | Column::ErrorRate => 7,
+            Column::Latency | Column::LatencyP95 => 8,
+            Column::RequestsDelta => 8,
+            Column::BandwidthDelta => 12,
+            Column::RequestSize => 11,
+            Column::ClientErrors | Column::ServerErrors => 6,
+            column => column.width(),
+        }
+    }
+
+    /// Fixed width in cells; the ID column takes whatever space is left.
+    fn width(self) -> u16 {
+        match self {
+            Column::Type => 2,
+            Column::Id => 0,
+            Column::Ext => 8,
+            Column::Trend => TREND_BINS as u16,
+            Column::Requests => 10,
+            Column::AvgSize => 12,
+            Column::Bandwidth => 14,
+            Column::Unoptimized => 13,
+            Column::Variants => 10,
+            Column::CacheHit => 11,
+            Column::P50 | Column::P95 | Column::P99 => 11,
+            Column::Latency | Column::LatencyP95 => 10,
+            Column::RequestsDelta => 12,
+            Column::BandwidthDelta => 14,
+            Column::Change => 10,
+            Column::RequestSize => 12,
+            Column::ClientErrors | Column::ServerErrors => 8,
+            Column::ErrorRate => 9,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -52,166 +376,2515 @@ enum RequestType {
     Image,
     File,
     Query,
+    Mutation,
+    Document,
+    History,
+    Listen,
+    Export,
+    Upload,
     Other,
 }
 
 impl RequestType {
+    /// In the order the By Type view lists them.
+    const ALL: [RequestType; 10] = [
+        RequestType::Image,
+        RequestType::File,
+        RequestType::Query,
+        RequestType::Mutation,
+        RequestType::Document,
+        RequestType::History,
+        RequestType::Listen,
+        RequestType::Export,
+        RequestType::Upload,
+        RequestType::Other,
+    ];
+
     fn label(self) -> char {
         match self {
             RequestType::Image => 'I',
             RequestType::File => 'F',
             RequestType::Query => 'Q',
+            RequestType::Mutation => 'M',
+            RequestType::Document => 'D',
+            RequestType::History => 'H',
+            RequestType::Listen => 'L',
+            RequestType::Export => 'X',
+            RequestType::Upload => 'U',
             RequestType::Other => '?',
         }
     }
 
-    fn color(self) -> Color {
+    /// Lowercase name used in exported data.
+    fn name(self) -> &'static str {
         match self {
-            RequestType::Image => Color::Green,
-            RequestType::File => Color::Blue,
-            RequestType::Query => Color::Yellow,
-            RequestType::Other => Color::Gray,
+            RequestType::Image => "image",
+            RequestType::File => "file",
+            RequestType::Query => "query",
+            RequestType::Mutation => "mutation",
+            RequestType::Document => "document",
+            RequestType::History => "history",
+            RequestType::Listen => "listen",
+            RequestType::Export => "export",
+            RequestType::Upload => "upload",
+            RequestType::Other => "other",
         }
     }
+
+    fn style(self) -> Style {
+        theme().request_type(self)
+    }
 }
 
 #[derive(Debug, Clone)]
 struct DisplayRow {
     label: String,
+    /// The stats or group key behind a row that stands for exactly one.
+    key: Option<String>,
     ext: String,
     request_count: u64,
     bandwidth_sum: u64,
+    /// Bytes sent by clients, such as mutation bodies and uploaded assets.
+    request_size_sum: u64,
+    /// Requests answered with a 4xx and a 5xx status.
+    client_errors: u64,
+    server_errors: u64,
+    /// Part of `bandwidth_sum` spent on JPEG/PNG images not converted to a modern format.
+    unoptimized_bandwidth: u64,
+    /// Distinct variants of an asset, or transform variants beneath an image in the Transforms
+    /// view.
+    variant_count: usize,
+    cache_lookups: u64,
+    cache_hits: u64,
+    /// Response sizes at each of [`PERCENTILES`], when any were logged.
+    size_percentiles: Option<[u64; 3]>,
+    duration_sum: u64,
+    timed_requests: u64,
+    latency_p95: Option<u64>,
     req_type: RequestType,
     open_url: Option<String>,
     is_group: bool,
+    /// Requests over time, for the optional sparkline column.
+    trend: Option<Vec<u64>>,
+    /// The same row's traffic in the `diff` baseline, zero for rows new since then.
+    baseline: Option<Traffic>,
 }
 
 impl DisplayRow {
     fn avg_size(&self) -> u64 {
-        if self.request_count == 0 {
-            0
-        } else {
-            self.bandwidth_sum / self.request_count
+        self.bandwidth_sum
+            .checked_div(self.request_count)
+            .unwrap_or(0)
+    }
+
+    /// Share of requests with a known cache status that were served from cache.
+    fn cache_hit_rate(&self) -> Option<f64> {
+        (self.cache_lookups > 0).then(|| self.cache_hits as f64 / self.cache_lookups as f64)
+    }
+
+    /// Share of requests that failed with a 4xx or 5xx status.
+    fn error_rate(&self) -> Option<f64> {
+        (self.request_count > 0)
+            .then(|| (self.client_errors + self.server_errors) as f64 / self.request_count as f64)
+    }
+
+    fn bandwidth_delta(&self) -> Option<i64> {
+        self.baseline
+            .map(|baseline| self.bandwidth_sum as i64 - baseline.bandwidth as i64)
+    }
+
+    /// Relative change in bandwidth from the baseline, infinite for rows new since then.
+    fn change(&self) -> Option<f64> {
+        relative_change(self.baseline?.bandwidth, self.bandwidth_sum)
+    }
+
+    fn avg_latency(&self) -> Option<u64> {
+        self.duration_sum.checked_div(self.timed_requests)
+    }
+}
+
+const CHANGE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Number of time slices drawn in the By Asset sparkline column.
+const TREND_BINS: usize = 12;
+/// Images with more transform variants than this are highlighted in the Transforms view, as
+/// they usually point at srcset widths computed from unstable values.
+const MAX_VARIANTS: usize = 20;
+/// Quantiles shown by the p50/p95/p99 columns.
+const PERCENTILES: [f64; 3] = [0.5, 0.95, 0.99];
+/// Quantile shown by the p95 latency column.
+const LATENCY_PERCENTILE: f64 = 0.95;
+/// Requests taking at least this long are listed in the Slow Requests view.
+const SLOW_REQUEST_MS: u64 = 1000;
+/// Log lines shown by the raw record pane for the selected row.
+const RAW_RECORD_SAMPLES: usize = 5;
+/// Characters `<` and `>` scroll the ID column by.
+const ID_SCROLL_STEP: usize = 8;
+/// Terminals narrower than this get short headings and compact number columns.
+const NARROW_WIDTH: u16 = 80;
+/// Columns are dropped to keep at least this much room for IDs.
+const MIN_ID_WIDTH: usize = 20;
+/// Columns dropped when IDs would have too little room, least useful first. The type, ID,
+/// requests, and bandwidth are always kept.
+const DROP_ORDER: [Column; 17] = [
+    Column::Ext,
+    Column::AvgSize,
+    Column::Trend,
+    Column::P50,
+    Column::P99,
+    Column::Variants,
+    Column::RequestSize,
+    Column::LatencyP95,
+    Column::CacheHit,
+    Column::P95,
+    Column::Latency,
+    Column::ClientErrors,
+    Column::ServerErrors,
+    Column::RequestsDelta,
+    Column::Unoptimized,
+    Column::ErrorRate,
+    Column::BandwidthDelta,
+];
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The Over Time tab switches from hourly to daily buckets past this many hours of logs.
+const MAX_HOURLY_SPAN: i64 = 72;
+/// Picker presets, counted back from the newest loaded record rather than from now so that
+/// older log exports still get useful windows.
+const TIME_PRESETS: [(&str, i64); 3] = [
+    ("Last 24 hours", 24),
+    ("Last 7 days", 24 * 7),
+    ("Last 30 days", 24 * 30),
+];
+
+/// Request and bandwidth totals of one side of a `diff`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Traffic {
+    requests: u64,
+    bandwidth: u64,
+}
+
+impl Traffic {
+    fn of(stats: &PathStats) -> Self {
+        Traffic {
+            requests: stats.request_count,
+            bandwidth: stats.bandwidth_sum,
+        }
+    }
+}
+
+/// The "before" logs of `diff`, loaded along with the other inputs but kept out of every view
+/// except as the baseline of the By Asset delta columns.
+#[derive(Debug)]
+struct Baseline {
+    label: String,
+    /// Inputs before this position belong to the baseline.
+    inputs: usize,
+    requests: Vec<Request>,
+    stats: HashMap<String, PathStats>,
+}
+
+/// Text typed after `/`; only rows matching it are shown, or with a leading `!`, only rows
+/// that don't.
+#[derive(Debug, Default)]
+struct RowFilter {
+    text: String,
+    /// Whether keys are being typed into the filter rather than handled as shortcuts.
+    editing: bool,
+}
+
+impl RowFilter {
+    fn is_exclusion(&self) -> bool {
+        self.text.starts_with('!')
+    }
+
+    /// The text without a leading `!`.
+    fn pattern(&self) -> &str {
+        self.text.strip_prefix('!').unwrap_or(&self.text)
+    }
+
+    /// The pattern as a case-insensitive regular expression.
+    fn regex(&self) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(self.pattern())
+            .case_insensitive(true)
+            .build()
+    }
+
+    /// The regex, or while the pattern isn't a valid one (such as halfway through typing a
+    /// group), the pattern matched literally.
+    fn matcher(&self) -> Option<Regex> {
+        self.regex()
+            .or_else(|_| {
+                RegexBuilder::new(&regex::escape(self.pattern()))
+                    .case_insensitive(true)
+                    .build()
+            })
+            .ok()
+    }
+}
+
+/// Kinds of rows hidden with `I`, `F`, `Q`, and `O` in the asset and By Type views.
+#[derive(Debug, Default)]
+struct TypeFilter {
+    /// Indexed like [`TypeFilter::KEYS`].
+    hidden: [bool; 4],
+}
+
+impl TypeFilter {
+    /// Key of each toggle and the type shown for it. Every API request other than queries
+    /// counts as "other".
+    const KEYS: [(char, RequestType); 4] = [
+        ('I', RequestType::Image),
+        ('F', RequestType::File),
+        ('Q', RequestType::Query),
+        ('O', RequestType::Other),
+    ];
+
+    /// The preset type of each toggle, in the order of [`TypeFilter::KEYS`].
+    const PRESET_TYPES: [PresetType; 4] = [
+        PresetType::Image,
+        PresetType::File,
+        PresetType::Query,
+        PresetType::Other,
+    ];
+
+    fn slot(kind: RequestType) -> usize {
+        match kind {
+            RequestType::Image => 0,
+            RequestType::File => 1,
+            RequestType::Query => 2,
+            _ => 3,
+        }
+    }
+
+    fn shows(&self, kind: RequestType) -> bool {
+        !self.hidden[Self::slot(kind)]
+    }
+
+    fn is_active(&self) -> bool {
+        self.hidden.contains(&true)
+    }
+}
+
+/// Rows that used less bandwidth than this are hidden (`--min-bandwidth`, or `m`).
+#[derive(Debug, Default)]
+struct MinBandwidth {
+    bytes: Option<u64>,
+    /// Text being typed after `m`, until Enter applies it.
+    input: Option<String>,
+}
+
+/// Only requests from matching referers are counted (`--referer`, or `x`).
+#[derive(Debug, Default)]
+struct RefererFilter {
+    pattern: Option<Regex>,
+    /// Text being typed after `x`, until Enter applies it.
+    input: Option<String>,
+}
+
+/// How the table is shown, as set on the command line and in the config file.
+#[derive(Debug, Clone, Default)]
+struct ViewOptions {
+    min_bandwidth: Option<u64>,
+    referer: Option<Regex>,
+    presets: Vec<(String, FilterPreset)>,
+    /// Index into `presets` of the one to start with (`--filter-preset`).
+    preset: Option<usize>,
+    /// Column the TUI starts sorted by. Output without the TUI keeps its own order.
+    sort: Option<SortField>,
+    /// View the TUI starts on. Output without the TUI always covers By Asset.
+    view: Option<StartView>,
+    keymap: Keymap,
+    /// Columns hidden in the TUI, as saved from its columns menu. Output without the TUI
+    /// shows them all.
+    hidden_columns: BTreeSet<Column>,
+    /// Where the TUI was left with the same logs, to pick up from, and their name in the
+    /// state file to save it under on quitting.
+    session: Session,
+    session_key: Option<String>,
+    /// Sanity Studio that document lookups open in (`--studio-url`).
+    studio_url: Option<Url>,
+    /// What a GB of bandwidth costs (`--price-per-gb`), to show the cost beside it.
+    price_per_gb: Option<f64>,
+}
+
+/// Which loaded requests the views aggregate: those in the time window from a matching
+/// referer, behind every row focused on with `=` and, with a filter preset applied, those
+/// passing its request criteria.
+struct RequestFilter {
+    range: TimeRange,
+    referer: Option<Regex>,
+    preset: Option<FilterPreset>,
+    focus: Vec<Focus>,
+}
+
+impl RequestFilter {
+    fn includes(&self, request: &Request) -> bool {
+        let referer = request.record.referer.as_deref().unwrap_or_default();
+        self.range.contains(request.record.timestamp)
+            && self
+                .referer
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(referer))
+            && self
+                .preset
+                .as_ref()
+                .is_none_or(|preset| preset.includes(request))
+            && self.focus.iter().all(|focus| focus.includes(request))
+    }
+}
+
+/// The requests behind one row, picked with `=` so other views can break them down.
+#[derive(Debug, Clone)]
+struct Focus {
+    label: String,
+    on: FocusOn,
+}
+
+#[derive(Debug, Clone)]
+enum FocusOn {
+    /// An asset or API path, by [`Request::stats_key`].
+    Key(String),
+    /// One variant of an asset, by [`Request::variant_key`].
+    Variant {
+        key: String,
+        variant: String,
+    },
+    Type(RequestType),
+    /// Images or files with an extension, or without one for `None`.
+    Ext(RequestType, Option<String>),
+    /// A GROQ query, by its normalized text.
+    Query(String),
+    Group(Dimension, String),
+    /// Every group beneath a heading of a nested view.
+    Parent(Dimension, String),
+}
+
+impl Focus {
+    fn includes(&self, request: &Request) -> bool {
+        match &self.on {
+            FocusOn::Key(key) => request.stats_key() == *key,
+            FocusOn::Variant { key, variant } => {
+                request.stats_key() == *key && request.variant_key() == *variant
+            }
+            FocusOn::Type(kind) => detect_request_type(request.path()) == *kind,
+            FocusOn::Ext(kind, ext) => {
+                detect_request_type(request.path()) == *kind
+                    && extract_extension(request.path()) == *ext
+            }
+            FocusOn::Query(query) => groq::query_text(&request.url).is_some_and(|q| q == *query),
+            FocusOn::Group(dimension, key) => {
+                dimension.includes(request) && dimension.key(request) == *key
+            }
+            FocusOn::Parent(dimension, parent) => {
+                dimension.includes(request) && dimension.parent(&dimension.key(request)) == *parent
+            }
         }
     }
 }
 
+/// One of the filters narrowing what is shown, as listed in the active-filters bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ActiveFilter {
+    /// `--since`/`--until`.
+    LoadedRange,
+    /// `--filter-regex`.
+    Path,
+    /// One of the `--exclude` patterns.
+    Exclude(usize),
+    /// One of the `--param` conditions.
+    Param(usize),
+    Dataset,
+    /// `--ua`.
+    UserAgent,
+    /// One of the `--exclude-ua` patterns, or `--no-bots`.
+    ExcludeUserAgent(usize),
+    /// The window chosen with `t`.
+    TimeWindow,
+    Referer,
+    Preset,
+    /// One of the rows focused on with `=`.
+    Focus(usize),
+    Types,
+    MinBandwidth,
+    /// The `/` filter.
+    Text,
+}
+
+impl ActiveFilter {
+    /// Whether the filter is applied while reading the logs, so removing it reads them again.
+    fn is_applied_while_loading(self) -> bool {
+        matches!(
+            self,
+            ActiveFilter::LoadedRange
+                | ActiveFilter::Path
+                | ActiveFilter::Exclude(_)
+                | ActiveFilter::Param(_)
+                | ActiveFilter::Dataset
+                | ActiveFilter::UserAgent
+                | ActiveFilter::ExcludeUserAgent(_)
+        )
+    }
+}
+
+/// Fuzzy search over the table's rows, opened with `f`, for jumping to one among thousands.
+#[derive(Debug, Default)]
+struct Finder {
+    query: String,
+    /// Rows matching `query`, best first, with the char positions that matched.
+    matches: Vec<(usize, Vec<usize>)>,
+    selected: usize,
+}
+
+/// A time window chosen in the TUI, applied on top of what was loaded.
+#[derive(Debug, Clone)]
+struct TimeFilter {
+    label: String,
+    range: TimeRange,
+}
+
+/// The variants of one asset, opened with Enter from the By Asset view.
+#[derive(Debug)]
+struct DrillDown {
+    /// Stats key of the asset's row, see [`Request::stats_key`].
+    key: String,
+    title: String,
+    /// Stats per variant, keyed by [`Request::variant_key`].
+    variants: HashMap<String, PathStats>,
+    /// Row to select again when going back.
+    return_selection: Option<usize>,
+}
+
+/// Log lines behind the selected row as they were read, opened with `i`.
+#[derive(Debug)]
+struct RawRecords {
+    title: String,
+    /// `(input:line, text)` of each record found.
+    records: Vec<(String, String)>,
+    error: Option<String>,
+    scroll: u16,
+}
+
+/// Response sizes counted into power-of-two buckets, opened with `D`.
+#[derive(Debug)]
+struct SizeHistogram {
+    title: String,
+    requests: u64,
+    /// Lower bound of each bucket in bytes, with its request count.
+    buckets: Vec<(u64, u64)>,
+}
+
+impl SizeHistogram {
+    fn new(title: String, sizes: impl Iterator<Item = u64>) -> Self {
+        let mut counts = [0u64; 65];
+        for size in sizes {
+            counts[(u64::BITS - size.leading_zeros()) as usize] += 1;
+        }
+        let first = counts.iter().position(|&count| count > 0).unwrap_or(0);
+        let last = counts.iter().rposition(|&count| count > 0).unwrap_or(0);
+        let buckets = (first..=last)
+            .map(|index| {
+                let lower = if index == 0 { 0 } else { 1u64 << (index - 1) };
+                (lower, counts[index])
+            })
+            .collect();
+        SizeHistogram {
+            title,
+            requests: counts.iter().sum(),
+            buckets,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TimePicker {
+    /// Index into "All time", the presets, then "Custom range…".
+    selected: usize,
+    /// Text being typed for a custom range, once that option is chosen.
+    custom: Option<String>,
+    error: Option<String>,
+}
+
 struct App {
+    inputs: Vec<Input>,
+    load_options: LoadOptions,
+    loader: Receiver<LoadEvent>,
+    modified: Vec<Option<SystemTime>>,
+    last_change_check: Instant,
+    reload_pending: bool,
+    requests: Vec<Request>,
+    stats: HashMap<String, PathStats>,
+    /// Hourly traffic for the Over Time tab, keyed by the hour's start in Unix seconds.
+    timeline: BTreeMap<i64, TrafficBucket>,
+    /// Bucket size chosen with `H`, overriding the automatic choice.
+    granularity: Option<Granularity>,
+    show_trends: bool,
+    /// Per-path request counts over time, computed while the sparkline column is shown.
+    trends: HashMap<String, Vec<u64>>,
+    show_percentiles: bool,
+    /// Columns hidden with the columns menu, in every view that has them.
+    hidden_columns: BTreeSet<Column>,
+    /// Name of the logs in the state file, when they have one.
+    session_key: Option<String>,
+    /// The columns menu, while open, as the index of the selected entry.
+    column_menu: Option<usize>,
+    /// Characters scrolled past with `>` in the ID column, to read the end of long IDs.
+    id_scroll: usize,
+    /// Rows the table showed when last drawn, which Page Up and Page Down move by.
+    page_rows: usize,
+    /// Row number being typed after `:`, until Enter jumps to it.
+    go_to_row: Option<String>,
+    /// Keys of the rows marked with Space, whose combined traffic the footer shows. Cleared
+    /// on switching to another table.
+    marked: HashSet<String>,
+    /// Keys of the rows pinned with `B`, kept at the top of every table listing them whatever
+    /// the sort order and filters.
+    pinned: HashSet<String>,
+    /// Response size percentiles over every row, for the totals row.
+    total_size_percentiles: Option<[u64; 3]>,
+    total_latency_p95: Option<u64>,
+    /// Stats for the grouped view being shown, keyed by group name.
+    groups: HashMap<String, PathStats>,
+    /// Stats per path rather than per asset, so the By Type view can break assets down by
+    /// extension. Only kept while that view is shown.
+    type_items: Vec<PathStats>,
+    /// Stats over requests slower than [`SLOW_REQUEST_MS`], while that view is shown.
+    slow_items: Vec<PathStats>,
+    drill_down: Option<DrillDown>,
     base_items: Vec<PathStats>,
+    summary: LoadSummary,
+    loading: Option<LoadProgress>,
+    load_started: Instant,
     items: Vec<DisplayRow>,
     sort_field: SortField,
     descending: bool,
+    /// The field and direction that break ties in the sort, set with `,` and a sort key.
+    then_sort: Option<(SortField, bool)>,
+    /// Set by `,` until the next key, which picks the tiebreaker if it's a sort key.
+    picking_then_sort: bool,
     table_state: TableState,
     view_mode: ViewMode,
     show_help: bool,
+    skipped: BTreeMap<SkipReason, u64>,
+    parse_errors: Vec<ParseError>,
+    show_errors: bool,
+    error_scroll: usize,
+    time_filter: Option<TimeFilter>,
+    time_picker: Option<TimePicker>,
+    size_histogram: Option<SizeHistogram>,
+    raw_records: Option<RawRecords>,
+    baseline: Option<Baseline>,
+    filter: RowFilter,
+    type_filter: TypeFilter,
+    min_bandwidth: MinBandwidth,
+    referer: RefererFilter,
+    /// Filter presets from the config file, and the one applied.
+    presets: Vec<(String, FilterPreset)>,
+    preset: Option<usize>,
+    /// Rows focused on with `=`, each narrowing the requests further.
+    focus: Vec<Focus>,
+    finder: Option<Finder>,
+    /// Whether `-` was pressed, so the next digit removes that filter from the active-filters
+    /// bar.
+    removing_filter: bool,
+    /// What came of the last export, or why it failed, shown in the footer until the next key.
+    notice: Option<Result<String, String>>,
+    /// Set by `S` to save the next frame drawn, which no longer shows the previous notice.
+    screenshot_requested: bool,
+    keymap: Keymap,
+    studio_url: Option<Url>,
+    price_per_gb: Option<f64>,
 }
 
 impl App {
-    fn new(base_items: Vec<PathStats>) -> Self {
+    fn new(
+        inputs: Vec<Input>,
+        load_options: LoadOptions,
+        baseline: Option<Baseline>,
+        view_options: ViewOptions,
+    ) -> Self {
+        let sort_field = view_options.sort.unwrap_or(SortField::Bandwidth);
+        let session = view_options.session;
         let mut app = Self {
-            base_items,
+            modified: inputs.iter().map(Input::modified).collect(),
+            loader: spawn_loader(inputs.clone(), load_options.clone()),
+            inputs,
+            load_options,
+            last_change_check: Instant::now(),
+            reload_pending: false,
+            requests: Vec::new(),
+            stats: HashMap::new(),
+            timeline: BTreeMap::new(),
+            granularity: None,
+            show_trends: false,
+            trends: HashMap::new(),
+            show_percentiles: false,
+            hidden_columns: session
+                .hidden_columns
+                .unwrap_or(view_options.hidden_columns),
+            session_key: view_options.session_key,
+            column_menu: None,
+            id_scroll: 0,
+            page_rows: 1,
+            go_to_row: None,
+            marked: HashSet::new(),
+            pinned: HashSet::new(),
+            total_size_percentiles: None,
+            total_latency_p95: None,
+            groups: HashMap::new(),
+            type_items: Vec::new(),
+            slow_items: Vec::new(),
+            drill_down: None,
+            base_items: Vec::new(),
+            summary: LoadSummary::default(),
+            loading: Some(LoadProgress::default()),
+            load_started: Instant::now(),
             items: Vec::new(),
-            sort_field: SortField::Bandwidth,
-            descending: true,
+            sort_field,
+            descending: if session.sort == Some(sort_field) {
+                session.descending
+            } else {
+                sort_field.descending_by_default()
+            },
+            then_sort: session
+                .then_sort
+                .filter(|field| *field != sort_field)
+                .map(|field| (field, session.then_descending)),
+            picking_then_sort: false,
             table_state: TableState::default(),
-            view_mode: ViewMode::Path,
+            view_mode: view_options.view.map_or(ViewMode::Path, StartView::mode),
             show_help: false,
+            skipped: BTreeMap::new(),
+            parse_errors: Vec::new(),
+            show_errors: false,
+            error_scroll: 0,
+            time_filter: None,
+            time_picker: None,
+            size_histogram: None,
+            raw_records: None,
+            baseline,
+            filter: RowFilter::default(),
+            type_filter: TypeFilter::default(),
+            min_bandwidth: MinBandwidth {
+                bytes: view_options.min_bandwidth,
+                input: None,
+            },
+            referer: RefererFilter {
+                pattern: view_options.referer,
+                input: None,
+            },
+            presets: view_options.presets,
+            preset: None,
+            focus: Vec::new(),
+            finder: None,
+            removing_filter: false,
+            notice: None,
+            screenshot_requested: false,
+            keymap: view_options.keymap,
+            studio_url: view_options.studio_url,
+            price_per_gb: view_options.price_per_gb,
         };
-        app.rebuild_view();
-        if !app.items.is_empty() {
-            app.table_state.select(Some(0));
+        if view_options.preset.is_some() {
+            app.set_preset(view_options.preset);
+        }
+        if let Some(filters) = session.filters {
+            app.restore_filters(filters);
         }
         app
     }
 
-    fn set_sort(&mut self, field: SortField) {
-        if self.sort_field == field {
-            self.descending = !self.descending;
-        } else {
-            self.sort_field = field;
-            self.descending = !matches!(field, SortField::Path | SortField::Ext);
+    /// Applies the filters saved with the session, skipping a preset or referer that no
+    /// longer reads.
+    fn restore_filters(&mut self, filters: SavedFilters) {
+        let preset = filters
+            .preset
+            .and_then(|name| self.presets.iter().position(|(preset, _)| *preset == name));
+        self.set_preset(preset);
+        self.type_filter.hidden =
+            TypeFilter::PRESET_TYPES.map(|kind| filters.hidden_types.contains(&kind));
+        self.min_bandwidth.bytes = filters.min_bandwidth;
+        self.filter.text = filters.text;
+        if let Some(pattern) = filters
+            .referer
+            .and_then(|text| cli::parse_pattern(&text).ok())
+        {
+            self.referer.pattern = Some(pattern);
+            self.reaggregate();
         }
-        self.rebuild_view();
-        self.clamp_selection();
     }
 
-    fn toggle_view(&mut self) {
-        self.view_mode = match self.view_mode {
-            ViewMode::Path => ViewMode::Type,
-            ViewMode::Type => ViewMode::Path,
+    /// The view, sort, filters, and columns to pick up from next time with the same logs.
+    fn session(&self) -> Session {
+        let hidden_types = TypeFilter::PRESET_TYPES
+            .into_iter()
+            .zip(self.type_filter.hidden)
+            .filter_map(|(kind, hidden)| hidden.then_some(kind))
+            .collect();
+        Session {
+            view: StartView::of(self.view_mode),
+            sort: Some(self.sort_field),
+            descending: self.descending,
+            then_sort: self.then_sort.map(|(field, _)| field),
+            then_descending: self.then_sort.is_some_and(|(_, descending)| descending),
+            hidden_columns: Some(self.hidden_columns.clone()),
+            filters: Some(SavedFilters {
+                preset: self.preset.map(|index| self.presets[index].0.clone()),
+                referer: self.referer.pattern.as_ref().map(Regex::to_string),
+                hidden_types,
+                min_bandwidth: self.min_bandwidth.bytes,
+                text: self.filter.text.clone(),
+            }),
+        }
+    }
+
+    /// Saves the session for these logs, keeping anything saved for others since the TUI
+    /// opened.
+    fn save_session(&self) -> Result<()> {
+        let Some(key) = &self.session_key else {
+            return Ok(());
         };
-        self.rebuild_view();
-        self.clamp_selection();
+        let mut state = State::load();
+        state.sessions.insert(key.clone(), self.session());
+        state.save()
     }
 
-    fn next_view(&mut self) {
-        if self.view_mode == ViewMode::Path {
-            self.toggle_view();
+    fn reload(&mut self) {
+        self.modified = self.inputs.iter().map(Input::modified).collect();
+        self.loader = spawn_loader(self.inputs.clone(), self.load_options.clone());
+        self.reload_pending = false;
+        self.requests.clear();
+        self.stats.clear();
+        if let Some(baseline) = self.baseline.as_mut() {
+            baseline.requests.clear();
+            baseline.stats.clear();
         }
+        self.timeline.clear();
+        self.skipped.clear();
+        self.parse_errors.clear();
+        self.error_scroll = 0;
+        self.summary = LoadSummary::default();
+        self.loading = Some(LoadProgress::default());
+        self.load_started = Instant::now();
     }
 
-    fn previous_view(&mut self) {
-        if self.view_mode == ViewMode::Type {
-            self.toggle_view();
+    /// Blocks until every input has been read, for exporting without the TUI.
+    fn finish_loading(&mut self) -> Result<()> {
+        while self.loading.is_some() {
+            self.poll_loader()?;
+            std::thread::sleep(Duration::from_millis(20));
         }
+        Ok(())
     }
 
-    fn rebuild_view(&mut self) {
-        let descending = self.descending;
-        let field = self.sort_field;
-        self.items = build_display_rows(&self.base_items, self.view_mode, field, descending);
+    fn check_for_changes(&mut self) {
+        if self.reload_pending || self.last_change_check.elapsed() < CHANGE_CHECK_INTERVAL {
+            return;
+        }
+        self.last_change_check = Instant::now();
+        self.reload_pending = self
+            .inputs
+            .iter()
+            .zip(&self.modified)
+            .any(|(input, modified)| input.modified() != *modified);
     }
 
-    fn clamp_selection(&mut self) {
-        let len = self.items.len();
-        let next = match self.table_state.selected() {
-            Some(idx) if idx < len => idx,
-            _ if len == 0 => {
-                self.table_state.select(None);
-                return;
+    fn poll_loader(&mut self) -> Result<()> {
+        let mut changed = false;
+        while let Ok(event) = self.loader.try_recv() {
+            match event {
+                LoadEvent::Progress(progress) => self.loading = Some(progress),
+                LoadEvent::Batch(mut batch, progress) => {
+                    let filter = self.request_filter();
+                    let include = |request: &Request| filter.includes(request);
+                    if let Some(baseline) = self.baseline.as_mut() {
+                        let (before, after): (Vec<Request>, Vec<Request>) =
+                            std::mem::take(&mut batch.requests)
+                                .into_iter()
+                                .partition(|request| request.input < baseline.inputs);
+                        merge_stats(&mut baseline.stats, aggregate(&before, include));
+                        baseline.requests.extend(before);
+                        batch.requests = after;
+                    }
+                    merge_stats(&mut self.stats, aggregate(&batch.requests, include));
+                    merge_buckets(&mut self.timeline, bucket_by_hour(&batch.requests, include));
+                    self.requests.extend(batch.requests);
+                    for (reason, count) in batch.skipped {
+                        *self.skipped.entry(reason).or_default() += count;
+                    }
+                    let room = MAX_PARSE_ERRORS.saturating_sub(self.parse_errors.len());
+                    self.parse_errors
+                        .extend(batch.errors.into_iter().take(room));
+                    self.summary.line_count = progress.lines;
+                    self.summary.duplicates += batch.duplicates;
+                    self.summary.filtered_out += batch.filtered_out;
+                    if let Some(span) = batch.time_span {
+                        TimeSpan::widen(&mut self.summary.time_span, span);
+                    }
+                    self.loading = Some(progress);
+                    changed = true;
+                }
+                LoadEvent::Finished(result) => {
+                    self.summary = result?;
+                    self.loading = None;
+                }
             }
-            _ => len.saturating_sub(1),
-        };
-        self.table_state.select(Some(next));
+        }
+        if changed {
+            self.refresh_items();
+        }
+        Ok(())
     }
 
-    fn next(&mut self) {
-        if self.items.is_empty() {
-            return;
+    fn refresh_items(&mut self) {
+        self.refresh_trends();
+        self.refresh_groups();
+        self.refresh_types();
+        self.refresh_slow();
+        self.refresh_drill_down();
+        self.base_items = self.stats.values().cloned().collect();
+        self.base_items
+            .sort_by_key(|item| std::cmp::Reverse(item.bandwidth_sum));
+        self.rebuild_view();
+        if self.table_state.selected().is_none() && !self.items.is_empty() {
+            self.table_state.select(Some(0));
+        } else {
+            self.clamp_selection();
         }
-        let next = match self.table_state.selected() {
-            Some(idx) if idx + 1 < self.items.len() => idx + 1,
-            _ => self.items.len() - 1,
-        };
-        self.table_state.select(Some(next));
     }
 
-    fn previous(&mut self) {
-        if self.items.is_empty() {
+    fn refresh_trends(&mut self) {
+        self.trends.clear();
+        let (Some((&first, _)), Some((&last, _))) = (
+            self.timeline.first_key_value(),
+            self.timeline.last_key_value(),
+        ) else {
+            return;
+        };
+        if !self.show_trends {
             return;
         }
-        let prev = match self.table_state.selected() {
-            Some(idx) if idx > 0 => idx - 1,
-            _ => 0,
+        let filter = self.request_filter();
+        self.trends = path_trends(
+            &self.requests,
+            |request| filter.includes(request),
+            first,
+            last + HOUR_SECONDS,
+            TREND_BINS,
+        );
+    }
+
+    fn refresh_groups(&mut self) {
+        self.groups.clear();
+        let ViewMode::Group(dimension) = self.view_mode else {
+            return;
         };
-        self.table_state.select(Some(prev));
+        let filter = self.request_filter();
+        self.groups = aggregate_by(
+            &self.requests,
+            |request| filter.includes(request) && dimension.includes(request),
+            |request| dimension.key(request),
+        );
     }
-}
 
-fn main() -> Result<()> {
-    let path = env::args().nth(1).unwrap_or_default();
-    if path.is_empty() {
-        eprintln!("Usage: sanity-log-explorer <ndjson-file>");
-        return Ok(());
+    fn refresh_types(&mut self) {
+        self.type_items.clear();
+        if self.view_mode != ViewMode::Type {
+            return;
+        }
+        let filter = self.request_filter();
+        self.type_items = aggregate_by(
+            &self.requests,
+            |request| filter.includes(request),
+            Request::path_key,
+        )
+        .into_values()
+        .collect();
     }
 
-    let stats = load_stats(&path).with_context(|| format!("failed to load {path}"))?;
-    let mut terminal = setup_terminal()?;
+    fn refresh_slow(&mut self) {
+        self.slow_items.clear();
+        if self.view_mode != ViewMode::Slow {
+            return;
+        }
+        let filter = self.request_filter();
+        self.slow_items = aggregate(&self.requests, |request| {
+            filter.includes(request)
+                && request
+                    .record
+                    .duration_ms
+                    .is_some_and(|duration| duration >= SLOW_REQUEST_MS)
+        })
+        .into_values()
+        .collect();
+    }
 
-    let result = run_app(&mut terminal, stats);
+    /// Replaces the By Asset table with the selected asset's variants. Returns false when the
+    /// selected row isn't an image or file.
+    fn open_drill_down(&mut self) -> bool {
+        if self.view_mode != ViewMode::Path || self.drill_down.is_some() {
+            return false;
+        }
+        let Some(row) = self
+            .table_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+        else {
+            return false;
+        };
+        let Some(key) = row.key.clone() else {
+            return false;
+        };
+        if !matches!(row.req_type, RequestType::Image | RequestType::File) {
+            return false;
+        }
+        let title = format!("{}{}", row.label, row.ext);
+        self.marked.clear();
+        self.drill_down = Some(DrillDown {
+            key,
+            title,
+            variants: HashMap::new(),
+            return_selection: self.table_state.selected(),
+        });
+        self.refresh_drill_down();
+        self.rebuild_view();
+        self.table_state.select(Some(0));
+        self.clamp_selection();
+        true
+    }
 
-    restore_terminal(&mut terminal)?;
-    result
+    fn close_drill_down(&mut self) {
+        let Some(drill_down) = self.drill_down.take() else {
+            return;
+        };
+        self.marked.clear();
+        self.rebuild_view();
+        self.table_state.select(drill_down.return_selection);
+        self.clamp_selection();
+    }
+
+    fn refresh_drill_down(&mut self) {
+        let filter = self.request_filter();
+        let Some(drill_down) = self.drill_down.as_mut() else {
+            return;
+        };
+        let key = &drill_down.key;
+        drill_down.variants = aggregate_by(
+            &self.requests,
+            |request| filter.includes(request) && request.stats_key() == *key,
+            Request::variant_key,
+        );
+    }
+
+    /// The baseline's totals, when the table shows `diff` deltas.
+    fn baseline_totals(&self) -> Option<Traffic> {
+        let baseline = self.baseline.as_ref()?;
+        if !self.view_columns().contains(&Column::Change) {
+            return None;
+        }
+        Some(
+            baseline
+                .stats
+                .values()
+                .fold(Traffic::default(), |total, stats| Traffic {
+                    requests: total.requests + stats.request_count,
+                    bandwidth: total.bandwidth + stats.bandwidth_sum,
+                }),
+        )
+    }
+
+    /// More requests and bytes than any row can have, here or in the baseline, to make room
+    /// for exact numbers.
+    fn largest_traffic(&self) -> Traffic {
+        let stats = self.stats.values().chain(
+            self.baseline
+                .iter()
+                .flat_map(|baseline| baseline.stats.values()),
+        );
+        stats.fold(Traffic::default(), |total, stats| Traffic {
+            requests: total.requests + stats.request_count,
+            bandwidth: total.bandwidth + stats.bandwidth_sum.max(stats.request_size_sum),
+        })
+    }
+
+    /// Stats behind the rows of the table being shown, which the totals row adds up.
+    fn shown_stats(&self) -> Vec<&PathStats> {
+        let mut stats = self.unfiltered_stats();
+        if self.is_filtering() || self.is_hiding_small_rows() {
+            let shown: HashSet<&str> = self
+                .items
+                .iter()
+                .filter_map(|row| row.key.as_deref())
+                .collect();
+            stats.retain(|stats| shown.contains(stats.path.as_str()));
+        }
+        if self.is_filtering_types() {
+            stats.retain(|stats| self.type_filter.shows(detect_request_type(&stats.path)));
+        }
+        stats
+    }
+
+    fn unfiltered_stats(&self) -> Vec<&PathStats> {
+        if let Some(drill_down) = &self.drill_down {
+            return drill_down.variants.values().collect();
+        }
+        match self.view_mode {
+            ViewMode::Group(_) => self.groups.values().collect(),
+            ViewMode::Slow => self.slow_items.iter().collect(),
+            ViewMode::Type => self.type_items.iter().collect(),
+            ViewMode::Errors => self
+                .base_items
+                .iter()
+                .filter(|item| has_errors(item))
+                .collect(),
+            ViewMode::Path | ViewMode::Time | ViewMode::Heatmap => self.base_items.iter().collect(),
+        }
+    }
+
+    fn toggle_trends(&mut self) {
+        self.show_trends = !self.show_trends;
+        self.refresh_items();
+    }
+
+    /// The requests behind the selected row, with a label for it. Rows that don't stand for a
+    /// single path or group (such as the By Type rows) select every request.
+    fn selected_requests(&self) -> (String, Vec<&Request>) {
+        let filter = self.request_filter();
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+            .and_then(|row| Some((row.label.clone(), row.key.clone()?)));
+        let matches: Box<dyn Fn(&Request) -> bool> = match (self.view_mode, &selected) {
+            (_, Some((_, key))) if let Some(drill_down) = &self.drill_down => {
+                Box::new(move |request| {
+                    request.stats_key() == drill_down.key && request.variant_key() == *key
+                })
+            }
+            (ViewMode::Path, Some((_, key))) => {
+                Box::new(move |request| request.stats_key() == *key)
+            }
+            (ViewMode::Slow, Some((_, key))) => Box::new(move |request| {
+                request.stats_key() == *key
+                    && request
+                        .record
+                        .duration_ms
+                        .is_some_and(|duration| duration >= SLOW_REQUEST_MS)
+            }),
+            (ViewMode::Errors, Some((_, key))) => Box::new(move |request| {
+                request.stats_key() == *key && request.record.status.is_some_and(|s| s >= 400)
+            }),
+            (ViewMode::Group(dimension), Some((_, key))) => Box::new(move |request| {
+                dimension.includes(request) && dimension.key(request) == *key
+            }),
+            _ => Box::new(|_| true),
+        };
+        let requests = self
+            .requests
+            .iter()
+            .filter(|request| filter.includes(request) && matches(request))
+            .collect();
+        let label = match &selected {
+            Some((label, _)) => label.clone(),
+            None => "All requests".to_string(),
+        };
+        (label, requests)
+    }
+
+    /// Opens the size histogram for the selected row.
+    fn open_size_histogram(&mut self) {
+        let (label, requests) = self.selected_requests();
+        let sizes = requests
+            .iter()
+            .filter_map(|request| request.record.response_size);
+        self.size_histogram = Some(SizeHistogram::new(label, sizes));
+    }
+
+    /// Reads the first few log lines behind the selected row back from their inputs.
+    fn open_raw_records(&mut self) {
+        let (label, requests) = self.selected_requests();
+        let mut lines: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+        for request in requests.iter().take(RAW_RECORD_SAMPLES) {
+            lines
+                .entry(request.input)
+                .or_default()
+                .push(request.line_number);
+        }
+        let mut raw = RawRecords {
+            title: label,
+            records: Vec::new(),
+            error: None,
+            scroll: 0,
+        };
+        for (index, line_numbers) in lines {
+            let Some(input) = self.inputs.get(index) else {
+                continue;
+            };
+            match read_raw_lines(input, self.load_options.format, &line_numbers) {
+                Ok(found) => raw.records.extend(
+                    found
+                        .into_iter()
+                        .map(|(line_number, text)| (format!("{input}:{line_number}"), text)),
+                ),
+                Err(err) => raw.error = Some(format!("{err:#}")),
+            }
+        }
+        self.raw_records = Some(raw);
+    }
+
+    /// Writes every log line behind the selected row, as logged, to a file in the working
+    /// directory, so the subset can be shared or loaded again on its own. CSV files keep the
+    /// header of the first input.
+    fn save_raw_lines(&mut self) {
+        let (label, requests) = self.selected_requests();
+        let format = self.load_options.format;
+        let mut lines: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+        for request in &requests {
+            lines
+                .entry(request.input)
+                .or_default()
+                .push(request.line_number);
+        }
+        let extension = match format {
+            InputFormat::Ndjson => "ndjson",
+            InputFormat::Csv => "csv",
+            InputFormat::Combined => "log",
+        };
+        let view = self.view_mode.title().to_lowercase().replace(' ', "-");
+        let time = Timestamp::now().strftime("%Y%m%d-%H%M%S");
+        let path = format!("sanity-log-explorer-{view}-records-{time}.{extension}");
+        let written = File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| write_raw_lines(BufWriter::new(file), &self.inputs, format, lines));
+        let count = format_count(requests.len() as u64);
+        self.notice = Some(match written {
+            Ok(()) => Ok(format!("Saved {count} records for {label} to {path}")),
+            Err(err) => Err(format!("Couldn't write {path}: {err:#}")),
+        });
+    }
+
+    /// Copies the selected row's sample URL to the clipboard, or with `id` its asset document
+    /// ID, falling back to the full ID or group name for rows that aren't assets.
+    fn copy_selected(&mut self, id: bool) {
+        let Some(row) = self
+            .table_state
+            .selected()
+            .and_then(|selected| self.items.get(selected))
+        else {
+            return;
+        };
+        let text = if id {
+            row.open_url
+                .as_deref()
+                .and_then(|url| Url::parse(url).ok())
+                .and_then(|url| {
+                    asset_document_id(url.path())
+                        .or_else(|| Some(looked_up_document_ids(url.path())?.to_string()))
+                })
+                .or_else(|| row.key.clone())
+                .unwrap_or_else(|| row.label.clone())
+        } else if let Some(url) = self.studio_link(row) {
+            url
+        } else if let Some(url) = &row.open_url {
+            url.clone()
+        } else {
+            self.notice = Some(Err(format!(
+                "This row has no URL; {} copies its ID",
+                self.keymap.label(Action::CopyId)
+            )));
+            return;
+        };
+        self.notice = Some(match copy_to_clipboard(&text) {
+            Ok(()) => Ok(format!("Copied {text}")),
+            Err(err) => Err(format!("Couldn't copy: {err}")),
+        });
+    }
+
+    /// Where a row for a lookup of one document opens it in the Studio set with `--studio-url`
+    /// or `studio_url`, rather than in the API.
+    fn studio_link(&self, row: &DisplayRow) -> Option<String> {
+        let studio = self.studio_url.as_ref()?;
+        if row.req_type != RequestType::Document || row.is_group {
+            return None;
+        }
+        let url = Url::parse(row.open_url.as_deref()?).ok()?;
+        let ids = looked_up_document_ids(url.path())?;
+        if ids.contains(',') {
+            return None;
+        }
+        // Intents are relative to the Studio's base path, which must end in a slash to be kept.
+        let mut base = studio.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        // The Studio opens a document's draft by its published ID.
+        let id = ids.strip_prefix("drafts.").unwrap_or(ids);
+        base.join(&format!("intent/edit/id={id}"))
+            .ok()
+            .map(String::from)
+    }
+
+    /// Copies the table as shown, with its headings and formatted values, to the clipboard as
+    /// tab-separated values for pasting into a spreadsheet or chat.
+    fn copy_table(&mut self) {
+        let mut tsv = Vec::new();
+        let copied = export::write_tsv(&mut tsv, self)
+            .and_then(|()| copy_to_clipboard(&String::from_utf8_lossy(&tsv)));
+        self.notice = Some(match copied {
+            Ok(()) => Ok(format!(
+                "Copied {} rows as TSV",
+                format_count(self.items.len() as u64)
+            )),
+            Err(err) => Err(format!("Couldn't copy: {err}")),
+        });
+    }
+
+    fn toggle_percentiles(&mut self) {
+        self.show_percentiles = !self.show_percentiles;
+        self.rebuild_view();
+    }
+
+    /// The columns shown, leaving out those hidden with the columns menu. The ID column is
+    /// always shown.
+    fn columns(&self) -> Vec<Column> {
+        let mut columns = self.view_columns();
+        columns.retain(|column| *column == Column::Id || !self.hidden_columns.contains(column));
+        columns
+    }
+
+    /// Columns the menu can hide: every one the current view has except the ID.
+    fn menu_columns(&self) -> Vec<Column> {
+        let mut columns = self.view_columns();
+        columns.retain(|column| *column != Column::Id);
+        columns
+    }
+
+    /// Switches sizes between binary and decimal units, saying which are now shown.
+    fn toggle_units(&mut self) {
+        let units = match units::units() {
+            ByteUnits::Binary => ByteUnits::Decimal,
+            ByteUnits::Decimal => ByteUnits::Binary,
+        };
+        units::set(units);
+        self.notice = Some(Ok(match units {
+            ByteUnits::Binary => "Sizes in binary units (1 KiB = 1024 bytes)".to_string(),
+            ByteUnits::Decimal => "Sizes in decimal units (1 KB = 1000 bytes)".to_string(),
+        }));
+    }
+
+    /// Switches counts and sizes between abbreviated and in full, saying which are now shown.
+    fn toggle_exact_numbers(&mut self) {
+        let exact = !units::exact();
+        units::set_exact(exact);
+        self.notice = Some(Ok(if exact {
+            "Counts and sizes in full".to_string()
+        } else {
+            "Counts and sizes abbreviated".to_string()
+        }));
+    }
+
+    /// Shows or hides the column selected in the columns menu, and saves the choice for the
+    /// next run.
+    fn toggle_menu_column(&mut self) {
+        let Some(column) = self
+            .column_menu
+            .and_then(|selected| self.menu_columns().get(selected).copied())
+        else {
+            return;
+        };
+        if !self.hidden_columns.remove(&column) {
+            self.hidden_columns.insert(column);
+        }
+        let mut state = State::load();
+        state.hidden_columns = self.hidden_columns.clone();
+        if let Err(err) = state.save() {
+            self.notice = Some(Err(format!("Couldn't save columns: {err:#}")));
+        }
+    }
+
+    /// Every column of the current view, hidden or not.
+    fn view_columns(&self) -> Vec<Column> {
+        if self.drill_down.is_some() {
+            let mut columns = vec![
+                Column::Id,
+                Column::Requests,
+                Column::AvgSize,
+                Column::Bandwidth,
+            ];
+            if self.show_percentiles {
+                columns.extend([Column::P50, Column::P95, Column::P99]);
+            }
+            columns.push(Column::Unoptimized);
+            return columns;
+        }
+        if let ViewMode::Group(dimension) = self.view_mode {
+            let mut columns = vec![
+                Column::Id,
+                Column::Requests,
+                Column::AvgSize,
+                Column::Bandwidth,
+            ];
+            if self.show_percentiles {
+                columns.extend([Column::P50, Column::P95, Column::P99]);
+            }
+            if dimension == Dimension::Transform {
+                columns.extend([Column::Variants, Column::Unoptimized]);
+            }
+            if dimension == Dimension::Write {
+                columns.insert(2, Column::RequestSize);
+            }
+            return columns;
+        }
+        if self.view_mode == ViewMode::Errors {
+            return vec![
+                Column::Type,
+                Column::Id,
+                Column::Ext,
+                Column::Requests,
+                Column::ClientErrors,
+                Column::ServerErrors,
+                Column::ErrorRate,
+                Column::Bandwidth,
+            ];
+        }
+        if self.view_mode == ViewMode::Slow {
+            let mut columns = vec![
+                Column::Type,
+                Column::Id,
+                Column::Ext,
+                Column::Requests,
+                Column::AvgSize,
+                Column::Bandwidth,
+            ];
+            if self.show_percentiles {
+                columns.extend([Column::P50, Column::P95, Column::P99]);
+            }
+            columns.extend([Column::Latency, Column::LatencyP95]);
+            return columns;
+        }
+        if self.baseline.is_some() && self.view_mode == ViewMode::Path {
+            let mut columns = vec![
+                Column::Type,
+                Column::Id,
+                Column::Ext,
+                Column::Requests,
+                Column::RequestsDelta,
+                Column::AvgSize,
+                Column::Bandwidth,
+                Column::BandwidthDelta,
+                Column::Change,
+            ];
+            if self.show_percentiles {
+                columns.extend([Column::P50, Column::P95, Column::P99]);
+            }
+            return columns;
+        }
+        let mut columns = vec![Column::Type, Column::Id, Column::Ext];
+        if self.show_trends && self.view_mode == ViewMode::Path {
+            columns.push(Column::Trend);
+        }
+        columns.extend([Column::Requests, Column::AvgSize, Column::Bandwidth]);
+        // Only logs recording request sizes can fill this in, and only uploads and mutations
+        // send much.
+        if self.stats.values().any(|stats| stats.request_size_sum > 0) {
+            columns.push(Column::RequestSize);
+        }
+        if self.show_percentiles {
+            columns.extend([Column::P50, Column::P95, Column::P99]);
+        }
+        if self.view_mode == ViewMode::Path {
+            columns.push(Column::Variants);
+        }
+        columns.push(Column::Unoptimized);
+        // Only CDN logs with a cache status field can fill this in.
+        if self.stats.values().any(|stats| stats.cache_lookups > 0) {
+            columns.push(Column::CacheHit);
+        }
+        if self.stats.values().any(|stats| stats.timed_requests > 0) {
+            columns.extend([Column::Latency, Column::LatencyP95]);
+        }
+        columns
+    }
+
+    /// Heading of the ID column, naming what the rows are.
+    fn id_title(&self) -> &'static str {
+        match self.view_mode {
+            _ if self.drill_down.is_some() => "Variant",
+            ViewMode::Group(dimension) => dimension.label(),
+            ViewMode::Time | ViewMode::Heatmap => "Time",
+            _ => Column::Id.title(),
+        }
+    }
+
+    /// The columns of the table being shown. The time views list only their totals.
+    fn export_columns(&self) -> Vec<Column> {
+        match self.view_mode {
+            ViewMode::Time | ViewMode::Heatmap if self.drill_down.is_none() => vec![
+                Column::Id,
+                Column::Requests,
+                Column::AvgSize,
+                Column::Bandwidth,
+            ],
+            _ => self.columns(),
+        }
+    }
+
+    /// Writes the rows being shown to a CSV file in the working directory, named after the view.
+    fn export_csv(&mut self) {
+        let view = self.view_mode.title().to_lowercase().replace(' ', "-");
+        let time = Timestamp::now().strftime("%Y%m%d-%H%M%S");
+        let path = format!("sanity-log-explorer-{view}-{time}.csv");
+        let written =
+            File::create(&path).and_then(|file| export::write_csv(BufWriter::new(file), self));
+        self.notice = Some(match written {
+            Ok(()) => Ok(format!(
+                "Saved {} rows to {path}",
+                format_count(self.items.len() as u64)
+            )),
+            Err(err) => Err(format!("Couldn't write {path}: {err}")),
+        });
+    }
+
+    /// Saves a rendered frame to the working directory twice: as plain text for pasting, and
+    /// with its colors as ANSI escape sequences for `cat` or `less -R`.
+    fn save_screenshot(&mut self, buffer: &Buffer) {
+        let view = self.view_mode.title().to_lowercase().replace(' ', "-");
+        let time = Timestamp::now().strftime("%Y%m%d-%H%M%S");
+        let name = format!("sanity-log-explorer-{view}-screen-{time}");
+        let text = format!("{name}.txt");
+        let ansi = format!("{name}.ans");
+        let written = File::create(&text)
+            .and_then(|file| screenshot::write_text(BufWriter::new(file), buffer))
+            .and_then(|()| File::create(&ansi))
+            .and_then(|file| screenshot::write_ansi(BufWriter::new(file), buffer));
+        self.notice = Some(match written {
+            Ok(()) => Ok(format!("Saved the screen to {text} and {ansi}")),
+            Err(err) => Err(format!("Couldn't save the screen: {err}")),
+        });
+    }
+
+    fn time_range(&self) -> TimeRange {
+        self.time_filter
+            .as_ref()
+            .map(|filter| filter.range)
+            .unwrap_or_default()
+    }
+
+    fn request_filter(&self) -> RequestFilter {
+        RequestFilter {
+            range: self.time_range(),
+            referer: self.referer.pattern.clone(),
+            preset: self.preset.map(|index| self.presets[index].1.clone()),
+            focus: self.focus.clone(),
+        }
+    }
+
+    /// What `=` narrows the requests down to for the selected row: an asset, an extension or
+    /// request type from the By Type view, or a group. Time buckets have the time picker instead.
+    fn selected_focus(&self) -> Option<Focus> {
+        let row = self.items.get(self.table_state.selected()?)?;
+        let (label, on) = match self.view_mode {
+            _ if let Some(drill_down) = &self.drill_down => (
+                format!("variant {}", row.label),
+                FocusOn::Variant {
+                    key: drill_down.key.clone(),
+                    variant: row.key.clone()?,
+                },
+            ),
+            ViewMode::Time | ViewMode::Heatmap => return None,
+            ViewMode::Type if row.is_group => {
+                (format!("type {}", row.label), FocusOn::Type(row.req_type))
+            }
+            ViewMode::Type if row.req_type == RequestType::Query => (
+                format!("query {}", row.label),
+                FocusOn::Query(row.label.clone()),
+            ),
+            ViewMode::Type => (
+                format!("{} {}", type_label(row.req_type).to_lowercase(), row.ext),
+                FocusOn::Ext(row.req_type, row.ext.strip_prefix('.').map(str::to_string)),
+            ),
+            ViewMode::Group(dimension) => {
+                let label = format!("{} {}", dimension.label().to_lowercase(), row.label);
+                let on = match &row.key {
+                    _ if row.is_group => FocusOn::Parent(dimension, row.label.clone()),
+                    Some(key) => FocusOn::Group(dimension, key.clone()),
+                    None => return None,
+                };
+                (label, on)
+            }
+            ViewMode::Path | ViewMode::Slow | ViewMode::Errors => {
+                let kind = match row.req_type {
+                    RequestType::Image | RequestType::File => "asset",
+                    RequestType::Query => "query",
+                    _ => "path",
+                };
+                (
+                    format!("{kind} {}", row.label),
+                    FocusOn::Key(row.key.clone()?),
+                )
+            }
+        };
+        Some(Focus {
+            label: truncate_id(&label, 48),
+            on,
+        })
+    }
+
+    /// Narrows every view to the requests behind the selected row, so another tab can break
+    /// them down.
+    fn focus_selected(&mut self) {
+        if let Some(focus) = self.selected_focus() {
+            self.focus.push(focus);
+            self.reaggregate();
+        }
+    }
+
+    fn set_time_filter(&mut self, filter: Option<TimeFilter>) {
+        self.time_filter = filter;
+        self.reaggregate();
+    }
+
+    /// Applies a filter preset from the config file, replacing the type toggles, minimum
+    /// bandwidth, and `/` filter with its own. `None` clears them.
+    fn set_preset(&mut self, index: Option<usize>) {
+        self.preset = index;
+        let preset = index
+            .map(|index| self.presets[index].1.clone())
+            .unwrap_or_default();
+        self.type_filter.hidden = TypeFilter::PRESET_TYPES
+            .map(|kind| !preset.types.is_empty() && !preset.types.contains(&kind));
+        self.min_bandwidth.bytes = preset.min_bandwidth;
+        self.filter.text = preset.filter.unwrap_or_default();
+        self.reaggregate();
+    }
+
+    /// Applies the next preset in the config file, then none after the last.
+    fn next_preset(&mut self) {
+        let next = match self.preset {
+            None => 0,
+            Some(index) => index + 1,
+        };
+        self.set_preset((next < self.presets.len()).then_some(next));
+    }
+
+    /// Counts only requests from referers matching the pattern typed after `x`; an empty one
+    /// counts every request again.
+    fn apply_referer(&mut self) -> Result<(), String> {
+        let input = self.referer.input.as_deref().unwrap_or_default();
+        let pattern = match input.trim() {
+            "" => None,
+            text => Some(cli::parse_pattern(text)?),
+        };
+        self.referer = RefererFilter {
+            pattern,
+            input: None,
+        };
+        self.reaggregate();
+        Ok(())
+    }
+
+    /// Re-aggregates the loaded requests after the time window, referer, or filter preset
+    /// changes.
+    fn reaggregate(&mut self) {
+        let filter = self.request_filter();
+        let include = |request: &Request| filter.includes(request);
+        self.stats = aggregate(&self.requests, include);
+        if let Some(baseline) = self.baseline.as_mut() {
+            baseline.stats = aggregate(&baseline.requests, include);
+        }
+        self.timeline = bucket_by_hour(&self.requests, include);
+        self.refresh_items();
+    }
+
+    /// Every filter in effect with its label for the active-filters bar, in the order they are
+    /// applied: while loading, then to requests, then to rows.
+    fn active_filters(&self) -> Vec<(ActiveFilter, String)> {
+        let options = &self.load_options;
+        let mut filters = Vec::new();
+        if !options.range.is_unbounded() {
+            let format = |time: Option<Timestamp>| {
+                time.map(|time| time.strftime("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default()
+            };
+            filters.push((
+                ActiveFilter::LoadedRange,
+                format!(
+                    "loaded {}..{}",
+                    format(options.range.since),
+                    format(options.range.until)
+                ),
+            ));
+        }
+        if let Some(pattern) = &options.path_filter {
+            filters.push((ActiveFilter::Path, format!("path ~ {pattern}")));
+        }
+        for (idx, pattern) in options.exclude.iter().enumerate() {
+            filters.push((ActiveFilter::Exclude(idx), format!("path !~ {pattern}")));
+        }
+        for (idx, param) in options.params.iter().enumerate() {
+            filters.push((ActiveFilter::Param(idx), format!("?{param}")));
+        }
+        if let Some(dataset) = &options.dataset {
+            filters.push((ActiveFilter::Dataset, format!("dataset {dataset}")));
+        }
+        if let Some(pattern) = &options.user_agent {
+            filters.push((ActiveFilter::UserAgent, format!("ua ~ {pattern}")));
+        }
+        for (idx, pattern) in options.exclude_user_agents.iter().enumerate() {
+            let label = if pattern.as_str() == BOT_USER_AGENTS {
+                "no bots".to_string()
+            } else {
+                format!("ua !~ {pattern}")
+            };
+            filters.push((ActiveFilter::ExcludeUserAgent(idx), label));
+        }
+        if let Some(filter) = &self.time_filter {
+            filters.push((ActiveFilter::TimeWindow, filter.label.clone()));
+        }
+        if let Some(pattern) = &self.referer.pattern {
+            filters.push((ActiveFilter::Referer, format!("referer ~ {pattern}")));
+        }
+        if let Some(index) = self.preset {
+            let name = &self.presets[index].0;
+            filters.push((ActiveFilter::Preset, format!("preset {name}")));
+        }
+        for (idx, focus) in self.focus.iter().enumerate() {
+            filters.push((ActiveFilter::Focus(idx), focus.label.clone()));
+        }
+        if self.type_filter.is_active() {
+            let shown: String = TypeFilter::KEYS
+                .iter()
+                .filter(|(_, kind)| self.type_filter.shows(*kind))
+                .map(|(key, _)| *key)
+                .collect();
+            filters.push((ActiveFilter::Types, format!("types {shown}")));
+        }
+        if let Some(min) = self.min_bandwidth.bytes {
+            filters.push((
+                ActiveFilter::MinBandwidth,
+                format!("≥ {}", format_bytes(min)),
+            ));
+        }
+        if !self.filter.pattern().is_empty() {
+            filters.push((ActiveFilter::Text, format!("/{}", self.filter.text)));
+        }
+        filters
+    }
+
+    /// Whether a filter narrows the view being shown. Row filters are kept, but left alone, in
+    /// views they don't apply to.
+    fn applies(&self, filter: ActiveFilter) -> bool {
+        match filter {
+            ActiveFilter::Types => self.is_filtering_types(),
+            ActiveFilter::MinBandwidth => self.is_hiding_small_rows(),
+            ActiveFilter::Text => self.is_filtering(),
+            _ => true,
+        }
+    }
+
+    /// Drops one filter, leaving the rest in place. Removing one applied while loading reads
+    /// the logs again.
+    fn remove_filter(&mut self, filter: ActiveFilter) {
+        let options = &mut self.load_options;
+        match filter {
+            ActiveFilter::LoadedRange => options.range = TimeRange::default(),
+            ActiveFilter::Path => options.path_filter = None,
+            ActiveFilter::Exclude(idx) => {
+                options.exclude.remove(idx);
+            }
+            ActiveFilter::Param(idx) => {
+                options.params.remove(idx);
+            }
+            ActiveFilter::Dataset => options.dataset = None,
+            ActiveFilter::UserAgent => options.user_agent = None,
+            ActiveFilter::ExcludeUserAgent(idx) => {
+                options.exclude_user_agents.remove(idx);
+            }
+            ActiveFilter::TimeWindow => self.time_filter = None,
+            ActiveFilter::Referer => self.referer.pattern = None,
+            ActiveFilter::Preset => self.preset = None,
+            ActiveFilter::Focus(idx) => {
+                self.focus.remove(idx);
+            }
+            ActiveFilter::Types => self.type_filter = TypeFilter::default(),
+            ActiveFilter::MinBandwidth => self.min_bandwidth.bytes = None,
+            ActiveFilter::Text => self.filter.text.clear(),
+        }
+        match filter {
+            _ if filter.is_applied_while_loading() => self.reload(),
+            ActiveFilter::TimeWindow
+            | ActiveFilter::Referer
+            | ActiveFilter::Preset
+            | ActiveFilter::Focus(_) => {
+                self.reaggregate();
+            }
+            _ => {
+                self.rebuild_view();
+                self.clamp_selection();
+            }
+        }
+    }
+
+    fn apply_time_preset(&mut self, (label, hours): (&str, i64)) -> Result<(), String> {
+        let end = self
+            .summary
+            .time_span
+            .map(|span| span.last)
+            .ok_or("the loaded logs have no timestamps")?;
+        let since = end
+            .checked_sub(SignedDuration::from_hours(hours))
+            .map_err(|err| err.to_string())?;
+        let span = format_time_span(TimeSpan {
+            first: since,
+            last: end,
+        });
+        self.set_time_filter(Some(TimeFilter {
+            label: format!("{label} ({span})"),
+            range: TimeRange {
+                since: Some(since),
+                until: None,
+            },
+        }));
+        Ok(())
+    }
+
+    fn set_sort(&mut self, field: SortField) {
+        if self.sort_field == field {
+            self.descending = !self.descending;
+        } else {
+            self.sort_field = field;
+            self.descending = field.descending_by_default();
+        }
+        if self.then_sort.is_some_and(|(then, _)| then == field) {
+            self.then_sort = None;
+        }
+        self.rebuild_view();
+        self.clamp_selection();
+    }
+
+    /// Breaks ties in the sort by `field`, reversing it when it already does, and clearing
+    /// the tiebreaker when `field` is the main sort.
+    fn set_then_sort(&mut self, field: SortField) {
+        self.then_sort = match self.then_sort {
+            _ if field == self.sort_field => None,
+            Some((then, descending)) if then == field => Some((field, !descending)),
+            _ => Some((field, field.descending_by_default())),
+        };
+        self.rebuild_view();
+        self.clamp_selection();
+    }
+
+    fn sort_order(&self) -> SortOrder {
+        SortOrder {
+            then: self.then_sort,
+            ..SortOrder::by(self.sort_field, self.descending)
+        }
+    }
+
+    fn set_view(&mut self, view_mode: ViewMode) {
+        self.view_mode = view_mode;
+        self.drill_down = None;
+        self.marked.clear();
+        self.refresh_groups();
+        self.refresh_types();
+        self.refresh_slow();
+        self.rebuild_view();
+        self.clamp_selection();
+    }
+
+    fn toggle_view(&mut self) {
+        let next = (self.view_mode.index() + 1) % ViewMode::ALL.len();
+        self.set_view(ViewMode::ALL[next]);
+    }
+
+    fn next_view(&mut self) {
+        if let Some(next) = ViewMode::ALL.get(self.view_mode.index() + 1) {
+            self.set_view(*next);
+        }
+    }
+
+    fn previous_view(&mut self) {
+        if let Some(previous) = self.view_mode.index().checked_sub(1) {
+            self.set_view(ViewMode::ALL[previous]);
+        }
+    }
+
+    fn effective_granularity(&self) -> Granularity {
+        self.granularity.unwrap_or_else(|| {
+            let span = match (
+                self.timeline.first_key_value(),
+                self.timeline.last_key_value(),
+            ) {
+                (Some((first, _)), Some((last, _))) => (last - first) / HOUR_SECONDS,
+                _ => 0,
+            };
+            if span <= MAX_HOURLY_SPAN {
+                Granularity::Hour
+            } else {
+                Granularity::Day
+            }
+        })
+    }
+
+    fn toggle_granularity(&mut self) {
+        self.granularity = Some(match self.effective_granularity() {
+            Granularity::Hour => Granularity::Day,
+            Granularity::Day => Granularity::Hour,
+        });
+        self.rebuild_view();
+        self.clamp_selection();
+    }
+
+    /// The hourly timeline rolled up to the current granularity, oldest first. Quiet periods
+    /// between the first and last bucket are included as empty buckets.
+    fn timeline_buckets(&self, granularity: Granularity) -> Vec<(i64, TrafficBucket)> {
+        let size = granularity.seconds();
+        let mut buckets: BTreeMap<i64, TrafficBucket> = BTreeMap::new();
+        for (start, bucket) in &self.timeline {
+            let entry = buckets.entry(start - start.rem_euclid(size)).or_default();
+            entry.requests += bucket.requests;
+            entry.bandwidth += bucket.bandwidth;
+        }
+        let (Some(&first), Some(&last)) = (buckets.keys().next(), buckets.keys().next_back())
+        else {
+            return Vec::new();
+        };
+        (first..=last)
+            .step_by(size as usize)
+            .map(|start| (start, buckets.get(&start).copied().unwrap_or_default()))
+            .collect()
+    }
+
+    fn skipped_count(&self) -> u64 {
+        self.skipped.values().sum()
+    }
+
+    fn rebuild_view(&mut self) {
+        let order = self.sort_order();
+        self.items = match self.view_mode {
+            _ if let Some(drill_down) = &self.drill_down => {
+                let req_type = detect_request_type(&drill_down.key);
+                build_variant_rows(&drill_down.variants, req_type, order)
+            }
+            ViewMode::Time => {
+                let granularity = self.effective_granularity();
+                build_timeline_rows(&self.timeline_buckets(granularity), granularity, order)
+            }
+            // One row per day, always in calendar order to match the grid.
+            ViewMode::Heatmap => build_timeline_rows(
+                &self.timeline_buckets(Granularity::Day),
+                Granularity::Day,
+                SortOrder::by(SortField::Path, false),
+            ),
+            ViewMode::Path => build_path_rows(
+                &self.base_items,
+                &self.trends,
+                self.baseline.as_ref().map(|baseline| &baseline.stats),
+                order,
+            ),
+            ViewMode::Slow => build_path_rows(&self.slow_items, &self.trends, None, order),
+            ViewMode::Errors => {
+                let mut rows = build_path_rows(&self.base_items, &self.trends, None, order);
+                rows.retain(|row| row.client_errors + row.server_errors > 0);
+                rows
+            }
+            ViewMode::Type => build_type_rows(&self.type_items, order),
+            ViewMode::Group(dimension) => build_group_rows(&self.groups, dimension, order),
+        };
+        let pinned = if self.narrows_rows() && !self.pinned.is_empty() {
+            let (pinned, rest) = std::mem::take(&mut self.items)
+                .into_iter()
+                .partition(|row| self.is_pinned(row));
+            self.items = rest;
+            pinned
+        } else {
+            Vec::new()
+        };
+        if self.is_filtering()
+            && let Some(regex) = self.filter.matcher()
+        {
+            let matches = |row: &DisplayRow| {
+                [Some(&row.label), row.key.as_ref(), Some(&row.ext)]
+                    .into_iter()
+                    .flatten()
+                    .any(|text| regex.is_match(text))
+            };
+            let rows = std::mem::take(&mut self.items);
+            self.items = if self.filter.is_exclusion() {
+                exclude_rows(rows, matches)
+            } else {
+                filter_rows(rows, matches)
+            };
+        }
+        if self.is_hiding_small_rows()
+            && let Some(min) = self.min_bandwidth.bytes
+        {
+            // Only rows for single assets or groups are compared, so a heading stays while any
+            // row beneath it is big enough.
+            self.items = filter_rows(std::mem::take(&mut self.items), |row| {
+                !row.is_group && row.bandwidth_sum >= min
+            });
+        }
+        if self.is_filtering_types() {
+            let types = &self.type_filter;
+            self.items = filter_rows(std::mem::take(&mut self.items), |row| {
+                types.shows(row.req_type)
+            });
+        }
+        if !pinned.is_empty() {
+            self.items.splice(0..0, pinned);
+        }
+        self.refresh_totals();
+        self.refresh_finder();
+    }
+
+    /// Whether the table being shown lists assets or groups that the `/` filter and minimum
+    /// bandwidth narrow down. The By Type and time views are overviews without IDs to match,
+    /// so they always show every row.
+    fn narrows_rows(&self) -> bool {
+        self.drill_down.is_some()
+            || !matches!(
+                self.view_mode,
+                ViewMode::Type | ViewMode::Time | ViewMode::Heatmap
+            )
+    }
+
+    fn is_filtering(&self) -> bool {
+        !self.filter.pattern().is_empty() && self.narrows_rows()
+    }
+
+    fn is_hiding_small_rows(&self) -> bool {
+        self.min_bandwidth.bytes.is_some() && self.narrows_rows()
+    }
+
+    /// Applies the threshold typed after `m`; an empty one shows every row again.
+    fn apply_min_bandwidth(&mut self) -> Result<(), String> {
+        let input = self.min_bandwidth.input.as_deref().unwrap_or_default();
+        let bytes = match input.trim() {
+            "" => None,
+            text => Some(cli::parse_size(text)?),
+        };
+        self.min_bandwidth = MinBandwidth { bytes, input: None };
+        self.rebuild_view();
+        self.clamp_selection();
+        Ok(())
+    }
+
+    /// Whether rows of some types are hidden from the table being shown. Grouped and time views
+    /// mix every type in a row, and an asset's variants share its type, so they are left alone.
+    fn is_filtering_types(&self) -> bool {
+        self.type_filter.is_active()
+            && self.drill_down.is_none()
+            && matches!(
+                self.view_mode,
+                ViewMode::Path | ViewMode::Type | ViewMode::Slow | ViewMode::Errors
+            )
+    }
+
+    fn toggle_type(&mut self, slot: usize) {
+        self.type_filter.hidden[slot] = !self.type_filter.hidden[slot];
+        self.rebuild_view();
+        self.clamp_selection();
+    }
+
+    fn set_filter_text(&mut self, text: String) {
+        self.filter.text = text;
+        self.rebuild_view();
+        self.table_state.select(Some(0));
+        self.clamp_selection();
+    }
+
+    fn open_finder(&mut self) {
+        self.finder = Some(Finder::default());
+        self.refresh_finder();
+    }
+
+    /// Ranks the rows against the finder's query. With no query, every row is listed in table
+    /// order.
+    fn refresh_finder(&mut self) {
+        let Some(finder) = self.finder.as_mut() else {
+            return;
+        };
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, row)| {
+                let (score, positions) = fuzzy::score(&finder.query, finder_text(row))?;
+                Some((score, idx, positions))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        finder.matches = scored
+            .into_iter()
+            .map(|(_, idx, positions)| (idx, positions))
+            .collect();
+        finder.selected = finder.selected.min(finder.matches.len().saturating_sub(1));
+    }
+
+    /// Closes the finder, selecting the row chosen in it.
+    fn jump_to_found(&mut self) {
+        if let Some(finder) = self.finder.take()
+            && let Some((row, _)) = finder.matches.get(finder.selected)
+        {
+            self.table_state.select(Some(*row));
+        }
+    }
+
+    /// Merges the distributions behind the totals row's percentile columns, when shown.
+    fn refresh_totals(&mut self) {
+        self.total_size_percentiles = None;
+        self.total_latency_p95 = None;
+        let show_latency = self.view_columns().contains(&Column::LatencyP95);
+        if !self.show_percentiles && !show_latency {
+            return;
+        }
+        let mut sizes = Distribution::default();
+        let mut durations = Distribution::default();
+        for item in self.shown_stats() {
+            if self.show_percentiles {
+                sizes.merge(&item.response_sizes);
+            }
+            if show_latency {
+                durations.merge(&item.durations);
+            }
+        }
+        self.total_size_percentiles = size_percentiles(&sizes);
+        self.total_latency_p95 = durations.quantile(LATENCY_PERCENTILE);
+    }
+
+    fn clamp_selection(&mut self) {
+        let len = self.items.len();
+        let next = match self.table_state.selected() {
+            Some(idx) if idx < len => idx,
+            _ if len == 0 => {
+                self.table_state.select(None);
+                return;
+            }
+            _ => len.saturating_sub(1),
+        };
+        self.table_state.select(Some(next));
+    }
+
+    /// Moves the selection down by `offset` rows, or up when negative, stopping at the first
+    /// and last rows.
+    fn move_selection(&mut self, offset: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        let selected = self.table_state.selected().unwrap_or(0);
+        let moved = selected
+            .saturating_add_signed(offset)
+            .min(self.items.len() - 1);
+        self.table_state.select(Some(moved));
+    }
+
+    /// Moves the selection by `offset` rows and scrolls the table to keep it in the middle,
+    /// so moving a page at a time shows what's around it on both sides.
+    fn scroll_selection(&mut self, offset: isize) {
+        self.move_selection(offset);
+        if let Some(selected) = self.table_state.selected() {
+            *self.table_state.offset_mut() = selected.saturating_sub(self.page_rows / 2);
+        }
+    }
+
+    /// Selects the row numbered as typed after `:`, counting from 1 in the table's order, or
+    /// the last row when there are fewer. Leaves the prompt open on anything but a number.
+    fn apply_go_to_row(&mut self) {
+        let Some(number) = self.go_to_row.as_deref().and_then(parse_row_number) else {
+            return;
+        };
+        self.go_to_row = None;
+        if !self.items.is_empty() {
+            self.table_state.select(Some(0));
+            self.scroll_selection(number as isize - 1);
+        }
+    }
+
+    /// Marks the selected row, or unmarks it, and moves on to the next. Group headings can't
+    /// be marked, as their rows would be counted twice.
+    fn toggle_mark(&mut self) {
+        if let Some(row) = self
+            .table_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+            && !row.is_group
+            && let Some(key) = &row.key
+            && !self.marked.remove(key)
+        {
+            self.marked.insert(key.clone());
+        }
+        self.move_selection(1);
+    }
+
+    fn is_pinned(&self, row: &DisplayRow) -> bool {
+        !row.is_group
+            && row
+                .key
+                .as_ref()
+                .is_some_and(|key| self.pinned.contains(key))
+    }
+
+    /// Pins the selected row to the top of the table, or unpins it, keeping it selected.
+    fn toggle_pin(&mut self) {
+        if !self.narrows_rows() {
+            return;
+        }
+        let Some(key) = self
+            .table_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+            .filter(|row| !row.is_group)
+            .and_then(|row| row.key.clone())
+        else {
+            return;
+        };
+        if !self.pinned.remove(&key) {
+            self.pinned.insert(key.clone());
+        }
+        self.rebuild_view();
+        let index = self
+            .items
+            .iter()
+            .position(|row| row.key.as_ref() == Some(&key));
+        self.table_state.select(index);
+        self.clamp_selection();
+    }
+
+    /// The marked rows the table shows. Rows hidden by a filter stay marked but aren't counted.
+    fn marked_rows(&self) -> impl Iterator<Item = &DisplayRow> {
+        self.items.iter().filter(|row| {
+            row.key
+                .as_ref()
+                .is_some_and(|key| self.marked.contains(key))
+        })
+    }
+
+    fn select_first(&mut self) {
+        if !self.items.is_empty() {
+            self.table_state.select(Some(0));
+        }
+    }
+
+    fn select_last(&mut self) {
+        if !self.items.is_empty() {
+            self.table_state.select(Some(self.items.len() - 1));
+        }
+    }
+
+    /// Scrolls the ID column's text left or right. Drawing the table stops it at the end of
+    /// the longest ID.
+    fn scroll_ids(&mut self, right: bool) {
+        self.id_scroll = if right {
+            self.id_scroll.saturating_add(ID_SCROLL_STEP)
+        } else {
+            self.id_scroll.saturating_sub(ID_SCROLL_STEP)
+        };
+    }
+}
+
+fn main() -> Result<()> {
+    // The config comes first so sizes on the command line are read with its decimal separator.
+    let config = Config::load()?;
+    let mut cli = Cli::parse();
+    cli.fill_from(&config);
+    theme::set(cli.theme());
+    let _ = ID_TRUNCATION.set(config.truncate_ids.unwrap_or_default());
+    if cli.json && !cli.summary && !matches!(cli.command, Some(Command::Check { .. })) {
+        anyhow::bail!("--json only applies to --summary and check");
+    }
+    let mut inputs = expand_inputs(&cli.inputs)?;
+    let mut baseline = None;
+    if let Some(
+        Command::Report { inputs: logs, .. }
+        | Command::Export { inputs: logs, .. }
+        | Command::Top { inputs: logs, .. }
+        | Command::Watch { inputs: logs, .. }
+        | Command::Check { inputs: logs, .. },
+    ) = &cli.command
+    {
+        inputs = expand_inputs(logs)?;
+    }
+    if let Some(Command::Diff { before, after }) = &cli.command {
+        inputs = expand_inputs(std::slice::from_ref(before))?;
+        baseline = Some(Baseline {
+            label: before.clone(),
+            inputs: inputs.len(),
+            requests: Vec::new(),
+            stats: HashMap::new(),
+        });
+        inputs.extend(expand_inputs(std::slice::from_ref(after))?);
+    }
+    if let Some(path) = &cli.geoip {
+        group::load_geoip(path)?;
+    }
+    let load_options = LoadOptions {
+        strict: cli.strict,
+        dedupe: cli.dedupe,
+        range: TimeRange {
+            since: cli.since,
+            until: cli.until,
+        },
+        path_filter: cli.filter_regex.clone(),
+        exclude: cli.exclude.clone(),
+        params: cli.param.clone(),
+        dataset: cli.dataset.clone(),
+        user_agent: cli.ua.clone(),
+        exclude_user_agents: exclude_user_agents(&cli)?,
+        format: cli.format.unwrap_or_default(),
+        json_fields: cli.json_fields(),
+        csv_columns: CsvColumns {
+            url: cli.url_column.take().unwrap_or_else(|| "url".to_string()),
+            size: cli
+                .size_column
+                .take()
+                .unwrap_or_else(|| "responseSize".to_string()),
+        },
+    };
+    if let Some(project) = cli.project {
+        let token = cli
+            .token
+            .context("--project requires an API token (--token or SANITY_AUTH_TOKEN)")?;
+        inputs.push(Input::SanityApi {
+            project,
+            dataset: cli.dataset,
+            token,
+        });
+    }
+
+    let presets: Vec<(String, FilterPreset)> = config.filters.into_iter().collect();
+    let preset = match &cli.filter_preset {
+        Some(name) => Some(
+            presets
+                .iter()
+                .position(|(preset, _)| preset == name)
+                .with_context(|| format!("no filter preset named {name} in the config file"))?,
+        ),
+        None => None,
+    };
+    let view_options = ViewOptions {
+        min_bandwidth: cli.min_bandwidth,
+        referer: cli.referer.clone(),
+        presets,
+        preset,
+        sort: None,
+        view: None,
+        keymap: Keymap::default(),
+        hidden_columns: BTreeSet::new(),
+        session: Session::default(),
+        session_key: None,
+        studio_url: cli.studio_url.clone(),
+        price_per_gb: cli.price_per_gb,
+    };
+
+    if let Some(Command::Report { output, .. }) = cli.command {
+        let app = load_headless(inputs, load_options, baseline, view_options)?;
+        report::write(io::stdout().lock(), &app, output)?;
+        return Ok(());
+    }
+    if let Some(Command::Export { format, output, .. }) = &cli.command {
+        let app = load_headless(inputs, load_options, baseline, view_options)?;
+        match format {
+            RecordFormat::Sqlite => database::write_sqlite(output, &app)?,
+            RecordFormat::Parquet => columnar::write_parquet(output, &app)?,
+        }
+        return Ok(());
+    }
+    if let Some(format) = cli.export {
+        let app = load_headless(inputs, load_options, baseline, view_options)?;
+        let out = io::stdout().lock();
+        match format {
+            ExportFormat::Csv => export::write_csv(out, &app)?,
+        }
+        return Ok(());
+    }
+    if let Some(Command::Watch {
+        report_every,
+        report_file,
+        output,
+        inputs: logs,
+    }) = &cli.command
+    {
+        if load_options.format == InputFormat::Csv {
+            anyhow::bail!(
+                "watch can't read CSV logs: their records have no timestamps, so every report \
+                 would be empty"
+            );
+        }
+        // Files are listed again for every report; API inputs are fetched again as they are.
+        inputs.retain(|input| matches!(input, Input::SanityApi { .. }));
+        let watch = Watch {
+            logs,
+            api_inputs: inputs,
+            load_options,
+            view_options,
+            report_file,
+            format: *output,
+        };
+        return watch.run(*report_every);
+    }
+    if let Some(Command::Top { by, limit, .. }) = cli.command {
+        let mut app = load_headless(inputs, load_options, baseline, view_options)?;
+        app.sort_field = by;
+        app.descending = by.descending_by_default();
+        app.rebuild_view();
+        report::write_top(io::stdout().lock(), &app, limit)?;
+        return Ok(());
+    }
+    if let Some(Command::Check {
+        max_bandwidth,
+        max_requests,
+        ..
+    }) = cli.command
+    {
+        let mut app = load_headless(inputs, load_options, baseline, view_options)?;
+        // Budgets count every request that was loaded, so hiding rows can't meet one.
+        for filter in [
+            ActiveFilter::Types,
+            ActiveFilter::MinBandwidth,
+            ActiveFilter::Text,
+        ] {
+            app.remove_filter(filter);
+        }
+        let budgets = Budgets {
+            max_bandwidth,
+            max_requests,
+        };
+        let out = io::stdout().lock();
+        let passed = if cli.json {
+            budget::write_check_json(out, &app, budgets)?
+        } else {
+            budget::write_check(out, &app, budgets)?
+        };
+        if !passed {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if cli.summary {
+        let app = load_headless(inputs, load_options, baseline, view_options)?;
+        let out = io::stdout().lock();
+        if cli.json {
+            report::write_summary_json(out, &app)?;
+        } else {
+            report::write_summary(out, &app)?;
+        }
+        return Ok(());
+    }
+
+    // The flags come first, then where the TUI was left with the same logs, then the config.
+    let mut state = State::load();
+    let session_key = state::session_key(&inputs);
+    let mut session = session_key
+        .as_ref()
+        .and_then(|key| state.sessions.remove(key))
+        .unwrap_or_default();
+    if cli.filter_preset.is_some() || cli.min_bandwidth.is_some() || cli.referer.is_some() {
+        session.filters = None;
+    }
+    let view_options = ViewOptions {
+        sort: cli.sort.or(session.sort).or(config.sort),
+        view: cli.view.or(session.view).or(config.view),
+        keymap: Keymap::new(&config.keys).context("invalid config file")?,
+        hidden_columns: state.hidden_columns,
+        session,
+        session_key,
+        ..view_options
+    };
+    let mut terminal = setup_terminal()?;
+    let result = run_app(&mut terminal, inputs, load_options, baseline, view_options);
+
+    restore_terminal(&mut terminal)?;
+    result
+}
+
+/// What `watch` needs to write each report.
+struct Watch<'a> {
+    logs: &'a [String],
+    api_inputs: Vec<Input>,
+    load_options: LoadOptions,
+    view_options: ViewOptions,
+    report_file: &'a Path,
+    format: ReportFormat,
+}
+
+impl Watch<'_> {
+    /// Writes a report now and then once every `every` until interrupted. A report that fails,
+    /// such as while a log is being rotated, is skipped rather than ending the watch.
+    fn run(&self, every: Duration) -> Result<()> {
+        let period = SignedDuration::try_from(every)?;
+        let mut next = Instant::now();
+        loop {
+            let end = Timestamp::now();
+            if let Err(err) = self.write_report(end.checked_sub(period)?, end) {
+                eprintln!("sanity-log-explorer: skipped the report at {end}: {err:#}");
+            }
+            next += every;
+            thread::sleep(next.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    /// Reads the inputs again and writes a report on the requests logged between `start` and
+    /// `end`. The report goes to a temporary file first, so readers never see half of one.
+    fn write_report(&self, start: Timestamp, end: Timestamp) -> Result<()> {
+        let mut inputs = expand_inputs(self.logs)?;
+        inputs.extend(self.api_inputs.iter().cloned());
+        let mut load_options = self.load_options.clone();
+        let range = load_options.range;
+        load_options.range = TimeRange {
+            since: Some(range.since.map_or(start, |since| since.max(start))),
+            until: Some(range.until.map_or(end, |until| until.min(end))),
+        };
+        let app = load_headless(inputs, load_options, None, self.view_options.clone())?;
+
+        let mut partial = self.report_file.as_os_str().to_owned();
+        partial.push(".partial");
+        let file = File::create(&partial)
+            .with_context(|| format!("failed to create {}", partial.display()))?;
+        report::write(BufWriter::new(file), &app, self.format)?;
+        fs::rename(&partial, self.report_file)
+            .with_context(|| format!("failed to write {}", self.report_file.display()))
+    }
+}
+
+/// Reads every input without the TUI, for commands that print their results.
+fn load_headless(
+    inputs: Vec<Input>,
+    load_options: LoadOptions,
+    baseline: Option<Baseline>,
+    view_options: ViewOptions,
+) -> Result<App> {
+    let mut app = App::new(inputs, load_options, baseline, view_options);
+    app.finish_loading()?;
+    Ok(app)
+}
+
+/// The `--exclude-ua` patterns, plus the built-in bot list with `--no-bots`.
+fn exclude_user_agents(cli: &Cli) -> Result<Vec<Regex>> {
+    let mut patterns = cli.exclude_ua.clone();
+    if cli.no_bots {
+        patterns.push(Regex::new(BOT_USER_AGENTS).context("invalid bot pattern")?);
+    }
+    Ok(patterns)
+}
+
+fn expand_inputs(args: &[String]) -> Result<Vec<Input>> {
+    let mut inputs = Vec::new();
+    for arg in args {
+        if source::is_remote(arg) || !arg.contains(['*', '?', '[']) {
+            inputs.push(Input::Path(arg.clone()));
+            continue;
+        }
+        let matches = glob::glob(arg).with_context(|| format!("invalid pattern {arg}"))?;
+        let start = inputs.len();
+        for entry in matches {
+            let path = entry.with_context(|| format!("failed to expand {arg}"))?;
+            if path.is_file() {
+                inputs.push(Input::Path(path.to_string_lossy().into_owned()));
+            }
+        }
+        if inputs.len() == start {
+            anyhow::bail!("no files match {arg}");
+        }
+    }
+    Ok(inputs)
 }
 
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stderr>>> {
@@ -229,19 +2902,32 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stderr>>) -> Result
     Ok(())
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<Stderr>>, items: Vec<PathStats>) -> Result<()> {
-    let mut app = App::new(items);
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    inputs: Vec<Input>,
+    load_options: LoadOptions,
+    baseline: Option<Baseline>,
+    view_options: ViewOptions,
+) -> Result<()> {
+    let mut app = App::new(inputs, load_options, baseline, view_options);
     loop {
-        terminal.draw(|frame| render(frame, &mut app))?;
+        app.poll_loader()?;
+        app.check_for_changes();
+        let frame = terminal.draw(|frame| render(frame, &mut app))?;
+        if app.screenshot_requested {
+            app.screenshot_requested = false;
+            app.save_screenshot(frame.buffer);
+        }
 
-        if event::poll(Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                if handle_key(&mut app, key) {
-                    break;
-                }
-            }
+        if event::poll(Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+            && handle_key(&mut app, key)
+        {
+            break;
         }
     }
+    // Losing the session isn't worth an error on the way out.
+    let _ = app.save_session();
     Ok(())
 }
 
@@ -249,61 +2935,677 @@ fn handle_key(app: &mut App, key: KeyEvent) -> bool {
     if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
         return true;
     }
-    if key.code == KeyCode::Char('?') {
+    app.notice = None;
+    if app.filter.editing {
+        match key.code {
+            KeyCode::Esc => {
+                app.filter.editing = false;
+                app.set_filter_text(String::new());
+            }
+            KeyCode::Enter => app.filter.editing = false,
+            KeyCode::Backspace => {
+                let mut text = app.filter.text.clone();
+                text.pop();
+                app.set_filter_text(text);
+            }
+            KeyCode::Char(ch) => {
+                let text = format!("{}{ch}", app.filter.text);
+                app.set_filter_text(text);
+            }
+            _ => {}
+        }
+        return false;
+    }
+    if let Some(input) = app.min_bandwidth.input.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.min_bandwidth.input = None,
+            // An invalid size stays open, shown in red, to be corrected.
+            KeyCode::Enter => {
+                let _ = app.apply_min_bandwidth();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) => input.push(ch),
+            _ => {}
+        }
+        return false;
+    }
+    if let Some(input) = app.go_to_row.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.go_to_row = None,
+            // Anything but a row number stays open, shown in red, to be corrected.
+            KeyCode::Enter => app.apply_go_to_row(),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) => input.push(ch),
+            _ => {}
+        }
+        return false;
+    }
+    if let Some(input) = app.referer.input.as_mut() {
+        match key.code {
+            KeyCode::Esc => app.referer.input = None,
+            KeyCode::Enter => {
+                let _ = app.apply_referer();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) => input.push(ch),
+            _ => {}
+        }
+        return false;
+    }
+    if app.finder.is_some() {
+        handle_finder_key(app, key);
+        return false;
+    }
+    if app.removing_filter {
+        // Any key other than a listed filter's number cancels.
+        app.removing_filter = false;
+        if let KeyCode::Char(digit) = key.code
+            && let Some(idx) = digit.to_digit(10).and_then(|number| number.checked_sub(1))
+            && let Some(&(filter, _)) = app.active_filters().get(idx as usize)
+        {
+            app.remove_filter(filter);
+        }
+        return false;
+    }
+    let action = app.keymap.action(key);
+    if action == Some(Action::Help) {
         app.show_help = !app.show_help;
         return false;
     }
-    if app.show_help && key.code == KeyCode::Esc {
+    if app.show_help && action == Some(Action::Back) {
         app.show_help = false;
         return false;
     }
+    if app.time_picker.is_some() {
+        handle_time_picker_key(app, key);
+        return false;
+    }
+    if let Some(raw) = app.raw_records.as_mut() {
+        match action {
+            Some(Action::Back | Action::RawRecords) => app.raw_records = None,
+            Some(Action::SaveRecords) => app.save_raw_lines(),
+            Some(Action::Screenshot) => app.screenshot_requested = true,
+            Some(Action::Up) => raw.scroll = raw.scroll.saturating_sub(1),
+            Some(Action::Down) => raw.scroll = raw.scroll.saturating_add(1),
+            Some(Action::PageUp) => raw.scroll = raw.scroll.saturating_sub(10),
+            Some(Action::PageDown) => raw.scroll = raw.scroll.saturating_add(10),
+            Some(Action::Top) => raw.scroll = 0,
+            // Drawing the popup stops the scroll at its last line.
+            Some(Action::Bottom) => raw.scroll = u16::MAX,
+            Some(Action::Quit) => return true,
+            _ => {}
+        }
+        return false;
+    }
+    if app.size_histogram.is_some() {
+        match action {
+            Some(Action::Back | Action::Histogram) => app.size_histogram = None,
+            Some(Action::Screenshot) => app.screenshot_requested = true,
+            Some(Action::Quit) => return true,
+            _ => {}
+        }
+        return false;
+    }
+    if app.show_errors {
+        match action {
+            Some(Action::Back | Action::Errors) => app.show_errors = false,
+            Some(Action::Screenshot) => app.screenshot_requested = true,
+            Some(Action::Up) => app.error_scroll = app.error_scroll.saturating_sub(1),
+            Some(Action::Down) if app.error_scroll + 1 < app.parse_errors.len() => {
+                app.error_scroll += 1;
+            }
+            Some(Action::Quit) => return true,
+            _ => {}
+        }
+        return false;
+    }
+    if let Some(selected) = app.column_menu {
+        let last = app.menu_columns().len().saturating_sub(1);
+        match action {
+            Some(Action::Back | Action::Columns) => app.column_menu = None,
+            Some(Action::Up) => app.column_menu = Some(selected.saturating_sub(1)),
+            Some(Action::Down) => app.column_menu = Some((selected + 1).min(last)),
+            Some(Action::Open | Action::Mark) => app.toggle_menu_column(),
+            Some(Action::Quit) => return true,
+            _ => {}
+        }
+        return false;
+    }
+    if std::mem::take(&mut app.picking_then_sort)
+        && let Some(Action::Sort(field)) = action
+    {
+        if app.baseline.is_some() || !matches!(field, SortField::BandwidthDelta | SortField::Change)
+        {
+            app.set_then_sort(field);
+        }
+        return false;
+    }
+    let Some(action) = action else {
+        return false;
+    };
+    match action {
+        Action::Quit => return true,
+        Action::Up => app.move_selection(-1),
+        Action::Down => app.move_selection(1),
+        Action::PageUp => app.scroll_selection(-(app.page_rows as isize)),
+        Action::PageDown => app.scroll_selection(app.page_rows as isize),
+        Action::HalfPageUp => app.scroll_selection(-(app.page_rows as isize / 2).max(1)),
+        Action::HalfPageDown => app.scroll_selection((app.page_rows as isize / 2).max(1)),
+        Action::GoToRow => app.go_to_row = Some(String::new()),
+        Action::Mark => app.toggle_mark(),
+        Action::Pin => app.toggle_pin(),
+        Action::Top => app.select_first(),
+        Action::Bottom => app.select_last(),
+        Action::PreviousView => app.previous_view(),
+        Action::NextView => app.next_view(),
+        Action::CycleView => app.toggle_view(),
+        Action::Filter => app.filter.editing = true,
+        Action::Find => app.open_finder(),
+        Action::NextPreset => app.next_preset(),
+        Action::Focus => app.focus_selected(),
+        Action::RemoveFilter if !app.active_filters().is_empty() => app.removing_filter = true,
+        Action::MinBandwidth => app.min_bandwidth.input = Some(String::new()),
+        Action::Referer => {
+            let current = app.referer.pattern.as_ref().map(Regex::to_string);
+            app.referer.input = Some(current.unwrap_or_default());
+        }
+        Action::ToggleType(kind) => app.toggle_type(TypeFilter::slot(kind)),
+        Action::Back if !app.filter.text.is_empty() => app.set_filter_text(String::new()),
+        Action::Back if !app.marked.is_empty() => app.marked.clear(),
+        Action::Back => app.close_drill_down(),
+        Action::Open if app.open_drill_down() => {}
+        Action::Open => {
+            if let Some(selected) = app.table_state.selected()
+                && let Some(item) = app.items.get(selected)
+                && let Some(url) = app.studio_link(item).or_else(|| item.open_url.clone())
+            {
+                let _ = open_url(&url);
+            }
+        }
+        Action::CopyUrl => app.copy_selected(false),
+        Action::CopyId => app.copy_selected(true),
+        Action::CopyTable => app.copy_table(),
+        Action::Sort(SortField::BandwidthDelta | SortField::Change) if app.baseline.is_none() => {}
+        Action::Sort(field) => app.set_sort(field),
+        Action::ThenSort => {
+            app.picking_then_sort = true;
+            app.notice = Some(Ok("Then by: press a sort key to break ties".to_string()));
+        }
+        Action::Reload => app.reload(),
+        Action::Errors => app.show_errors = true,
+        Action::TimeRange => app.time_picker = Some(TimePicker::default()),
+        Action::Granularity => app.toggle_granularity(),
+        Action::Trends => app.toggle_trends(),
+        Action::Percentiles => app.toggle_percentiles(),
+        Action::Columns => app.column_menu = Some(0),
+        Action::Units => app.toggle_units(),
+        Action::ExactNumbers => app.toggle_exact_numbers(),
+        Action::ScrollLeft => app.scroll_ids(false),
+        Action::ScrollRight => app.scroll_ids(true),
+        Action::Histogram => app.open_size_histogram(),
+        Action::RawRecords => app.open_raw_records(),
+        Action::SaveRecords => app.save_raw_lines(),
+        Action::ExportCsv => app.export_csv(),
+        Action::Screenshot => app.screenshot_requested = true,
+        Action::RemoveFilter | Action::Help => {}
+    }
+    false
+}
+
+fn handle_finder_key(app: &mut App, key: KeyEvent) {
+    let Some(finder) = app.finder.as_mut() else {
+        return;
+    };
+    let control = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.finder = None,
+        KeyCode::Enter => app.jump_to_found(),
+        KeyCode::Up => finder.selected = finder.selected.saturating_sub(1),
+        KeyCode::Char('p' | 'k') if control => {
+            finder.selected = finder.selected.saturating_sub(1);
+        }
+        KeyCode::Down => finder.selected += 1,
+        KeyCode::Char('n' | 'j') if control => finder.selected += 1,
+        KeyCode::Backspace => {
+            finder.query.pop();
+        }
+        KeyCode::Char(ch) if !control => finder.query.push(ch),
+        _ => return,
+    }
+    // Also clamps the selection moved above.
+    app.refresh_finder();
+}
+
+fn handle_time_picker_key(app: &mut App, key: KeyEvent) {
+    let action = app.keymap.action(key);
+    let Some(picker) = app.time_picker.as_mut() else {
+        return;
+    };
+    if let Some(input) = picker.custom.as_mut() {
+        match key.code {
+            KeyCode::Esc => {
+                picker.custom = None;
+                picker.error = None;
+            }
+            KeyCode::Enter => match parse_custom_range(input) {
+                Ok(range) => {
+                    let label = input.trim().to_string();
+                    app.time_picker = None;
+                    app.set_time_filter(Some(TimeFilter { label, range }));
+                }
+                Err(err) => picker.error = Some(err),
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) => input.push(ch),
+            _ => {}
+        }
+        return;
+    }
+    let last = TIME_PRESETS.len() + 1;
     match key.code {
-        KeyCode::Char('q') => return true,
-        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-        KeyCode::Down | KeyCode::Char('j') => app.next(),
-        KeyCode::Left | KeyCode::Char('h') => app.previous_view(),
-        KeyCode::Right | KeyCode::Char('l') => app.next_view(),
-        KeyCode::Tab => app.toggle_view(),
-        KeyCode::Enter => {
-            if let Some(selected) = app.table_state.selected() {
-                if let Some(item) = app.items.get(selected) {
-                    if let Some(url) = item.open_url.as_deref() {
-                        let _ = open_url(url);
+        KeyCode::Esc => app.time_picker = None,
+        KeyCode::Enter => match picker.selected {
+            0 => {
+                app.time_picker = None;
+                app.set_time_filter(None);
+            }
+            idx if idx == last => picker.custom = Some(String::new()),
+            idx => match app.apply_time_preset(TIME_PRESETS[idx - 1]) {
+                Ok(()) => app.time_picker = None,
+                Err(err) => {
+                    if let Some(picker) = app.time_picker.as_mut() {
+                        picker.error = Some(err);
+                    }
+                }
+            },
+        },
+        _ => match action {
+            Some(Action::Back | Action::TimeRange | Action::Quit) => app.time_picker = None,
+            Some(Action::Up) => picker.selected = picker.selected.saturating_sub(1),
+            Some(Action::Down) => picker.selected = (picker.selected + 1).min(last),
+            _ => {}
+        },
+    }
+}
+
+/// A row number typed after `:`, counting from 1. Thousands separators are allowed.
+fn parse_row_number(text: &str) -> Option<usize> {
+    let separator = units::separators().thousands;
+    let number = text.trim().replace([',', separator], "").parse().ok()?;
+    (number > 0).then_some(number)
+}
+
+/// Parses `FROM..TO`, where either side may be left empty. A single time means "from then on".
+fn parse_custom_range(text: &str) -> Result<TimeRange, String> {
+    let (since, until) = text.split_once("..").unwrap_or((text, ""));
+    let parse = |value: &str| {
+        let value = value.trim();
+        (!value.is_empty())
+            .then(|| cli::parse_time(value))
+            .transpose()
+    };
+    let range = TimeRange {
+        since: parse(since)?,
+        until: parse(until)?,
+    };
+    if range.is_unbounded() {
+        return Err("enter a start and/or end time".to_string());
+    }
+    Ok(range)
+}
+
+fn render(frame: &mut Frame, app: &mut App) {
+    // With more than one filter in effect, they get a line of their own so it's clear which
+    // slice of the traffic is shown. Rows focused on with `=` aren't shown anywhere else.
+    let filters = app.active_filters();
+    let filters_height =
+        u16::from(filters.len() > 1 || !app.focus.is_empty() || app.removing_filter);
+    // Long URLs wrap onto a few lines rather than being cut short again.
+    let detail = selected_detail(app);
+    let detail_height = detail.as_ref().map_or(0, |detail| {
+        let width = frame.size().width.max(1) as usize;
+        detail.width().div_ceil(width).clamp(1, 3) as u16
+    });
+    let chunks = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(filters_height),
+        Constraint::Min(1),
+        Constraint::Length(detail_height),
+        Constraint::Length(1),
+    ])
+    .split(frame.size());
+    render_header(frame, chunks[0], app);
+    if filters_height > 0 {
+        render_active_filters(frame, chunks[1], app, &filters);
+    }
+    if app.loading.is_some() && app.items.is_empty() {
+        render_loading(frame, chunks[2], app);
+    } else if app.view_mode == ViewMode::Time {
+        render_timeline(frame, chunks[2], app);
+    } else if app.view_mode == ViewMode::Heatmap {
+        render_heatmap(frame, chunks[2], app);
+    } else if let Some((query, params)) = selected_query(app) {
+        let params_height = if params.is_empty() {
+            0
+        } else {
+            params.len() + 2
+        };
+        let detail_height = (query.lines().count() + params_height + 2) as u16;
+        let detail_height = detail_height.min(chunks[2].height / 2);
+        let parts = Layout::vertical([Constraint::Min(6), Constraint::Length(detail_height)])
+            .split(chunks[2]);
+        render_table(frame, parts[0], app);
+        render_query_detail(frame, parts[1], &query, &params);
+    } else {
+        render_table(frame, chunks[2], app);
+    }
+    if let Some(detail) = detail {
+        let detail = Paragraph::new(detail)
+            .style(theme().accent)
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, chunks[3]);
+    }
+    render_footer(frame, chunks[4], app);
+    if app.show_errors {
+        render_errors_popup(frame, frame.size(), app);
+    }
+    if let Some(histogram) = &app.size_histogram {
+        render_size_histogram(frame, frame.size(), histogram, &app.keymap);
+    }
+    if let Some(raw) = app.raw_records.as_mut() {
+        render_raw_records(frame, frame.size(), raw, &app.keymap);
+    }
+    if let Some(picker) = &app.time_picker {
+        render_time_picker(frame, frame.size(), picker);
+    }
+    if let Some(selected) = app.column_menu {
+        render_column_menu(frame, frame.size(), app, selected);
+    }
+    if let Some(finder) = &app.finder {
+        render_finder(frame, frame.size(), finder, &app.items);
+    }
+    if app.show_help {
+        render_help_popup(frame, frame.size(), &app.keymap);
+    }
+}
+
+/// The full URL of the selected asset, or the whole name of the selected group, as the table
+/// cuts long ones short. Queries have their own panel, and the time views' dates always fit.
+fn selected_detail(app: &App) -> Option<String> {
+    if matches!(app.view_mode, ViewMode::Time | ViewMode::Heatmap) {
+        return None;
+    }
+    let row = app.items.get(app.table_state.selected()?)?;
+    if row.is_group || selected_query(app).is_some() {
+        return None;
+    }
+    // Other rows link to a sample request, which would pass for the row itself.
+    let asset = app.drill_down.is_some()
+        || matches!(
+            app.view_mode,
+            ViewMode::Path | ViewMode::Slow | ViewMode::Errors
+        );
+    match &row.open_url {
+        Some(url) if asset => Some(url.clone()),
+        _ => Some(row.label.clone()),
+    }
+}
+
+/// The pretty-printed GROQ query and parameters of the selected row, if it is a query.
+fn selected_query(app: &App) -> Option<(String, Vec<(String, String)>)> {
+    let row = app.items.get(app.table_state.selected()?)?;
+    if row.req_type != RequestType::Query || row.is_group {
+        return None;
+    }
+    let url = Url::parse(row.open_url.as_deref()?).ok()?;
+    let query = groq::query_text(&url)?;
+    Some((groq::pretty(&query), groq::params(&url)))
+}
+
+fn render_query_detail(frame: &mut Frame, area: Rect, query: &str, params: &[(String, String)]) {
+    let block = Block::default().borders(Borders::ALL).title(" GROQ Query ");
+    let mut depth = 0usize;
+    let mut lines: Vec<Line> = query
+        .lines()
+        .map(|line| groq_line(line, &mut depth))
+        .collect();
+    if !params.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Params (from a sample request)",
+            theme().dim,
+        )));
+        lines.extend(params.iter().map(|(name, value)| {
+            Line::from(vec![
+                Span::styled(name.clone(), theme().name),
+                Span::raw(" = "),
+                Span::raw(value.clone()),
+            ])
+        }));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Highlights one line of a pretty-printed query: strings in green, `$params` in magenta, and
+/// projections in cyan. `depth` carries the projection nesting over to the next line.
+fn groq_line(line: &str, depth: &mut usize) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut chars = line.chars().peekable();
+    let mut text = String::new();
+    let plain = |depth: usize| {
+        if depth > 0 {
+            theme().accent
+        } else {
+            Style::default()
+        }
+    };
+    let flush = |text: &mut String, spans: &mut Vec<Span<'static>>, style: Style| {
+        if !text.is_empty() {
+            spans.push(Span::styled(std::mem::take(text), style));
+        }
+    };
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' | '\'' => {
+                flush(&mut text, &mut spans, plain(*depth));
+                let mut literal = ch.to_string();
+                let mut escaped = false;
+                for inner in chars.by_ref() {
+                    literal.push(inner);
+                    match inner {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        _ if inner == ch => break,
+                        _ => {}
+                    }
+                }
+                spans.push(Span::styled(literal, theme().good));
+            }
+            '$' => {
+                flush(&mut text, &mut spans, plain(*depth));
+                let mut param = ch.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
                     }
+                    param.push(next);
+                    chars.next();
+                }
+                spans.push(Span::styled(param, theme().name));
+            }
+            '{' | '}' => {
+                flush(&mut text, &mut spans, plain(*depth));
+                if ch == '{' {
+                    *depth += 1;
+                } else {
+                    *depth = depth.saturating_sub(1);
                 }
+                spans.push(Span::styled(
+                    ch.to_string(),
+                    theme().accent.add_modifier(Modifier::BOLD),
+                ));
             }
+            _ => text.push(ch),
+        }
+    }
+    flush(&mut text, &mut spans, plain(*depth));
+    Line::from(spans)
+}
+
+fn render_header(frame: &mut Frame, area: Rect, app: &App) {
+    // The preset and type toggles get room of their own so they stay visible however many tabs
+    // there are.
+    let filters = header_filters(app);
+    let filters_width = if filters.width() > 0 {
+        filters.width() as u16 + 2
+    } else {
+        0
+    };
+    let chunks = Layout::horizontal([
+        Constraint::Length(22),
+        Constraint::Min(0),
+        Constraint::Length(filters_width),
+    ])
+    .split(area);
+    render_title(frame, chunks[0]);
+    frame.render_widget(
+        Paragraph::new(filters).alignment(Alignment::Right),
+        chunks[2],
+    );
+    let (start, end) = visible_tabs(chunks[1].width, app.view_mode.index());
+    let tabs_width = ViewMode::ALL[start..end]
+        .iter()
+        .map(|mode| tab_width(*mode))
+        .sum::<u16>()
+        + 1;
+    let right = Layout::horizontal([
+        Constraint::Length(1),
+        Constraint::Length(tabs_width),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .split(chunks[1]);
+    let marker = |shown: bool, symbol: &'static str| {
+        Paragraph::new(if shown { symbol } else { "" }).style(theme().dim)
+    };
+    frame.render_widget(marker(start > 0, "‹"), right[0]);
+    render_tabs(frame, right[1], app, start, end);
+    frame.render_widget(marker(end < ViewMode::ALL.len(), "›"), right[2]);
+    render_tabs_hint(frame, right[3], app);
+}
+
+/// The applied filter preset, and the `I`/`F`/`Q`/`O` toggles with hidden types crossed out.
+fn header_filters(app: &App) -> Line<'static> {
+    let mut spans = Vec::new();
+    if let Some(pattern) = &app.referer.pattern {
+        spans.push(Span::styled(format!("referer ~ {pattern}"), theme().accent));
+    }
+    if let Some(index) = app.preset {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(app.presets[index].0.clone(), theme().name));
+    }
+    if app.type_filter.is_active() {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
         }
-        KeyCode::Char('r') => app.set_sort(SortField::Requests),
-        KeyCode::Char('s') => app.set_sort(SortField::AvgRequestSize),
-        KeyCode::Char('b') => app.set_sort(SortField::Bandwidth),
-        KeyCode::Char('d') => app.set_sort(SortField::Path),
-        KeyCode::Char('e') => app.set_sort(SortField::Ext),
-        _ => {}
+        spans.extend(TypeFilter::KEYS.iter().map(|&(key, kind)| {
+            let style = if app.type_filter.shows(kind) {
+                kind.style()
+            } else {
+                theme().dim.add_modifier(Modifier::CROSSED_OUT)
+            };
+            Span::styled(key.to_string(), style)
+        }));
     }
-    false
+    Line::from(spans)
 }
 
-fn render(frame: &mut Frame, app: &mut App) {
-    let chunks = Layout::vertical([
-        Constraint::Length(1),
-        Constraint::Min(1),
-        Constraint::Length(1),
-    ])
-    .split(frame.size());
-    render_header(frame, chunks[0], app);
-    render_table(frame, chunks[1], app);
-    render_footer(frame, chunks[2]);
-    if app.show_help {
-        render_help_popup(frame, frame.size());
+/// The filters in effect, numbered for removal with `-` and a digit. Row filters that don't
+/// apply to the view being shown are dimmed.
+fn render_active_filters(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    filters: &[(ActiveFilter, String)],
+) {
+    let number_style = if app.removing_filter {
+        theme().warning.add_modifier(Modifier::REVERSED)
+    } else {
+        theme().warning
+    };
+    let mut spans = vec![Span::styled("Filters", theme().dim)];
+    for (idx, (filter, label)) in filters.iter().enumerate() {
+        spans.push(Span::raw("  "));
+        if idx < 9 {
+            spans.push(Span::styled(format!("{}", idx + 1), number_style));
+            spans.push(Span::raw(" "));
+        }
+        let style = if app.applies(*filter) {
+            theme().accent
+        } else {
+            theme().dim
+        };
+        spans.push(Span::styled(label.clone(), style));
     }
+    let hint = if app.removing_filter {
+        "number to remove · any other key cancels"
+    } else {
+        "- then a number removes"
+    };
+    let chunks = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(hint.chars().count() as u16 + 1),
+    ])
+    .split(area);
+    frame.render_widget(Paragraph::new(Line::from(spans)), chunks[0]);
+    frame.render_widget(
+        Paragraph::new(hint)
+            .alignment(Alignment::Right)
+            .style(theme().dim),
+        chunks[1],
+    );
 }
 
-fn render_header(frame: &mut Frame, area: Rect, app: &App) {
-    let chunks = Layout::horizontal([Constraint::Length(22), Constraint::Min(0)]).split(area);
-    render_title(frame, chunks[0]);
-    let right = Layout::horizontal([Constraint::Length(22), Constraint::Min(0)]).split(chunks[1]);
-    render_tabs(frame, right[0], app);
-    render_tabs_hint(frame, right[1]);
+fn tab_width(mode: ViewMode) -> u16 {
+    mode.title().len() as u16 + 3
+}
+
+/// The range of tabs to draw in `width` cells. When they don't all fit, the window grows
+/// outwards from the selected tab.
+fn visible_tabs(width: u16, selected: usize) -> (usize, usize) {
+    let widths: Vec<u16> = ViewMode::ALL.iter().map(|mode| tab_width(*mode)).collect();
+    if widths.iter().sum::<u16>() + 3 <= width {
+        return (0, widths.len());
+    }
+    let budget = width.saturating_sub(3);
+    let (mut start, mut end) = (selected, selected + 1);
+    let mut used = widths[selected];
+    loop {
+        let mut grew = false;
+        if end < widths.len() && used + widths[end] <= budget {
+            used += widths[end];
+            end += 1;
+            grew = true;
+        }
+        if start > 0 && used + widths[start - 1] <= budget {
+            start -= 1;
+            used += widths[start];
+            grew = true;
+        }
+        if !grew {
+            return (start, end);
+        }
+    }
 }
 
 fn render_title(frame: &mut Frame, area: Rect) {
@@ -313,7 +3615,7 @@ fn render_title(frame: &mut Frame, area: Rect) {
     frame.render_widget(title, area);
 }
 
-fn render_help_popup(frame: &mut Frame, area: Rect) {
+fn render_help_popup(frame: &mut Frame, area: Rect, keymap: &Keymap) {
     let popup = centered_rect_clamped(70, 60, 20, area);
     let block = Block::default()
         .borders(Borders::ALL)
@@ -331,42 +3633,86 @@ fn render_help_popup(frame: &mut Frame, area: Rect) {
         .style(Style::default().add_modifier(Modifier::BOLD));
     let version = Paragraph::new(version)
         .alignment(Alignment::Right)
-        .style(Style::default().fg(Color::DarkGray));
+        .style(theme().dim);
     let subtitle = Paragraph::new("Keyboard Shortcuts")
         .alignment(Alignment::Left)
         .style(Style::default());
-    let key_style = Style::default().fg(Color::Cyan);
+    let key_style = theme().accent;
     let key_width = 10;
     let key_cell = |label: &str| Span::styled(format!("{label:<key_width$}"), key_style);
-    let spacer = Span::raw("  ");
-    let shortcuts = vec![
-        ListItem::new(Line::from(vec![
-            key_cell("↑/↓ or j/k"),
-            spacer.clone(),
-            Span::raw("move selection"),
-        ])),
-        ListItem::new(Line::from(vec![
-            key_cell("←/→ or h/l"),
-            spacer.clone(),
-            Span::raw("switch tabs"),
-        ])),
-        ListItem::new(Line::from(vec![
-            key_cell("Tab"),
-            spacer.clone(),
-            Span::raw("next tab"),
-        ])),
-        ListItem::new(Line::from(vec![
-            key_cell("Enter"),
-            spacer.clone(),
-            Span::raw("open selected asset"),
-        ])),
+    let label = |action: Action| keymap.label(action);
+    let pair = |first: Action, second: Action| format!("{}, {}", label(first), label(second));
+    let types = [
+        RequestType::Image,
+        RequestType::File,
+        RequestType::Query,
+        RequestType::Other,
+    ]
+    .map(|kind| label(Action::ToggleType(kind)))
+    .join(", ");
+    let shortcuts: Vec<ListItem> = [
+        (pair(Action::Up, Action::Down), "move selection"),
+        (pair(Action::PageUp, Action::PageDown), "move a page"),
+        (
+            pair(Action::HalfPageUp, Action::HalfPageDown),
+            "move half a page",
+        ),
+        (pair(Action::Top, Action::Bottom), "first / last row"),
+        (label(Action::GoToRow), "go to a row by number"),
+        (label(Action::Mark), "mark rows to total them"),
+        (label(Action::Pin), "pin a row to the top"),
+        (pair(Action::PreviousView, Action::NextView), "switch tabs"),
+        (label(Action::CycleView), "next tab"),
+        (label(Action::Open), "asset variants / open URL"),
+        (
+            pair(Action::CopyUrl, Action::CopyId),
+            "copy the row's URL / asset ID",
+        ),
+        (label(Action::Errors), "show parse errors"),
+        (label(Action::Reload), "reload logs"),
+        (label(Action::TimeRange), "filter by time range"),
+        (label(Action::Trends), "toggle trend sparklines"),
+        (label(Action::Percentiles), "toggle size percentiles"),
+        (label(Action::Columns), "show/hide columns"),
+        (label(Action::Units), "binary/decimal size units"),
+        (label(Action::ExactNumbers), "exact/abbreviated numbers"),
+        (
+            pair(Action::ScrollLeft, Action::ScrollRight),
+            "scroll long IDs",
+        ),
+        (label(Action::Histogram), "response size histogram"),
+        (label(Action::RawRecords), "raw log records"),
+        (
+            label(Action::SaveRecords),
+            "save every log line behind the row",
+        ),
+        (label(Action::ExportCsv), "save the rows shown as CSV"),
+        (label(Action::CopyTable), "copy the table as TSV"),
+        (
+            label(Action::Screenshot),
+            "save the screen as text and ANSI",
+        ),
+        (label(Action::Filter), "filter rows (Esc clears)"),
+        (types, "hide/show images, files, queries, other"),
+        (label(Action::MinBandwidth), "hide rows under a bandwidth"),
+        (label(Action::Referer), "only count a referer"),
+        (label(Action::NextPreset), "next filter preset"),
+        (label(Action::Focus), "only count the selected row"),
+        (label(Action::RemoveFilter), "remove a filter by its number"),
+        (label(Action::Find), "find and jump to a row"),
+        (label(Action::Granularity), "hourly/daily buckets"),
+        (label(Action::Back), "close help"),
+        (label(Action::Quit), "quit"),
+    ]
+    .into_iter()
+    .map(|(keys, description)| {
         ListItem::new(Line::from(vec![
-            key_cell("Esc"),
-            spacer.clone(),
-            Span::raw("close help"),
-        ])),
-        ListItem::new(Line::from(vec![key_cell("q"), spacer, Span::raw("quit")])),
-    ];
+            key_cell(&keys),
+            Span::raw("  "),
+            Span::raw(description),
+        ]))
+    })
+    .collect();
     let shortcut_count = shortcuts.len() as u16;
     let list = List::new(shortcuts);
     let details = Text::from(vec![
@@ -376,21 +3722,45 @@ fn render_help_popup(frame: &mut Frame, area: Rect) {
         )),
         Line::from("Use the underlined column letter to sort."),
         Line::from("Press again to toggle asc/desc."),
+        Line::from(format!(
+            "{} then a letter breaks ties.",
+            label(Action::ThenSort)
+        )),
         Line::from(""),
         Line::from(Span::styled(
             "Types",
             Style::default().add_modifier(Modifier::BOLD),
         )),
         Line::from(vec![
-            Span::styled("I", Style::default().fg(RequestType::Image.color())),
+            Span::styled("I", RequestType::Image.style()),
             Span::raw(" image"),
             Span::raw("    "),
-            Span::styled("F", Style::default().fg(RequestType::File.color())),
+            Span::styled("F", RequestType::File.style()),
             Span::raw(" file"),
             Span::raw("    "),
-            Span::styled("Q", Style::default().fg(RequestType::Query.color())),
+            Span::styled("Q", RequestType::Query.style()),
             Span::raw(" query"),
         ]),
+        Line::from(vec![
+            Span::styled("M", RequestType::Mutation.style()),
+            Span::raw(" mutate"),
+            Span::raw("   "),
+            Span::styled("D", RequestType::Document.style()),
+            Span::raw(" doc"),
+            Span::raw("     "),
+            Span::styled("H", RequestType::History.style()),
+            Span::raw(" history"),
+        ]),
+        Line::from(vec![
+            Span::styled("L", RequestType::Listen.style()),
+            Span::raw(" listen"),
+            Span::raw("   "),
+            Span::styled("X", RequestType::Export.style()),
+            Span::raw(" export"),
+            Span::raw("  "),
+            Span::styled("U", RequestType::Upload.style()),
+            Span::raw(" upload"),
+        ]),
     ]);
     let content = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(header[1]);
     let content_area = content[1];
@@ -438,80 +3808,459 @@ fn centered_rect_clamped(percent_x: u16, percent_y: u16, min_height: u16, rect:
     }
 }
 
-fn render_tabs(frame: &mut Frame, area: Rect, app: &App) {
+fn render_tabs(frame: &mut Frame, area: Rect, app: &App, start: usize, end: usize) {
     let base_style = Style::default();
-    let titles = ["By Asset", "By Type"]
+    let titles = ViewMode::ALL[start..end]
         .iter()
-        .map(|title| Line::from(Span::styled(*title, base_style)))
+        .map(|mode| Line::from(Span::styled(mode.title(), base_style)))
         .collect::<Vec<_>>();
-    let selected = match app.view_mode {
-        ViewMode::Path => 0,
-        ViewMode::Type => 1,
-    };
     let tabs = Tabs::new(titles)
-        .select(selected)
+        .select(app.view_mode.index() - start)
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
         .divider(Span::raw(" "))
         .padding(" ", " ");
     frame.render_widget(tabs, area);
 }
 
-fn render_tabs_hint(frame: &mut Frame, area: Rect) {
-    let hint = Paragraph::new("←→ switch tabs")
+fn render_tabs_hint(frame: &mut Frame, area: Rect, app: &App) {
+    let mut spans = Vec::new();
+    if let Some(filter) = &app.time_filter {
+        spans.push(Span::styled(filter.label.clone(), theme().accent));
+        spans.push(Span::raw(" · "));
+    }
+    spans.push(Span::raw("←→ switch tabs"));
+    // Drop the key hint rather than cutting it off when the tabs leave too little room.
+    let width: usize = spans.iter().map(|span| span.width()).sum();
+    if width > area.width as usize {
+        spans.pop();
+        spans.pop_if(|span| span.content == " · ");
+    }
+    let hint = Paragraph::new(Line::from(spans))
         .alignment(Alignment::Right)
-        .style(Style::default().fg(Color::DarkGray));
+        .style(theme().dim);
     frame.render_widget(hint, area);
 }
 fn render_table(frame: &mut Frame, area: Rect, app: &mut App) {
-    let id_width = id_column_width(area.width);
-    let header = Row::new([
-        type_header_cell(),
-        header_cell("ID", 'd', app, SortField::Path),
-        header_cell("Ext", 'e', app, SortField::Ext),
-        header_cell_aligned("Requests", 'r', app, SortField::Requests, Alignment::Right),
-        header_cell_aligned(
-            "Size (Avg)",
-            's',
-            app,
-            SortField::AvgRequestSize,
-            Alignment::Right,
-        ),
-        header_cell_aligned(
-            "Bandwidth",
-            'b',
-            app,
-            SortField::Bandwidth,
-            Alignment::Right,
-        ),
-    ])
+    let narrow = area.width < NARROW_WIDTH;
+    let (columns, column_widths) = fit_columns(app.columns(), app.largest_traffic(), area.width);
+    let id_width = id_column_width(area.width, &column_widths);
+    let longest_id = app
+        .items
+        .iter()
+        .map(|row| row.label.width())
+        .max()
+        .unwrap_or(0);
+    app.id_scroll = app.id_scroll.min(longest_id.saturating_sub(id_width));
+    let header = Row::new(
+        columns
+            .iter()
+            .map(|column| header_for(*column, app, narrow)),
+    )
     .style(Style::default().add_modifier(Modifier::BOLD));
 
     let visible_rows = visible_row_count(area.height);
     let content_rows = visible_rows.saturating_sub(3);
-    let (start, end) = visible_range(&app.items, app.table_state.selected(), content_rows);
+    let (start, end) = visible_range(&app.items, &mut app.table_state, content_rows);
+    app.page_rows = (end - start).max(1);
     let selected_index = app.table_state.selected();
     let rows = app.items[start..end].iter().enumerate().map(|(idx, item)| {
-        let is_selected = selected_index == Some(start + idx);
-        row_for_item(item, id_width, app.view_mode, is_selected)
+        let state = RowState {
+            selected: selected_index == Some(start + idx),
+            marked: item
+                .key
+                .as_ref()
+                .is_some_and(|key| app.marked.contains(key)),
+            pinned: app.is_pinned(item),
+        };
+        row_for_item(
+            item,
+            &columns,
+            id_width,
+            app.id_scroll,
+            app.view_mode,
+            state,
+        )
     });
 
-    let divider_top = divider_row(id_width);
-    let divider_bottom = divider_row(id_width);
-    let totals_row = totals_row(&app.base_items, id_width);
+    let divider_top = divider_row(&column_widths, id_width);
+    let divider_bottom = divider_row(&column_widths, id_width);
+    // Grouped views may leave requests out (Transforms only counts images), so total the groups.
+    let totals_row = totals_row(
+        app.shown_stats(),
+        app.total_size_percentiles,
+        app.total_latency_p95,
+        app.baseline_totals(),
+        &columns,
+        id_width,
+    );
     let rows = std::iter::once(divider_top)
         .chain(rows)
         .chain(std::iter::once(divider_bottom))
         .chain(std::iter::once(totals_row));
 
+    let widths = columns
+        .iter()
+        .zip(&column_widths)
+        .map(|(column, width)| match column {
+            Column::Id => Constraint::Length(id_width as u16),
+            _ => Constraint::Length(*width),
+        });
+    let mut block = match &app.drill_down {
+        Some(drill_down) => Block::default().borders(Borders::ALL).title(format!(
+            " Variants of {} ({} to go back) ",
+            drill_down.title,
+            app.keymap.label(Action::Back)
+        )),
+        None => match &app.baseline {
+            Some(baseline) if app.view_mode == ViewMode::Path => Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Changes since {} ", baseline.label)),
+            _ => Block::default().borders(Borders::ALL),
+        },
+    };
+    if let Some(input) = &app.referer.input {
+        let style = if cli::parse_pattern(input).is_ok() {
+            theme().warning
+        } else {
+            theme().error
+        };
+        block = block.title(
+            Line::from(Span::styled(format!(" referer: {input}█ "), style))
+                .alignment(Alignment::Right),
+        );
+    }
+    if let Some(input) = &app.go_to_row {
+        let style = if parse_row_number(input).is_some() || input.trim().is_empty() {
+            theme().warning
+        } else {
+            theme().error
+        };
+        block = block.title(
+            Line::from(Span::styled(format!(" go to row: {input}█ "), style))
+                .alignment(Alignment::Right),
+        );
+    }
+    if let Some(input) = &app.min_bandwidth.input {
+        let style = if cli::parse_size(input).is_ok() || input.trim().is_empty() {
+            theme().warning
+        } else {
+            theme().error
+        };
+        block = block.title(
+            Line::from(Span::styled(format!(" min bandwidth: {input}█ "), style))
+                .alignment(Alignment::Right),
+        );
+    } else if let Some(min) = app.min_bandwidth.bytes {
+        let style = if app.is_hiding_small_rows() {
+            theme().warning
+        } else {
+            theme().dim
+        };
+        block = block.title(
+            Line::from(Span::styled(format!(" ≥ {} ", format_bytes(min)), style))
+                .alignment(Alignment::Right),
+        );
+    }
+    if app.id_scroll > 0 {
+        block = block.title(
+            Line::from(Span::styled(
+                format!(" IDs from character {} ", app.id_scroll + 1),
+                theme().dim,
+            ))
+            .alignment(Alignment::Right),
+        );
+    }
+    if app.filter.editing || !app.filter.text.is_empty() {
+        let cursor = if app.filter.editing { "█" } else { "" };
+        let style = if app.filter.regex().is_err() {
+            theme().error
+        } else if app.is_filtering() || app.filter.editing {
+            theme().warning
+        } else {
+            theme().dim
+        };
+        block = block.title(
+            Line::from(Span::styled(
+                format!(" /{}{cursor} ", app.filter.text),
+                style,
+            ))
+            .alignment(Alignment::Right),
+        );
+    }
+    if let Some(selected) = app.table_state.selected()
+        && !app.items.is_empty()
+    {
+        block = block.title_bottom(
+            Line::from(Span::styled(
+                format!(
+                    " row {}/{} ",
+                    group_digits(selected as u64 + 1),
+                    group_digits(app.items.len() as u64)
+                ),
+                theme().dim,
+            ))
+            .alignment(Alignment::Right),
+        );
+    }
+    let table = Table::new(rows, widths)
+        .header(header)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(block);
+
+    let mut view_state = TableState::default();
+    if let Some(selected) = app.table_state.selected()
+        && selected >= start
+        && selected < end
+    {
+        view_state.select(Some(selected - start + 1));
+    }
+
+    frame.render_stateful_widget(table, area, &mut view_state);
+
+    // Drawn over the right border beside the rows, between the dividers, when they don't all fit.
+    if app.items.len() > content_rows {
+        let track = Rect {
+            y: area.y + 3,
+            height: (end - start) as u16,
+            ..area
+        };
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some("│"))
+            .thumb_style(theme().accent);
+        let mut scrollbar_state = ScrollbarState::new(app.items.len())
+            .position(app.table_state.selected().unwrap_or(start));
+        frame.render_stateful_widget(scrollbar, track, &mut scrollbar_state);
+    }
+}
+
+fn header_for(column: Column, app: &App, narrow: bool) -> Cell<'static> {
+    let title = if narrow {
+        column.short_title()
+    } else {
+        column.title()
+    };
+    match column {
+        Column::Type => type_header_cell(),
+        Column::Id => header_cell(app.id_title(), app, SortField::Path),
+        Column::Ext => header_cell(title, app, SortField::Ext),
+        Column::Trend => Cell::from(title),
+        Column::Requests => header_cell_aligned(title, app, SortField::Requests, Alignment::Right),
+        Column::AvgSize => {
+            header_cell_aligned(title, app, SortField::AvgRequestSize, Alignment::Right)
+        }
+        Column::Bandwidth => {
+            header_cell_aligned(title, app, SortField::Bandwidth, Alignment::Right)
+        }
+        Column::Unoptimized => {
+            header_cell_aligned(title, app, SortField::Unoptimized, Alignment::Right)
+        }
+        Column::Variants => header_cell_aligned(title, app, SortField::Variants, Alignment::Right),
+        Column::CacheHit => header_cell_aligned(title, app, SortField::CacheHit, Alignment::Right),
+        Column::P50 | Column::P95 | Column::P99 | Column::LatencyP95 | Column::RequestsDelta => {
+            right_cell(title.to_string())
+        }
+        Column::Latency => header_cell_aligned(title, app, SortField::Latency, Alignment::Right),
+        Column::BandwidthDelta => {
+            header_cell_aligned(title, app, SortField::BandwidthDelta, Alignment::Right)
+        }
+        Column::Change => header_cell_aligned(title, app, SortField::Change, Alignment::Right),
+        Column::RequestSize => {
+            header_cell_aligned(title, app, SortField::RequestSize, Alignment::Right)
+        }
+        Column::ClientErrors => {
+            header_cell_aligned(title, app, SortField::ClientErrors, Alignment::Right)
+        }
+        Column::ServerErrors => {
+            header_cell_aligned(title, app, SortField::ServerErrors, Alignment::Right)
+        }
+        Column::ErrorRate => {
+            header_cell_aligned(title, app, SortField::ErrorRate, Alignment::Right)
+        }
+    }
+}
+
+fn render_timeline(frame: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::vertical([Constraint::Percentage(40), Constraint::Min(6)]).split(area);
+    let granularity = app.effective_granularity();
+    let buckets = app.timeline_buckets(granularity);
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " Bandwidth per {} ({} to toggle) ",
+        granularity.label(),
+        app.keymap.label(Action::Granularity)
+    ));
+    let inner = block.inner(chunks[0]);
+    frame.render_widget(block, chunks[0]);
+    if buckets.is_empty() {
+        let message = Paragraph::new("No timestamped requests")
+            .alignment(Alignment::Center)
+            .style(theme().dim);
+        frame.render_widget(message, inner);
+    } else {
+        // Show as many of the most recent buckets as fit, widening bars when there is room.
+        let width = inner.width as usize;
+        let bar_width = if buckets.len() * 3 <= width { 2 } else { 1 };
+        let gap = usize::from(bar_width > 1);
+        let fits = (width / (bar_width + gap)).max(1);
+        let shown = &buckets[buckets.len().saturating_sub(fits)..];
+        let bars: Vec<Bar> = shown
+            .iter()
+            .map(|(_, bucket)| {
+                Bar::default()
+                    .value(bucket.bandwidth)
+                    .text_value(String::new())
+            })
+            .collect();
+        let chart = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width as u16)
+            .bar_gap(gap as u16)
+            .bar_style(theme().accent);
+        frame.render_widget(chart, inner);
+    }
+    render_timeline_table(frame, chunks[1], app);
+}
+
+/// Days down the side, UTC hours across the top, each cell colored by that hour's bandwidth.
+fn render_heatmap(frame: &mut Frame, area: Rect, app: &mut App) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Bandwidth by day and hour (UTC) ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    if app.items.is_empty() {
+        let message = Paragraph::new("No timestamped requests")
+            .alignment(Alignment::Center)
+            .style(theme().dim);
+        frame.render_widget(message, inner);
+        return;
+    }
+
+    let label_width = 11;
+    let cell_width = if inner.width as usize >= label_width + 24 * 3 + 12 {
+        3
+    } else {
+        2
+    };
+    let max = app
+        .timeline
+        .values()
+        .map(|bucket| bucket.bandwidth)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let heat = |bandwidth: u64| {
+        let levels = theme().heat.len() as u64 - 1;
+        let level = (bandwidth * levels).div_ceil(max).min(levels);
+        theme().heat[level as usize]
+    };
+
+    let mut hours = vec![Span::raw(" ".repeat(label_width))];
+    hours.extend((0..24).map(|hour| Span::styled(format!("{hour:<cell_width$}"), theme().dim)));
+    let mut lines = vec![Line::from(hours)];
+
+    let content_rows = inner.height.saturating_sub(3) as usize;
+    let (start, end) = visible_range(&app.items, &mut app.table_state, content_rows + 1);
+    app.page_rows = (end - start).max(1);
+    let days = app.timeline_buckets(Granularity::Day);
+    for (idx, (day_start, day)) in days.iter().enumerate().take(end).skip(start) {
+        let selected = app.table_state.selected() == Some(idx);
+        let label_style = if selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![
+            Span::styled(Granularity::Day.format(*day_start), label_style),
+            Span::raw(" "),
+        ];
+        spans.extend((0..24).map(|hour| {
+            let bandwidth = app
+                .timeline
+                .get(&(day_start + hour * HOUR_SECONDS))
+                .map_or(0, |bucket| bucket.bandwidth);
+            let (fill, style) = heat(bandwidth);
+            Span::styled(fill.to_string().repeat(cell_width), style)
+        }));
+        spans.push(Span::raw(format!(" {:>10}", format_bytes(day.bandwidth))));
+        lines.push(Line::from(spans));
+    }
+
+    let mut legend = vec![Span::styled(
+        format!("{:label_width$}less ", ""),
+        theme().dim,
+    )];
+    legend.extend(
+        theme()
+            .heat
+            .iter()
+            .map(|(fill, style)| Span::styled(fill.to_string().repeat(2), *style)),
+    );
+    legend.push(Span::styled(
+        format!(" more · busiest hour {}", format_bytes(max)),
+        theme().dim,
+    ));
+    let legend_area = Rect {
+        y: inner.y + inner.height.saturating_sub(1),
+        height: 1.min(inner.height),
+        ..inner
+    };
+    frame.render_widget(Paragraph::new(lines), inner);
+    frame.render_widget(Paragraph::new(Line::from(legend)), legend_area);
+}
+
+fn render_timeline_table(frame: &mut Frame, area: Rect, app: &mut App) {
+    let [requests_width, avg_width, bandwidth_width] = column_widths(
+        &[Column::Requests, Column::AvgSize, Column::Bandwidth],
+        app.largest_traffic(),
+        false,
+    )[..] else {
+        unreachable!()
+    };
+    let time_width = id_column_width(area.width, &[requests_width, avg_width, bandwidth_width, 0]);
+    let header = Row::new([
+        header_cell("Time", app, SortField::Path),
+        header_cell_aligned("Requests", app, SortField::Requests, Alignment::Right),
+        header_cell_aligned(
+            "Size (Avg)",
+            app,
+            SortField::AvgRequestSize,
+            Alignment::Right,
+        ),
+        header_cell_aligned("Bandwidth", app, SortField::Bandwidth, Alignment::Right),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let content_rows = visible_row_count(area.height).saturating_sub(1);
+    let (start, end) = visible_range(&app.items, &mut app.table_state, content_rows);
+    app.page_rows = (end - start).max(1);
+    let rows = app.items[start..end].iter().map(|item| {
+        Row::new([
+            Cell::from(item.label.clone()),
+            right_cell(format_count(item.request_count)),
+            right_cell(format_bytes(item.avg_size())),
+            right_cell(format_bytes(item.bandwidth_sum)),
+        ])
+    });
+    let divider = Row::new(
+        [
+            time_width as u16,
+            requests_width,
+            avg_width,
+            bandwidth_width,
+        ]
+        .map(|width| Cell::from("─".repeat(width as usize))),
+    )
+    .style(theme().dim);
+
     let table = Table::new(
-        rows,
+        std::iter::once(divider).chain(rows),
         [
-            Constraint::Length(2),
-            Constraint::Length(id_width as u16),
-            Constraint::Length(8),
-            Constraint::Length(10),
-            Constraint::Length(12),
-            Constraint::Length(14),
+            Constraint::Length(time_width as u16),
+            Constraint::Length(requests_width),
+            Constraint::Length(avg_width),
+            Constraint::Length(bandwidth_width),
         ],
     )
     .header(header)
@@ -519,49 +4268,601 @@ fn render_table(frame: &mut Frame, area: Rect, app: &mut App) {
     .block(Block::default().borders(Borders::ALL));
 
     let mut view_state = TableState::default();
-    if let Some(selected) = app.table_state.selected() {
-        if selected >= start && selected < end {
-            view_state.select(Some(selected - start + 1));
+    if let Some(selected) = app.table_state.selected()
+        && selected >= start
+        && selected < end
+    {
+        view_state.select(Some(selected - start + 1));
+    }
+    frame.render_stateful_widget(table, area, &mut view_state);
+}
+
+fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let hint = format!("Press {} for help", app.keymap.label(Action::Help));
+    let chunks = Layout::horizontal([
+        Constraint::Length(hint.chars().count() as u16 + 2),
+        Constraint::Min(0),
+    ])
+    .split(area);
+    let footer = Paragraph::new(hint)
+        .alignment(Alignment::Left)
+        .style(theme().dim);
+    frame.render_widget(footer, chunks[0]);
+    match app.loading {
+        _ if let Some(notice) = &app.notice => render_notice(frame, chunks[1], notice),
+        _ if !app.marked.is_empty() => render_marked_totals(frame, chunks[1], app),
+        Some(progress) => render_load_progress(frame, chunks[1], app, progress),
+        None if app.reload_pending => render_reload_prompt(frame, chunks[1], app),
+        None => render_load_summary(frame, chunks[1], app),
+    }
+}
+
+fn render_notice(frame: &mut Frame, area: Rect, notice: &Result<String, String>) {
+    let (text, style) = match notice {
+        Ok(text) => (text, theme().good),
+        Err(text) => (text, theme().error),
+    };
+    let notice = Paragraph::new(text.as_str())
+        .alignment(Alignment::Right)
+        .style(style);
+    frame.render_widget(notice, area);
+}
+
+/// The combined traffic of the marked rows, and their share of the rows shown.
+fn render_marked_totals(frame: &mut Frame, area: Rect, app: &App) {
+    let (rows, requests, bandwidth) =
+        app.marked_rows()
+            .fold((0u64, 0u64, 0u64), |(rows, requests, bandwidth), row| {
+                (
+                    rows + 1,
+                    requests + row.request_count,
+                    bandwidth + row.bandwidth_sum,
+                )
+            });
+    let total: u64 = app
+        .shown_stats()
+        .iter()
+        .map(|stats| stats.bandwidth_sum)
+        .sum();
+    let text = format!(
+        "{} marked · {} requests · {} ({} of bandwidth) · {} to clear",
+        format_count(rows),
+        format_count(requests),
+        format_bytes(bandwidth),
+        report::share(bandwidth, total),
+        app.keymap.label(Action::Back)
+    );
+    let totals = Paragraph::new(text)
+        .alignment(Alignment::Right)
+        .style(theme().name);
+    frame.render_widget(totals, area);
+}
+
+fn render_reload_prompt(frame: &mut Frame, area: Rect, app: &App) {
+    let prompt = Paragraph::new(format!(
+        "Logs changed on disk · press {} to reload",
+        app.keymap.label(Action::Reload)
+    ))
+    .alignment(Alignment::Right)
+    .style(theme().warning);
+    frame.render_widget(prompt, area);
+}
+
+fn render_loading(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default().borders(Borders::ALL);
+    let inner = block.inner(area);
+    let progress = app.loading.unwrap_or_default();
+    let text = Text::from(vec![
+        Line::from(Span::styled(
+            "Loading logs…",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(load_progress_text(app, progress)),
+    ]);
+    let top = inner.y + inner.height.saturating_sub(3) / 2;
+    let text_area = Rect {
+        y: top,
+        height: inner.height.min(3),
+        ..inner
+    };
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(text).alignment(Alignment::Center), text_area);
+}
+
+fn render_load_progress(frame: &mut Frame, area: Rect, app: &App, progress: LoadProgress) {
+    let status = Paragraph::new(format!("Loading… {}", load_progress_text(app, progress)))
+        .alignment(Alignment::Right)
+        .style(theme().dim);
+    frame.render_widget(status, area);
+}
+
+fn load_progress_text(app: &App, progress: LoadProgress) -> String {
+    let lines = format!("{} lines", format_count(progress.lines));
+    let Some(total) = progress.total_bytes else {
+        return format!("{lines} · {}", format_bytes(progress.bytes));
+    };
+    let bytes = format!(
+        "{} of {}",
+        format_bytes(progress.bytes),
+        format_bytes(total)
+    );
+    if progress.bytes == 0 {
+        return format!("{lines} · {bytes}");
+    }
+    let elapsed = app.load_started.elapsed().as_secs_f64();
+    let remaining = total.saturating_sub(progress.bytes) as f64;
+    let eta = elapsed * remaining / progress.bytes as f64;
+    format!("{lines} · {bytes} · ETA {}", format_duration(eta as u64))
+}
+
+fn format_duration(seconds: u64) -> String {
+    if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// What's loaded and what narrows it: the file, lines read and skipped, the dates covered, and
+/// the filters in effect.
+fn render_load_summary(frame: &mut Frame, area: Rect, app: &App) {
+    let summary = app.summary;
+    let mut spans = vec![Span::raw(format!(
+        " · {} lines",
+        format_count(summary.line_count)
+    ))];
+    if let Some(span) = summary.time_span {
+        spans.push(Span::raw(format!(" · {}", format_time_span(span))));
+    }
+    if summary.filtered_out > 0 {
+        spans.push(Span::raw(format!(
+            " · {} filtered out",
+            format_count(summary.filtered_out)
+        )));
+    }
+    if summary.duplicates > 0 {
+        spans.push(Span::raw(format!(
+            " · {} duplicates",
+            format_count(summary.duplicates)
+        )));
+    }
+    let skipped = app.skipped_count();
+    if skipped > 0 {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled(
+            format!(
+                "{} skipped ({})",
+                format_count(skipped),
+                app.keymap.label(Action::Errors)
+            ),
+            theme().warning,
+        ));
+    }
+    // Listed on their own line once there's more than one, so only counted here.
+    let filters = app.active_filters();
+    let filters = match filters.as_slice() {
+        [] => None,
+        [(_, label)] => Some(label.clone()),
+        filters => Some(format!("{} filters", filters.len())),
+    };
+    if let Some(filters) = filters {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled(filters, theme().accent));
+    }
+    // The name gives way to the counts when space runs out.
+    let rest: usize = spans.iter().map(Span::width).sum();
+    let name = truncate_with_ellipsis(&input_name(app), (area.width as usize).saturating_sub(rest));
+    spans.insert(0, Span::raw(name));
+    let status = Paragraph::new(Line::from(spans))
+        .alignment(Alignment::Right)
+        .style(theme().dim);
+    frame.render_widget(status, area);
+}
+
+/// The file's name when there's one, such as `2024-06.ndjson.gz`, or how many there are.
+fn input_name(app: &App) -> String {
+    match app.inputs.as_slice() {
+        [Input::Path(path)] => path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(path)
+            .to_string(),
+        [Input::SanityApi { project, .. }] => format!("project {project}"),
+        _ if app.summary.file_count == 1 => "1 file".to_string(),
+        _ => format!("{} files", app.summary.file_count),
+    }
+}
+
+fn format_time_span(span: TimeSpan) -> String {
+    let first = span.first.strftime("%Y-%m-%d").to_string();
+    let last = span.last.strftime("%Y-%m-%d").to_string();
+    if first == last {
+        first
+    } else {
+        format!("{first} – {last}")
+    }
+}
+
+fn render_time_picker(frame: &mut Frame, area: Rect, picker: &TimePicker) {
+    let popup = centered_rect_clamped(50, 50, 14, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::uniform(1));
+    let inner = block.inner(popup);
+    let chunks = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(0),
+        Constraint::Length(2),
+    ])
+    .split(inner);
+    let title = Paragraph::new("Time Range").style(Style::default().add_modifier(Modifier::BOLD));
+    let body = match &picker.custom {
+        Some(input) => Text::from(vec![
+            Line::from(vec![
+                Span::raw("FROM..TO: "),
+                Span::raw(input.as_str()),
+                Span::raw("▏"),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(
+                "e.g. 2024-06-01..2024-06-08, 2024-06-01.., ..3d",
+                theme().dim,
+            )),
+            Line::from(Span::styled("Enter to apply · Esc to go back", theme().dim)),
+        ]),
+        None => {
+            let options = std::iter::once("All time")
+                .chain(TIME_PRESETS.iter().map(|(label, _)| *label))
+                .chain(std::iter::once("Custom range…"));
+            Text::from(
+                options
+                    .enumerate()
+                    .map(|(idx, label)| {
+                        let style = if idx == picker.selected {
+                            Style::default().add_modifier(Modifier::REVERSED)
+                        } else {
+                            Style::default()
+                        };
+                        Line::from(Span::styled(format!(" {label} "), style))
+                    })
+                    .collect::<Vec<_>>(),
+            )
         }
+    };
+    let error = Paragraph::new(picker.error.clone().unwrap_or_default())
+        .style(theme().error)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(title, chunks[0]);
+    frame.render_widget(Paragraph::new(body), chunks[1]);
+    frame.render_widget(error, chunks[2]);
+}
+
+fn render_column_menu(frame: &mut Frame, area: Rect, app: &App, selected: usize) {
+    let columns = app.menu_columns();
+    let popup = centered_rect_clamped(40, 0, columns.len() as u16 + 2, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .title(" Columns ")
+        .title_bottom(
+            Line::from(format!(
+                " Space to show/hide · {} to close ",
+                app.keymap.label(Action::Back)
+            ))
+            .alignment(Alignment::Right),
+        );
+    let lines: Vec<Line> = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, column)| {
+            let mark = if app.hidden_columns.contains(column) {
+                "[ ]"
+            } else {
+                "[x]"
+            };
+            let style = if idx == selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(
+                format!(" {mark} {} ", column_menu_title(*column)),
+                style,
+            ))
+        })
+        .collect();
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// A column's name in the columns menu, spelling out headings too short to recognize there.
+fn column_menu_title(column: Column) -> &'static str {
+    match column {
+        Column::Type => "Type",
+        Column::ClientErrors => "4xx errors",
+        Column::ServerErrors => "5xx errors",
+        Column::LatencyP95 => "p95 latency",
+        column => column.title(),
+    }
+}
+
+fn render_finder(frame: &mut Frame, area: Rect, finder: &Finder, rows: &[DisplayRow]) {
+    let popup = centered_rect_clamped(60, 60, 10, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .title(" Find row ")
+        .title_bottom(
+            Line::from(format!(
+                " {}/{} · ↑/↓ choose · Enter to jump · Esc to close ",
+                finder.matches.len(),
+                rows.len()
+            ))
+            .alignment(Alignment::Right),
+        );
+    let inner = block.inner(popup);
+    let chunks = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).split(inner);
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", theme().accent),
+        Span::raw(finder.query.as_str()),
+        Span::raw("▏"),
+    ]));
+
+    let height = chunks[1].height as usize;
+    let start = (finder.selected + 1).saturating_sub(height);
+    let width = chunks[1].width as usize;
+    let highlight = theme().warning.add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = finder
+        .matches
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(idx, (row, positions))| {
+            let row = &rows[*row];
+            let mut spans = vec![Span::styled(
+                format!("{} ", row.req_type.label()),
+                row.req_type.style(),
+            )];
+            spans.extend(
+                finder_text(row)
+                    .chars()
+                    .take(width.saturating_sub(2))
+                    .enumerate()
+                    .map(|(pos, ch)| {
+                        if positions.contains(&pos) {
+                            Span::styled(ch.to_string(), highlight)
+                        } else {
+                            Span::raw(ch.to_string())
+                        }
+                    }),
+            );
+            let line = Line::from(spans);
+            if idx == finder.selected {
+                line.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                line
+            }
+        })
+        .collect();
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(input, chunks[0]);
+    frame.render_widget(Paragraph::new(lines), chunks[1]);
+}
+
+fn render_raw_records(frame: &mut Frame, area: Rect, raw: &mut RawRecords, keymap: &Keymap) {
+    let popup = centered_rect_clamped(80, 80, 12, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .title(format!(
+            " Raw records: {} ",
+            truncate_with_ellipsis(&raw.title, popup.width.saturating_sub(20) as usize)
+        ))
+        .title_bottom(
+            Line::from(format!(
+                " {} scroll · {} saves them all · {} to close ",
+                keymap.label(Action::Up) + " " + &keymap.label(Action::Down),
+                keymap.label(Action::SaveRecords),
+                keymap.label(Action::Back)
+            ))
+            .alignment(Alignment::Right),
+        );
+    let inner = block.inner(popup);
+
+    let location_style = theme().accent;
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(error) = &raw.error {
+        lines.push(Line::from(Span::styled(error.clone(), theme().error)));
+        lines.push(Line::from(""));
+    }
+    if raw.records.is_empty() && raw.error.is_none() {
+        lines.push(Line::from(Span::styled("No records", theme().dim)));
+    }
+    for (location, text) in &raw.records {
+        lines.push(Line::from(Span::styled(location.clone(), location_style)));
+        // JSON records are pretty-printed; other formats are shown as logged.
+        let pretty = serde_json::from_str::<serde_json::Value>(text)
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .unwrap_or_else(|_| text.clone());
+        lines.extend(pretty.lines().map(|line| Line::from(line.to_string())));
+        lines.push(Line::from(""));
+    }
+    let max_scroll = (lines.len() as u16).saturating_sub(inner.height);
+    raw.scroll = raw.scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines).scroll((raw.scroll, 0));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(paragraph, inner);
+}
+
+fn render_size_histogram(
+    frame: &mut Frame,
+    area: Rect,
+    histogram: &SizeHistogram,
+    keymap: &Keymap,
+) {
+    let popup = centered_rect_clamped(70, 70, 12, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .title(format!(
+            " Response sizes: {} ({} requests) ",
+            truncate_with_ellipsis(&histogram.title, popup.width.saturating_sub(30) as usize),
+            format_count(histogram.requests)
+        ))
+        .title_bottom(
+            Line::from(format!(" {} to close ", keymap.label(Action::Back)))
+                .alignment(Alignment::Right),
+        );
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    if histogram.buckets.is_empty() {
+        let message = Paragraph::new("No response sizes logged")
+            .alignment(Alignment::Center)
+            .style(theme().dim);
+        frame.render_widget(message, inner);
+        return;
     }
 
-    frame.render_stateful_widget(table, area, &mut view_state);
+    let bars: Vec<Bar> = histogram
+        .buckets
+        .iter()
+        .map(|&(lower, count)| {
+            let range = match lower {
+                0 => "0 B".to_string(),
+                lower => format!("{} – {}", format_bytes(lower), format_bytes(lower * 2)),
+            };
+            Bar::default()
+                .value(count)
+                .label(Line::from(format!(
+                    "{range:<21} {:>7}",
+                    format_count(count)
+                )))
+                .text_value(String::new())
+        })
+        .collect();
+    let chart = BarChart::default()
+        .direction(Direction::Horizontal)
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(theme().accent);
+    frame.render_widget(chart, inner);
 }
 
-fn render_footer(frame: &mut Frame, area: Rect) {
-    let footer = Paragraph::new("Press ? for help")
-        .alignment(Alignment::Left)
-        .style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(footer, area);
+fn render_errors_popup(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect_clamped(80, 70, 12, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .padding(Padding::uniform(1));
+    let inner = block.inner(popup);
+    let chunks = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .split(inner);
+    let title = Paragraph::new("Parse Errors").style(Style::default().add_modifier(Modifier::BOLD));
+    let counts = if app.skipped.is_empty() {
+        "No lines were skipped".to_string()
+    } else {
+        app.skipped
+            .iter()
+            .map(|(reason, count)| format!("{} {}", format_count(*count), reason.label()))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    };
+    let counts = Paragraph::new(counts).style(theme().warning);
+
+    let location_style = theme().accent;
+    let snippet_style = theme().dim;
+    let width = chunks[3].width as usize;
+    let items: Vec<ListItem> = app
+        .parse_errors
+        .iter()
+        .skip(app.error_scroll)
+        .map(|error| {
+            let location = if app.summary.file_count > 1 || app.inputs.len() > 1 {
+                format!("{}:{}", error.source, error.line_number)
+            } else {
+                format!("line {}", error.line_number)
+            };
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(location, location_style),
+                    Span::raw(format!("  {}: {}", error.reason.label(), error.message)),
+                ]),
+                Line::from(Span::styled(
+                    truncate_with_ellipsis(&error.snippet, width),
+                    snippet_style,
+                )),
+            ])
+        })
+        .collect();
+    let scroll = format!(
+        "{} {} to scroll",
+        app.keymap.label(Action::Up),
+        app.keymap.label(Action::Down)
+    );
+    let shown = if app.parse_errors.len() < app.skipped_count() as usize {
+        format!(
+            "First {} offending lines · {scroll}",
+            app.parse_errors.len()
+        )
+    } else {
+        scroll
+    };
+    let hint = Paragraph::new(shown).style(theme().dim);
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+    frame.render_widget(title, chunks[0]);
+    frame.render_widget(counts, chunks[1]);
+    frame.render_widget(hint, chunks[2]);
+    frame.render_widget(List::new(items), chunks[3]);
 }
 
 fn type_header_cell() -> Cell<'static> {
-    let line = Line::from(vec![Span::raw("T")]);
+    let line = Line::from(vec![Span::raw(Column::Type.title())]);
     Cell::from(line)
 }
 
-fn header_cell(label: &str, shortcut: char, app: &App, field: SortField) -> Cell<'static> {
-    let line = header_line(label, shortcut, app, field);
+fn header_cell(label: &str, app: &App, field: SortField) -> Cell<'static> {
+    let line = header_line(label, app, field);
     Cell::from(line)
 }
 
 fn header_cell_aligned(
     label: &str,
-    shortcut: char,
     app: &App,
     field: SortField,
     alignment: Alignment,
 ) -> Cell<'static> {
-    let line = header_line(label, shortcut, app, field);
+    let line = header_line(label, app, field);
     let text = Text::from(line).alignment(alignment);
     Cell::from(text)
 }
 
-fn header_line(label: &str, shortcut: char, app: &App, field: SortField) -> Line<'static> {
+/// A column heading with the key that sorts by it underlined, when the label has that letter.
+fn header_line(label: &str, app: &App, field: SortField) -> Line<'static> {
+    let shortcut = app.keymap.char(Action::Sort(field));
     let mut spans = Vec::new();
     let mut added_shortcut = false;
     for ch in label.chars() {
-        if !added_shortcut && ch.eq_ignore_ascii_case(&shortcut) {
+        if !added_shortcut && shortcut.is_some_and(|shortcut| ch.eq_ignore_ascii_case(&shortcut)) {
             spans.push(Span::styled(
                 ch.to_string(),
                 Style::default().add_modifier(Modifier::UNDERLINED),
@@ -575,81 +4876,44 @@ fn header_line(label: &str, shortcut: char, app: &App, field: SortField) -> Line
     if app.sort_field == field {
         spans.push(Span::raw(" "));
         spans.push(Span::raw(if app.descending { "↓" } else { "↑" }));
+    } else if let Some((then, descending)) = app.then_sort
+        && then == field
+    {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            if descending { "▾" } else { "▴" },
+            theme().dim,
+        ));
     }
 
     Line::from(spans)
 }
 
-fn load_stats(path: &str) -> Result<Vec<PathStats>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut map: HashMap<String, PathStats> = HashMap::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
-        let value: Value = match serde_json::from_str(&line) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-
-        let body = match value.get("body") {
-            Some(Value::Object(map)) => map,
-            _ => continue,
-        };
-
-        let url_str = match body.get("url").and_then(|v| v.as_str()) {
-            Some(url) => url,
-            None => continue,
-        };
-
-        let url = match Url::parse(url_str) {
-            Ok(url) => url,
-            Err(_) => continue,
-        };
-        let path = if url.path().is_empty() {
-            "/"
-        } else {
-            url.path()
-        };
-
-        let entry = map.entry(path.to_string()).or_insert_with(|| PathStats {
-            path: path.to_string(),
-            sample_url: url_str.to_string(),
-            request_count: 0,
-            request_size_sum: 0,
-            bandwidth_sum: 0,
-        });
-
-        entry.request_count += 1;
-
-        if let Some(req) = body.get("requestSize").and_then(as_u64) {
-            entry.request_size_sum += req;
+/// The columns of `columns` that fit in a table `area_width` wide, with their widths. Below
+/// [`NARROW_WIDTH`] they take their short headings and compact widths. Columns in
+/// [`DROP_ORDER`] are left out, first to last, while the ID column would be narrower than
+/// [`MIN_ID_WIDTH`], and come back once the terminal is wide enough again.
+fn fit_columns(
+    mut columns: Vec<Column>,
+    largest: Traffic,
+    area_width: u16,
+) -> (Vec<Column>, Vec<u16>) {
+    let narrow = area_width < NARROW_WIDTH;
+    for dropped in DROP_ORDER {
+        let widths = column_widths(&columns, largest, narrow);
+        if id_column_width(area_width, &widths) >= MIN_ID_WIDTH {
+            return (columns, widths);
         }
-
-        if let Some(resp) = body.get("responseSize").and_then(as_u64) {
-            entry.bandwidth_sum += resp;
-        }
-    }
-
-    let mut stats: Vec<PathStats> = map.into_values().collect();
-    stats.sort_by(|a, b| b.bandwidth_sum.cmp(&a.bandwidth_sum));
-    Ok(stats)
-}
-
-fn as_u64(value: &Value) -> Option<u64> {
-    match value {
-        Value::Number(num) => num.as_u64(),
-        Value::String(s) => s.parse::<u64>().ok(),
-        _ => None,
+        columns.retain(|column| *column != dropped);
     }
+    let widths = column_widths(&columns, largest, narrow);
+    (columns, widths)
 }
 
-fn id_column_width(area_width: u16) -> usize {
-    let fixed = 2u16 + 8 + 10 + 12 + 14;
-    let spacing = 4u16;
+/// What's left for the ID column, given the widths of every column with it as zero.
+fn id_column_width(area_width: u16, widths: &[u16]) -> usize {
+    let fixed: u16 = widths.iter().sum();
+    let spacing = widths.len().saturating_sub(1) as u16;
     let borders = 2u16;
     let available = area_width.saturating_sub(fixed + spacing + borders);
     available.max(10) as usize
@@ -661,9 +4925,11 @@ fn visible_row_count(height: u16) -> usize {
     rows.max(1)
 }
 
+/// The rows to draw, scrolled from where they were last drawn just far enough to show the
+/// selection. The first row drawn is kept in `state`'s offset.
 fn visible_range(
     items: &[DisplayRow],
-    selected: Option<usize>,
+    state: &mut TableState,
     visible_rows: usize,
 ) -> (usize, usize) {
     if items.is_empty() {
@@ -673,75 +4939,307 @@ fn visible_range(
     if max_items == 0 {
         return (0, 0);
     }
-    let selected = selected.unwrap_or(0).min(items.len().saturating_sub(1));
-    let mut start = 0usize;
-    if selected >= max_items {
+    let selected = state.selected().unwrap_or(0).min(items.len() - 1);
+    let mut start = state.offset().min(items.len().saturating_sub(max_items));
+    if selected < start {
+        start = selected;
+    } else if selected >= start + max_items {
         start = selected + 1 - max_items;
     }
+    *state.offset_mut() = start;
     let end = (start + max_items).min(items.len());
     (start, end)
 }
 
-fn build_display_rows(
+/// Rows for the By Asset and Slow Requests views. With a `diff` baseline, each row carries its
+/// baseline traffic, and assets requested only in the baseline get a row of their own.
+fn build_path_rows(
     base_items: &[PathStats],
-    view_mode: ViewMode,
-    field: SortField,
-    descending: bool,
+    trends: &HashMap<String, Vec<u64>>,
+    baseline: Option<&HashMap<String, PathStats>>,
+    order: SortOrder,
 ) -> Vec<DisplayRow> {
-    match view_mode {
-        ViewMode::Path => {
-            let mut rows: Vec<DisplayRow> = base_items
-                .iter()
-                .map(|item| {
-                    let req_type = detect_request_type(&item.path);
-                    let (id, ext) = asset_id_and_ext(&item.path, req_type);
-                    DisplayRow {
-                        label: id,
-                        ext,
-                        request_count: item.request_count,
-                        bandwidth_sum: item.bandwidth_sum,
-                        req_type,
-                        open_url: Some(item.sample_url.clone()),
-                        is_group: false,
-                    }
-                })
-                .collect();
-            sort_display_rows(&mut rows, field, descending);
-            rows
+    let row = |item: &PathStats| {
+        let req_type = detect_request_type(&item.path);
+        let (id, _) = asset_id_and_ext(&item.path, req_type);
+        let (_, ext) = asset_id_and_ext(&sample_path(item), req_type);
+        DisplayRow {
+            label: id,
+            key: Some(item.path.clone()),
+            ext,
+            request_count: item.request_count,
+            bandwidth_sum: item.bandwidth_sum,
+            request_size_sum: item.request_size_sum,
+            client_errors: error_count(&item.status_counts, 4),
+            server_errors: error_count(&item.status_counts, 5),
+            unoptimized_bandwidth: item.unoptimized_bandwidth,
+            variant_count: item.variants.len(),
+            size_percentiles: size_percentiles(&item.response_sizes),
+            duration_sum: item.duration_sum,
+            timed_requests: item.timed_requests,
+            latency_p95: item.durations.quantile(LATENCY_PERCENTILE),
+            cache_lookups: item.cache_lookups,
+            cache_hits: item.cache_hits,
+            req_type,
+            open_url: Some(item.sample_url.clone()),
+            is_group: false,
+            trend: trends.get(&item.path).cloned(),
+            baseline: baseline
+                .map(|stats| stats.get(&item.path).map(Traffic::of).unwrap_or_default()),
         }
-        ViewMode::Type => build_type_rows(base_items, field, descending),
+    };
+    let mut rows: Vec<DisplayRow> = base_items.iter().map(row).collect();
+    if let Some(baseline) = baseline {
+        let current: HashSet<&str> = base_items.iter().map(|item| item.path.as_str()).collect();
+        rows.extend(
+            baseline
+                .values()
+                .filter(|item| !current.contains(item.path.as_str()))
+                .map(|item| DisplayRow {
+                    request_count: 0,
+                    bandwidth_sum: 0,
+                    request_size_sum: 0,
+                    client_errors: 0,
+                    server_errors: 0,
+                    unoptimized_bandwidth: 0,
+                    variant_count: 0,
+                    size_percentiles: None,
+                    duration_sum: 0,
+                    timed_requests: 0,
+                    latency_p95: None,
+                    cache_lookups: 0,
+                    cache_hits: 0,
+                    trend: None,
+                    baseline: Some(Traffic::of(item)),
+                    ..row(item)
+                }),
+        );
+    }
+    sort_display_rows(&mut rows, order);
+    rows
+}
+
+fn build_timeline_rows(
+    buckets: &[(i64, TrafficBucket)],
+    granularity: Granularity,
+    order: SortOrder,
+) -> Vec<DisplayRow> {
+    let mut rows: Vec<DisplayRow> = buckets
+        .iter()
+        .map(|(start, bucket)| DisplayRow {
+            label: granularity.format(*start),
+            key: None,
+            ext: String::new(),
+            request_count: bucket.requests,
+            bandwidth_sum: bucket.bandwidth,
+            request_size_sum: 0,
+            client_errors: 0,
+            server_errors: 0,
+            unoptimized_bandwidth: 0,
+            variant_count: 0,
+            size_percentiles: None,
+            duration_sum: 0,
+            timed_requests: 0,
+            latency_p95: None,
+            cache_lookups: 0,
+            cache_hits: 0,
+            req_type: RequestType::Other,
+            open_url: None,
+            is_group: false,
+            trend: None,
+            baseline: None,
+        })
+        .collect();
+    sort_display_rows(&mut rows, order);
+    rows
+}
+
+fn build_variant_rows(
+    variants: &HashMap<String, PathStats>,
+    req_type: RequestType,
+    order: SortOrder,
+) -> Vec<DisplayRow> {
+    let mut rows: Vec<DisplayRow> = variants
+        .values()
+        .map(|variant| DisplayRow {
+            label: variant.path.clone(),
+            key: Some(variant.path.clone()),
+            ext: String::new(),
+            request_count: variant.request_count,
+            bandwidth_sum: variant.bandwidth_sum,
+            request_size_sum: variant.request_size_sum,
+            client_errors: error_count(&variant.status_counts, 4),
+            server_errors: error_count(&variant.status_counts, 5),
+            unoptimized_bandwidth: variant.unoptimized_bandwidth,
+            variant_count: 0,
+            cache_lookups: variant.cache_lookups,
+            cache_hits: variant.cache_hits,
+            size_percentiles: size_percentiles(&variant.response_sizes),
+            duration_sum: variant.duration_sum,
+            timed_requests: variant.timed_requests,
+            latency_p95: variant.durations.quantile(LATENCY_PERCENTILE),
+            req_type,
+            open_url: Some(variant.sample_url.clone()),
+            is_group: false,
+            trend: None,
+            baseline: None,
+        })
+        .collect();
+    sort_display_rows(&mut rows, order);
+    rows
+}
+
+/// Rows for a grouped view. Nested dimensions (such as status classes) get a bold row per
+/// parent followed by its groups.
+fn build_group_rows(
+    groups: &HashMap<String, PathStats>,
+    dimension: Dimension,
+    order: SortOrder,
+) -> Vec<DisplayRow> {
+    let group_row = |group: &PathStats| DisplayRow {
+        label: dimension.child_label(&group.path).to_string(),
+        key: Some(group.path.clone()),
+        ext: String::new(),
+        request_count: group.request_count,
+        bandwidth_sum: group.bandwidth_sum,
+        request_size_sum: group.request_size_sum,
+        client_errors: error_count(&group.status_counts, 4),
+        server_errors: error_count(&group.status_counts, 5),
+        unoptimized_bandwidth: group.unoptimized_bandwidth,
+        variant_count: 0,
+        size_percentiles: size_percentiles(&group.response_sizes),
+        duration_sum: group.duration_sum,
+        timed_requests: group.timed_requests,
+        latency_p95: group.durations.quantile(LATENCY_PERCENTILE),
+        cache_lookups: group.cache_lookups,
+        cache_hits: group.cache_hits,
+        req_type: RequestType::Other,
+        open_url: dimension
+            .link(&group.path)
+            .or_else(|| Some(group.sample_url.clone())),
+        is_group: false,
+        trend: None,
+        baseline: None,
+    };
+    if !dimension.is_nested() {
+        let mut rows: Vec<DisplayRow> = groups.values().map(group_row).collect();
+        sort_display_rows(&mut rows, order);
+        return rows;
+    }
+
+    let mut children: HashMap<String, Vec<DisplayRow>> = HashMap::new();
+    let mut parent_sizes: HashMap<String, Distribution> = HashMap::new();
+    let mut parent_durations: HashMap<String, Distribution> = HashMap::new();
+    for group in groups.values() {
+        let parent = dimension.parent(&group.path);
+        parent_sizes
+            .entry(parent.clone())
+            .or_default()
+            .merge(&group.response_sizes);
+        parent_durations
+            .entry(parent.clone())
+            .or_default()
+            .merge(&group.durations);
+        children.entry(parent).or_default().push(group_row(group));
+    }
+    let mut parent_rows: Vec<DisplayRow> = children
+        .iter()
+        .map(|(parent, members)| DisplayRow {
+            label: parent.clone(),
+            key: None,
+            ext: String::new(),
+            request_count: members.iter().map(|row| row.request_count).sum(),
+            bandwidth_sum: members.iter().map(|row| row.bandwidth_sum).sum(),
+            request_size_sum: members.iter().map(|row| row.request_size_sum).sum(),
+            client_errors: members.iter().map(|row| row.client_errors).sum(),
+            server_errors: members.iter().map(|row| row.server_errors).sum(),
+            unoptimized_bandwidth: members.iter().map(|row| row.unoptimized_bandwidth).sum(),
+            variant_count: members.len(),
+            size_percentiles: parent_sizes.get(parent).and_then(size_percentiles),
+            duration_sum: members.iter().map(|row| row.duration_sum).sum(),
+            timed_requests: members.iter().map(|row| row.timed_requests).sum(),
+            latency_p95: parent_durations
+                .get(parent)
+                .and_then(|durations| durations.quantile(LATENCY_PERCENTILE)),
+            cache_lookups: members.iter().map(|row| row.cache_lookups).sum(),
+            cache_hits: members.iter().map(|row| row.cache_hits).sum(),
+            req_type: RequestType::Other,
+            open_url: None,
+            is_group: true,
+            trend: None,
+            baseline: None,
+        })
+        .collect();
+    sort_display_rows(&mut parent_rows, order);
+
+    let mut rows = Vec::new();
+    for parent_row in parent_rows {
+        let mut members = children.remove(&parent_row.label).unwrap_or_default();
+        // Requests without the attribute form a parent of their own with nothing to expand.
+        members.retain(|row| row.label != parent_row.label);
+        sort_display_rows(&mut members, order);
+        rows.push(parent_row);
+        rows.extend(members);
     }
+    rows
 }
 
 #[derive(Default)]
 struct Agg {
     request_count: u64,
     bandwidth_sum: u64,
+    request_size_sum: u64,
+    unoptimized_bandwidth: u64,
+    cache_lookups: u64,
+    cache_hits: u64,
+    response_sizes: Distribution,
+    duration_sum: u64,
+    timed_requests: u64,
+    durations: Distribution,
     sample_url: Option<String>,
 }
 
-fn build_type_rows(
-    base_items: &[PathStats],
-    field: SortField,
-    descending: bool,
-) -> Vec<DisplayRow> {
+fn build_type_rows(type_items: &[PathStats], order: SortOrder) -> Vec<DisplayRow> {
     let mut type_map: HashMap<RequestType, Agg> = HashMap::new();
     let mut ext_map: HashMap<(RequestType, String), Agg> = HashMap::new();
 
-    for item in base_items {
+    for item in type_items {
         let req_type = detect_request_type(&item.path);
         let type_entry = type_map.entry(req_type).or_default();
         type_entry.request_count += item.request_count;
         type_entry.bandwidth_sum += item.bandwidth_sum;
+        type_entry.request_size_sum += item.request_size_sum;
+        type_entry.unoptimized_bandwidth += item.unoptimized_bandwidth;
+        type_entry.cache_lookups += item.cache_lookups;
+        type_entry.cache_hits += item.cache_hits;
+        type_entry.response_sizes.merge(&item.response_sizes);
+        type_entry.duration_sum += item.duration_sum;
+        type_entry.timed_requests += item.timed_requests;
+        type_entry.durations.merge(&item.durations);
         if type_entry.sample_url.is_none() {
             type_entry.sample_url = Some(item.sample_url.clone());
         }
 
-        if matches!(req_type, RequestType::Image | RequestType::File) {
-            let ext = extract_extension(&item.path).unwrap_or_else(|| "no ext".to_string());
-            let ext_entry = ext_map.entry((req_type, ext)).or_default();
+        // Images and files break down by extension, queries by their normalized text.
+        let child = match req_type {
+            RequestType::Image | RequestType::File => {
+                Some(extract_extension(&item.path).unwrap_or_else(|| "no ext".to_string()))
+            }
+            RequestType::Query => Some(asset_id_and_ext(&item.path, req_type).0),
+            _ => None,
+        };
+        if let Some(child) = child {
+            let ext_entry = ext_map.entry((req_type, child)).or_default();
             ext_entry.request_count += item.request_count;
             ext_entry.bandwidth_sum += item.bandwidth_sum;
+            ext_entry.request_size_sum += item.request_size_sum;
+            ext_entry.unoptimized_bandwidth += item.unoptimized_bandwidth;
+            ext_entry.cache_lookups += item.cache_lookups;
+            ext_entry.cache_hits += item.cache_hits;
+            ext_entry.response_sizes.merge(&item.response_sizes);
+            ext_entry.duration_sum += item.duration_sum;
+            ext_entry.timed_requests += item.timed_requests;
+            ext_entry.durations.merge(&item.durations);
             if ext_entry.sample_url.is_none() {
                 ext_entry.sample_url = Some(item.sample_url.clone());
             }
@@ -749,56 +5247,83 @@ fn build_type_rows(
     }
 
     let mut type_rows: Vec<DisplayRow> = Vec::new();
-    for req_type in [
-        RequestType::Image,
-        RequestType::File,
-        RequestType::Query,
-        RequestType::Other,
-    ] {
+    for req_type in RequestType::ALL {
         let agg = match type_map.get(&req_type) {
             Some(agg) => agg,
             None => continue,
         };
         type_rows.push(DisplayRow {
             label: type_label(req_type).to_string(),
+            key: None,
             ext: String::new(),
             request_count: agg.request_count,
             bandwidth_sum: agg.bandwidth_sum,
+            request_size_sum: agg.request_size_sum,
+            client_errors: 0,
+            server_errors: 0,
+            unoptimized_bandwidth: agg.unoptimized_bandwidth,
+            variant_count: 0,
+            size_percentiles: size_percentiles(&agg.response_sizes),
+            duration_sum: agg.duration_sum,
+            timed_requests: agg.timed_requests,
+            latency_p95: agg.durations.quantile(LATENCY_PERCENTILE),
+            cache_lookups: agg.cache_lookups,
+            cache_hits: agg.cache_hits,
             req_type,
             open_url: None,
             is_group: true,
+            trend: None,
+            baseline: None,
         });
     }
 
-    sort_display_rows(&mut type_rows, field, descending);
+    sort_display_rows(&mut type_rows, order);
 
     let mut rows: Vec<DisplayRow> = Vec::new();
     for type_row in type_rows {
         let req_type = type_row.req_type;
         rows.push(type_row);
-        if matches!(req_type, RequestType::Image | RequestType::File) {
+        if matches!(
+            req_type,
+            RequestType::Image | RequestType::File | RequestType::Query
+        ) {
             let mut ext_rows: Vec<DisplayRow> = ext_map
                 .iter()
-                .filter_map(|((kind, ext), agg)| {
+                .filter_map(|((kind, child), agg)| {
                     if *kind != req_type {
                         return None;
                     }
+                    let (label, ext) = match child.as_str() {
+                        _ if req_type == RequestType::Query => (child.clone(), String::new()),
+                        "no ext" => (String::new(), "(none)".to_string()),
+                        ext => (String::new(), format!(".{ext}")),
+                    };
                     Some(DisplayRow {
-                        label: String::new(),
-                        ext: if ext == "no ext" {
-                            "(none)".to_string()
-                        } else {
-                            format!(".{ext}")
-                        },
+                        label,
+                        ext,
                         request_count: agg.request_count,
                         bandwidth_sum: agg.bandwidth_sum,
+                        request_size_sum: agg.request_size_sum,
+                        client_errors: 0,
+                        server_errors: 0,
+                        unoptimized_bandwidth: agg.unoptimized_bandwidth,
+                        variant_count: 0,
+                        size_percentiles: size_percentiles(&agg.response_sizes),
+                        duration_sum: agg.duration_sum,
+                        timed_requests: agg.timed_requests,
+                        latency_p95: agg.durations.quantile(LATENCY_PERCENTILE),
+                        key: None,
+                        cache_lookups: agg.cache_lookups,
+                        cache_hits: agg.cache_hits,
                         req_type,
                         open_url: agg.sample_url.clone(),
                         is_group: false,
+                        trend: None,
+                        baseline: None,
                     })
                 })
                 .collect();
-            sort_display_rows(&mut ext_rows, field, descending);
+            sort_display_rows(&mut ext_rows, order);
             rows.extend(ext_rows);
         }
     }
@@ -806,115 +5331,521 @@ fn build_type_rows(
     rows
 }
 
-fn sort_display_rows(rows: &mut [DisplayRow], field: SortField, descending: bool) {
+/// Sorts by the primary field, breaking ties with the secondary one when there is one.
+fn sort_display_rows(rows: &mut [DisplayRow], order: SortOrder) {
     rows.sort_by(|a, b| {
-        let ordering = match field {
-            SortField::Path => {
-                let a_rank = if a.req_type == RequestType::Query {
-                    0
-                } else {
-                    1
-                };
-                let b_rank = if b.req_type == RequestType::Query {
-                    0
-                } else {
-                    1
-                };
-                (a_rank, &a.label).cmp(&(b_rank, &b.label))
+        let directed = |field, descending| {
+            let ordering = compare_rows(a, b, field);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
             }
-            SortField::Ext => a.ext.cmp(&b.ext),
-            SortField::Requests => a.request_count.cmp(&b.request_count),
-            SortField::AvgRequestSize => a.avg_size().cmp(&b.avg_size()),
-            SortField::Bandwidth => a.bandwidth_sum.cmp(&b.bandwidth_sum),
         };
-        if descending {
-            ordering.reverse()
-        } else {
-            ordering
-        }
+        directed(order.field, order.descending).then_with(|| match order.then {
+            Some((field, descending)) => directed(field, descending),
+            None => Ordering::Equal,
+        })
     });
 }
 
+fn compare_rows(a: &DisplayRow, b: &DisplayRow, field: SortField) -> Ordering {
+    match field {
+        SortField::Path => {
+            let a_rank = if a.req_type == RequestType::Query {
+                0
+            } else {
+                1
+            };
+            let b_rank = if b.req_type == RequestType::Query {
+                0
+            } else {
+                1
+            };
+            (a_rank, &a.label).cmp(&(b_rank, &b.label))
+        }
+        SortField::Ext => a.ext.cmp(&b.ext),
+        SortField::Requests => a.request_count.cmp(&b.request_count),
+        SortField::AvgRequestSize => a.avg_size().cmp(&b.avg_size()),
+        SortField::Bandwidth => a.bandwidth_sum.cmp(&b.bandwidth_sum),
+        SortField::RequestSize => a.request_size_sum.cmp(&b.request_size_sum),
+        SortField::ClientErrors => a.client_errors.cmp(&b.client_errors),
+        SortField::ServerErrors => a.server_errors.cmp(&b.server_errors),
+        SortField::ErrorRate => a
+            .error_rate()
+            .partial_cmp(&b.error_rate())
+            .unwrap_or(Ordering::Equal),
+        SortField::Unoptimized => a.unoptimized_bandwidth.cmp(&b.unoptimized_bandwidth),
+        SortField::Variants => a.variant_count.cmp(&b.variant_count),
+        SortField::Latency => a.avg_latency().cmp(&b.avg_latency()),
+        SortField::BandwidthDelta => a.bandwidth_delta().cmp(&b.bandwidth_delta()),
+        SortField::Change => a
+            .change()
+            .partial_cmp(&b.change())
+            .unwrap_or(Ordering::Equal),
+        SortField::CacheHit => a
+            .cache_hit_rate()
+            .partial_cmp(&b.cache_hit_rate())
+            .unwrap_or(Ordering::Equal),
+    }
+}
+
 fn type_label(kind: RequestType) -> &'static str {
     match kind {
         RequestType::Image => "Images",
         RequestType::File => "Files",
         RequestType::Query => "GROQ Queries",
+        RequestType::Mutation => "Mutations",
+        RequestType::Document => "Document lookups",
+        RequestType::History => "History",
+        RequestType::Listen => "Listeners",
+        RequestType::Export => "Exports",
+        RequestType::Upload => "Asset uploads",
         RequestType::Other => "Other",
     }
 }
 
+/// How a row stands apart from the others in the table.
+#[derive(Debug, Clone, Copy)]
+struct RowState {
+    selected: bool,
+    /// Marked with Space, to be totalled in the footer.
+    marked: bool,
+    /// Pinned to the top with `B`.
+    pinned: bool,
+}
+
 fn row_for_item(
     item: &DisplayRow,
+    columns: &[Column],
     path_width: usize,
+    id_scroll: usize,
     view_mode: ViewMode,
-    is_selected: bool,
+    state: RowState,
 ) -> Row<'static> {
-    let display_path = format_id_display(&item.label, path_width);
-    let type_cell = Cell::from(item.req_type.label().to_string())
-        .style(Style::default().fg(item.req_type.color()));
     let row_style = if item.is_group {
         Style::default().add_modifier(Modifier::BOLD)
+    } else if state.marked {
+        theme().name
     } else {
         Style::default()
     };
-    let id_cell = if view_mode == ViewMode::Type && item.label.is_empty() && !is_selected {
-        Cell::from("-").style(Style::default().fg(Color::DarkGray))
-    } else {
-        Cell::from(display_path)
+    let marker = match (state.pinned, state.marked) {
+        (true, true) => "◆ ● ",
+        (true, false) => "◆ ",
+        (false, true) => "● ",
+        (false, false) => "",
     };
-    let ext_cell = Cell::from(item.ext.clone());
-
-    Row::new([
-        type_cell,
-        id_cell,
-        ext_cell,
-        right_cell(format_count(item.request_count)),
-        right_cell(format_bytes(item.avg_size())),
-        right_cell(format_bytes(item.bandwidth_sum)),
-    ])
-    .style(row_style)
+    let cells = columns.iter().map(|column| match column {
+        Column::Type => Cell::from(item.req_type.label().to_string()).style(item.req_type.style()),
+        Column::Id if view_mode == ViewMode::Type && item.label.is_empty() && !state.selected => {
+            Cell::from("-").style(theme().dim)
+        }
+        Column::Id
+            if matches!(view_mode, ViewMode::Group(dimension) if dimension.is_nested())
+                && !item.is_group =>
+        {
+            Cell::from(format_id_display(
+                &format!("  {marker}{}", item.label),
+                path_width,
+                id_scroll,
+            ))
+        }
+        Column::Id => Cell::from(format_id_display(
+            &format!("{marker}{}", item.label),
+            path_width,
+            id_scroll,
+        )),
+        Column::Ext => Cell::from(item.ext.clone()),
+        Column::Trend => Cell::from(item.trend.as_deref().map(sparkline).unwrap_or_default())
+            .style(theme().accent),
+        Column::Requests => right_cell(format_count(item.request_count)),
+        Column::AvgSize => right_cell(format_bytes(item.avg_size())),
+        Column::Bandwidth => right_cell(format_bytes(item.bandwidth_sum)),
+        Column::RequestSize => right_cell(format_bytes(item.request_size_sum)),
+        Column::ClientErrors => error_count_cell(item.client_errors, theme().warning),
+        Column::ServerErrors => error_count_cell(item.server_errors, theme().error),
+        Column::ErrorRate => right_cell(item.error_rate().map(format_percent).unwrap_or_default()),
+        Column::Unoptimized => match item.unoptimized_bandwidth {
+            0 => Cell::from(""),
+            bytes => right_cell(format_bytes(bytes)).style(theme().warning),
+        },
+        Column::Variants => match item.variant_count {
+            0 => Cell::from(""),
+            count if count > MAX_VARIANTS => {
+                right_cell(format_count(count as u64)).style(theme().error)
+            }
+            count => right_cell(format_count(count as u64)),
+        },
+        Column::P50 | Column::P95 | Column::P99 => percentile_cell(item.size_percentiles, *column),
+        Column::Latency => right_cell(item.avg_latency().map(format_millis).unwrap_or_default()),
+        Column::LatencyP95 => right_cell(item.latency_p95.map(format_millis).unwrap_or_default()),
+        Column::RequestsDelta => match item.baseline {
+            Some(baseline) => delta_cell(baseline.requests, item.request_count, format_count),
+            None => Cell::from(""),
+        },
+        Column::BandwidthDelta => match item.baseline {
+            Some(baseline) => delta_cell(baseline.bandwidth, item.bandwidth_sum, format_bytes),
+            None => Cell::from(""),
+        },
+        Column::Change => change_cell(item.change()),
+        Column::CacheHit => right_cell(
+            item.cache_hit_rate()
+                .map(format_percent)
+                .unwrap_or_default(),
+        ),
+    });
+
+    Row::new(cells).style(row_style)
+}
+
+/// Copies the given lines of each input, keyed by its position in `inputs`, to `out`.
+fn write_raw_lines(
+    mut out: impl Write,
+    inputs: &[Input],
+    format: InputFormat,
+    lines: BTreeMap<usize, Vec<u64>>,
+) -> Result<()> {
+    let mut header_written = false;
+    for (index, mut line_numbers) in lines {
+        if format == InputFormat::Csv {
+            line_numbers.push(1);
+        }
+        for (line_number, text) in read_raw_lines(&inputs[index], format, &line_numbers)? {
+            if format == InputFormat::Csv && line_number == 1 {
+                if header_written {
+                    continue;
+                }
+                header_written = true;
+            }
+            // Elements of a JSON array may span lines; NDJSON needs one per line.
+            let text = match serde_json::from_str::<serde_json::Value>(&text) {
+                Ok(value) if text.contains('\n') => value.to_string(),
+                _ => text,
+            };
+            writeln!(out, "{text}")?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// The text [`row_for_item`] shows in a cell, without its styling or truncation.
+fn column_text(item: &DisplayRow, column: Column) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    match column {
+        Column::Type => item.req_type.label().to_string(),
+        Column::Id => item.label.clone(),
+        Column::Ext => item.ext.clone(),
+        Column::Trend => optional(item.trend.as_deref().map(sparkline)),
+        Column::Requests => format_count(item.request_count),
+        Column::AvgSize => format_bytes(item.avg_size()),
+        Column::Bandwidth => format_bytes(item.bandwidth_sum),
+        Column::RequestSize => format_bytes(item.request_size_sum),
+        Column::ClientErrors if item.client_errors == 0 => String::new(),
+        Column::ClientErrors => format_count(item.client_errors),
+        Column::ServerErrors if item.server_errors == 0 => String::new(),
+        Column::ServerErrors => format_count(item.server_errors),
+        Column::ErrorRate => optional(item.error_rate().map(format_percent)),
+        Column::Unoptimized if item.unoptimized_bandwidth == 0 => String::new(),
+        Column::Unoptimized => format_bytes(item.unoptimized_bandwidth),
+        Column::Variants if item.variant_count == 0 => String::new(),
+        Column::Variants => format_count(item.variant_count as u64),
+        Column::P50 => optional(item.size_percentiles.map(|values| format_bytes(values[0]))),
+        Column::P95 => optional(item.size_percentiles.map(|values| format_bytes(values[1]))),
+        Column::P99 => optional(item.size_percentiles.map(|values| format_bytes(values[2]))),
+        Column::Latency => optional(item.avg_latency().map(format_millis)),
+        Column::LatencyP95 => optional(item.latency_p95.map(format_millis)),
+        Column::RequestsDelta => optional(
+            item.baseline
+                .map(|baseline| delta_text(baseline.requests, item.request_count, format_count)),
+        ),
+        Column::BandwidthDelta => optional(
+            item.baseline
+                .map(|baseline| delta_text(baseline.bandwidth, item.bandwidth_sum, format_bytes)),
+        ),
+        Column::Change => optional(item.change().map(change_text)),
+        Column::CacheHit => optional(item.cache_hit_rate().map(format_percent)),
+    }
+}
+
+/// Draws counts as block characters scaled to the largest one; empty slices stay blank.
+fn sparkline(values: &[u64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            value => {
+                let level = (value * (SPARK_CHARS.len() as u64 - 1)).div_ceil(max) as usize;
+                SPARK_CHARS[level.min(SPARK_CHARS.len() - 1)]
+            }
+        })
+        .collect()
 }
 
-fn divider_row(id_width: usize) -> Row<'static> {
+fn divider_row(widths: &[u16], id_width: usize) -> Row<'static> {
     let fill = |width: usize| "─".repeat(width.max(1));
-    Row::new([
-        Cell::from(fill(2)),
-        Cell::from(fill(id_width)),
-        Cell::from(fill(8)),
-        Cell::from(fill(10)),
-        Cell::from(fill(12)),
-        Cell::from(fill(14)),
-    ])
-    .style(Style::default().fg(Color::DarkGray))
+    Row::new(widths.iter().map(|width| match width {
+        0 => Cell::from(fill(id_width)),
+        width => Cell::from(fill(*width as usize)),
+    }))
+    .style(theme().dim)
+}
+
+/// Widths of `columns`, with the ID column's as zero to take what's left. With exact numbers
+/// on, counts and sizes grow to fit `largest`, which no row can pass.
+fn column_widths(columns: &[Column], largest: Traffic, narrow: bool) -> Vec<u16> {
+    let exact = units::exact();
+    let count = format_count(largest.requests).width() as u16;
+    let size = format_bytes(largest.bandwidth).width() as u16;
+    columns
+        .iter()
+        .map(|column| {
+            let fit = match column {
+                _ if !exact => 0,
+                Column::Requests
+                | Column::Variants
+                | Column::ClientErrors
+                | Column::ServerErrors => count,
+                Column::RequestsDelta => count + 1,
+                Column::AvgSize
+                | Column::Bandwidth
+                | Column::RequestSize
+                | Column::Unoptimized
+                | Column::P50
+                | Column::P95
+                | Column::P99 => size,
+                Column::BandwidthDelta => size + 1,
+                _ => 0,
+            };
+            let width = if narrow {
+                column.compact_width()
+            } else {
+                column.width()
+            };
+            width.max(fit)
+        })
+        .collect()
 }
 
 fn right_cell(value: String) -> Cell<'static> {
     Cell::from(Text::from(value).alignment(Alignment::Right))
 }
 
-fn totals_row(items: &[PathStats], id_width: usize) -> Row<'static> {
+/// Requests answered with a status in the given hundred, such as 4 for 4xx.
+fn error_count(status_counts: &BTreeMap<u16, u64>, class: u16) -> u64 {
+    status_counts
+        .range(class * 100..(class + 1) * 100)
+        .map(|(_, count)| count)
+        .sum()
+}
+
+fn has_errors(stats: &PathStats) -> bool {
+    stats.status_counts.range(400..600).next().is_some()
+}
+
+fn error_count_cell(count: u64, style: Style) -> Cell<'static> {
+    match count {
+        0 => Cell::from(""),
+        count => right_cell(format_count(count)).style(style),
+    }
+}
+
+/// What the finder matches a row by: its full path or group name.
+fn finder_text(row: &DisplayRow) -> &str {
+    row.key.as_deref().unwrap_or(&row.label)
+}
+
+/// The rows that don't match, leaving out everything beneath a heading that does and headings
+/// with nothing left beneath them.
+fn exclude_rows(rows: Vec<DisplayRow>, matches: impl Fn(&DisplayRow) -> bool) -> Vec<DisplayRow> {
+    let mut kept = Vec::new();
+    let mut heading = None;
+    let mut heading_matches = false;
+    for row in rows {
+        if row.is_group {
+            heading_matches = matches(&row);
+            heading = (!heading_matches).then_some(row);
+        } else if !heading_matches && !matches(&row) {
+            kept.extend(heading.take());
+            kept.push(row);
+        }
+    }
+    kept
+}
+
+/// Keeps the rows `matches` accepts. A group heading that matches keeps all of its rows, and
+/// one that doesn't is still shown above any of its rows that do.
+fn filter_rows(rows: Vec<DisplayRow>, matches: impl Fn(&DisplayRow) -> bool) -> Vec<DisplayRow> {
+    let mut kept = Vec::new();
+    let mut heading = None;
+    let mut heading_matches = false;
+    for row in rows {
+        if row.is_group {
+            heading_matches = matches(&row);
+            if heading_matches {
+                kept.push(row);
+                heading = None;
+            } else {
+                heading = Some(row);
+            }
+        } else if heading_matches || matches(&row) {
+            kept.extend(heading.take());
+            kept.push(row);
+        }
+    }
+    kept
+}
+
+/// `after` relative to `before`, such as -0.25 for a quarter less, or infinite when `before` is
+/// zero.
+fn relative_change(before: u64, after: u64) -> Option<f64> {
+    match (before, after) {
+        (0, 0) => None,
+        (0, _) => Some(f64::INFINITY),
+        (before, after) => Some(after as f64 / before as f64 - 1.0),
+    }
+}
+
+/// The difference between two values, red for growth and green for savings.
+fn delta_cell(before: u64, after: u64, format: fn(u64) -> String) -> Cell<'static> {
+    let style = match after.cmp(&before) {
+        Ordering::Greater => theme().error,
+        Ordering::Less => theme().good,
+        Ordering::Equal => theme().dim,
+    };
+    right_cell(delta_text(before, after, format)).style(style)
+}
+
+fn delta_text(before: u64, after: u64, format: fn(u64) -> String) -> String {
+    match after.cmp(&before) {
+        Ordering::Greater => format!("+{}", format(after - before)),
+        Ordering::Less => format!("-{}", format(before - after)),
+        Ordering::Equal => format(0),
+    }
+}
+
+fn change_cell(change: Option<f64>) -> Cell<'static> {
+    match change {
+        None => Cell::from(""),
+        Some(change) => {
+            let style = if change > 0.0 {
+                theme().error
+            } else {
+                theme().good
+            };
+            right_cell(change_text(change)).style(style)
+        }
+    }
+}
+
+/// Assets missing from the baseline have an infinite change and are shown as new.
+fn change_text(change: f64) -> String {
+    if change.is_infinite() {
+        "new".to_string()
+    } else {
+        with_decimal_separator(format!("{:+.1}%", change * 100.0))
+    }
+}
+
+/// Reads the response sizes at [`PERCENTILES`] from a distribution.
+fn size_percentiles(sizes: &Distribution) -> Option<[u64; 3]> {
+    let [p50, p95, p99] = PERCENTILES.map(|quantile| sizes.quantile(quantile));
+    Some([p50?, p95?, p99?])
+}
+
+fn percentile_cell(percentiles: Option<[u64; 3]>, column: Column) -> Cell<'static> {
+    let index = match column {
+        Column::P50 => 0,
+        Column::P95 => 1,
+        _ => 2,
+    };
+    right_cell(
+        percentiles
+            .map(|values| format_bytes(values[index]))
+            .unwrap_or_default(),
+    )
+}
+
+fn totals_row<'a>(
+    items: impl IntoIterator<Item = &'a PathStats>,
+    size_percentiles: Option<[u64; 3]>,
+    latency_p95: Option<u64>,
+    baseline: Option<Traffic>,
+    columns: &[Column],
+    id_width: usize,
+) -> Row<'static> {
     let mut total_requests = 0u64;
     let mut total_bandwidth = 0u64;
+    let mut total_request_size = 0u64;
+    let mut total_client_errors = 0u64;
+    let mut total_server_errors = 0u64;
+    let mut total_unoptimized = 0u64;
+    let mut total_variants = 0u64;
+    let mut total_lookups = 0u64;
+    let mut total_hits = 0u64;
+    let mut total_duration = 0u64;
+    let mut total_timed = 0u64;
     for item in items {
+        total_variants += item.variants.len() as u64;
         total_requests += item.request_count;
         total_bandwidth += item.bandwidth_sum;
+        total_request_size += item.request_size_sum;
+        total_client_errors += error_count(&item.status_counts, 4);
+        total_server_errors += error_count(&item.status_counts, 5);
+        total_unoptimized += item.unoptimized_bandwidth;
+        total_lookups += item.cache_lookups;
+        total_hits += item.cache_hits;
+        total_duration += item.duration_sum;
+        total_timed += item.timed_requests;
     }
 
-    let avg_req = if total_requests == 0 {
-        0
-    } else {
-        total_bandwidth / total_requests
-    };
-    let label = format_id_display("TOTAL", id_width);
-    Row::new([
-        Cell::from(""),
-        Cell::from(label),
-        Cell::from(""),
-        right_cell(format_count(total_requests)),
-        right_cell(format_bytes(avg_req)),
-        right_cell(format_bytes(total_bandwidth)),
-    ])
+    let avg_req = total_bandwidth.checked_div(total_requests).unwrap_or(0);
+    Row::new(columns.iter().map(|column| {
+        match column {
+            Column::Id => Cell::from(truncate_with_ellipsis("TOTAL", id_width)),
+            Column::Requests => right_cell(format_count(total_requests)),
+            Column::AvgSize => right_cell(format_bytes(avg_req)),
+            Column::Bandwidth => right_cell(format_bytes(total_bandwidth)),
+            Column::RequestSize => right_cell(format_bytes(total_request_size)),
+            Column::ClientErrors => right_cell(format_count(total_client_errors)),
+            Column::ServerErrors => right_cell(format_count(total_server_errors)),
+            Column::ErrorRate => right_cell(
+                (total_requests > 0)
+                    .then(|| {
+                        (total_client_errors + total_server_errors) as f64 / total_requests as f64
+                    })
+                    .map(format_percent)
+                    .unwrap_or_default(),
+            ),
+            Column::Unoptimized => right_cell(format_bytes(total_unoptimized)),
+            Column::Variants => right_cell(format_count(total_variants)),
+            Column::P50 | Column::P95 | Column::P99 => percentile_cell(size_percentiles, *column),
+            Column::Latency => right_cell(
+                total_duration
+                    .checked_div(total_timed)
+                    .map(format_millis)
+                    .unwrap_or_default(),
+            ),
+            Column::CacheHit if total_lookups > 0 => {
+                right_cell(format_percent(total_hits as f64 / total_lookups as f64))
+            }
+            Column::LatencyP95 => right_cell(latency_p95.map(format_millis).unwrap_or_default()),
+            Column::RequestsDelta => match baseline {
+                Some(baseline) => delta_cell(baseline.requests, total_requests, format_count),
+                None => Cell::from(""),
+            },
+            Column::BandwidthDelta => match baseline {
+                Some(baseline) => delta_cell(baseline.bandwidth, total_bandwidth, format_bytes),
+                None => Cell::from(""),
+            },
+            Column::Change => change_cell(
+                baseline.and_then(|baseline| relative_change(baseline.bandwidth, total_bandwidth)),
+            ),
+            Column::Type | Column::Ext | Column::Trend | Column::CacheHit => Cell::from(""),
+        }
+    }))
     .style(Style::default().add_modifier(Modifier::BOLD))
 }
 
@@ -925,18 +5856,29 @@ fn detect_request_type(path: &str) -> RequestType {
     if path.starts_with("/files/") {
         return RequestType::File;
     }
+    // API endpoints look like /:version/data/:endpoint/:dataset or
+    // /:version/assets/:kind/:dataset.
     let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-    if parts.len() >= 4 && parts[1] == "data" && parts[2] == "query" {
-        return RequestType::Query;
+    match parts.as_slice() {
+        [_, "data", "query", _, ..] => RequestType::Query,
+        [_, "data", "mutate", ..] => RequestType::Mutation,
+        [_, "data", "doc", ..] => RequestType::Document,
+        [_, "data", "history", ..] => RequestType::History,
+        [_, "data", "listen", ..] => RequestType::Listen,
+        [_, "data", "export", ..] => RequestType::Export,
+        [_, "assets", "images" | "files", ..] => RequestType::Upload,
+        _ => RequestType::Other,
     }
-    RequestType::Other
 }
 
 fn asset_id_and_ext(path: &str, kind: RequestType) -> (String, String) {
     match kind {
         RequestType::Image => {
             let remainder = strip_prefix_segments(path, 3).unwrap_or_else(|| path.to_string());
-            let file = remainder.split('/').last().unwrap_or(remainder.as_str());
+            let file = remainder
+                .split('/')
+                .next_back()
+                .unwrap_or(remainder.as_str());
             let (name, ext) = match file.rsplit_once('.') {
                 Some((name, ext)) => (name, ext.to_string()),
                 None => (file, String::new()),
@@ -946,15 +5888,21 @@ fn asset_id_and_ext(path: &str, kind: RequestType) -> (String, String) {
         }
         RequestType::File => {
             let remainder = strip_prefix_segments(path, 3).unwrap_or_else(|| path.to_string());
-            let file = remainder.split('/').last().unwrap_or(remainder.as_str());
+            let file = remainder
+                .split('/')
+                .next_back()
+                .unwrap_or(remainder.as_str());
             let (name, ext) = match file.rsplit_once('.') {
                 Some((name, ext)) => (name.to_string(), ext.to_string()),
                 None => (file.to_string(), String::new()),
             };
             (name, format_ext(&ext))
         }
-        RequestType::Query => ("GROQ Queries".to_string(), String::new()),
-        RequestType::Other => {
+        RequestType::Query => match path.split_once("?query=") {
+            Some((_, query)) => (query.to_string(), String::new()),
+            None => ("GROQ Queries".to_string(), String::new()),
+        },
+        _ => {
             let remainder = strip_prefix_segments(path, 0).unwrap_or_else(|| path.to_string());
             let ext = extract_extension(&remainder).unwrap_or_default();
             (remainder, format_ext(&ext))
@@ -970,6 +5918,14 @@ fn format_ext(ext: &str) -> String {
     }
 }
 
+/// The path of an item's sample request. Assets are keyed by ID alone, so this is where their
+/// extension is read from.
+fn sample_path(item: &PathStats) -> String {
+    Url::parse(&item.sample_url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| item.path.clone())
+}
+
 fn strip_prefix_segments(path: &str, count: usize) -> Option<String> {
     let mut iter = path.split('/').filter(|s| !s.is_empty());
     for _ in 0..count {
@@ -992,49 +5948,182 @@ fn extract_extension(path: &str) -> Option<String> {
     }
 }
 
-fn format_id_display(value: &str, width: usize) -> String {
-    truncate_with_ellipsis(value, width)
+/// Where IDs too long for their column are cut, set with `truncate_ids` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+enum IdTruncation {
+    /// Keep the start, as in `images/abc123/product-hero-ph…`
+    #[default]
+    End,
+    /// Keep the start and the end, as in `images/abc1…-2400x1600.png`
+    Middle,
+}
+
+static ID_TRUNCATION: OnceLock<IdTruncation> = OnceLock::new();
+
+/// Cuts an asset ID, path, or other row label to `width` columns where `truncate_ids` says.
+fn truncate_id(value: &str, width: usize) -> String {
+    match ID_TRUNCATION.get().copied().unwrap_or_default() {
+        IdTruncation::End => truncate_with_ellipsis(value, width),
+        IdTruncation::Middle => truncate_middle(value, width),
+    }
+}
+
+/// Cuts text to `width` columns by replacing its middle with `…`, keeping the end, such as a
+/// file's extension or version suffix, in view. The end gets the extra column of an odd split.
+fn truncate_middle(value: &str, width: usize) -> String {
+    if value.width() <= width {
+        return value.to_string();
+    }
+    if width <= 2 {
+        return truncate_with_ellipsis(value, width);
+    }
+    let start = (width - 1) / 2;
+    format!(
+        "{}…{}",
+        take_left(value, start),
+        take_right(value, width - 1 - start)
+    )
 }
 
+/// An ID cut to the column's width, after skipping `scroll` columns of it. Each ID scrolls only
+/// until its end is in view, so short ones stay put while long ones are read to the end.
+fn format_id_display(value: &str, width: usize, scroll: usize) -> String {
+    let skip = scroll.min(value.width().saturating_sub(width));
+    if skip == 0 || width <= 1 {
+        return truncate_id(value, width);
+    }
+    let rest = &value[take_left(value, skip + 1).len()..];
+    let rest = rest.trim_start_matches(|c: char| c.width() == Some(0));
+    format!("…{}", truncate_with_ellipsis(rest, width - 1))
+}
+
+/// Cuts text to `width` terminal columns, ending it with `…` when it doesn't fit. Wide
+/// characters, such as CJK and emoji, take two columns.
 fn truncate_with_ellipsis(value: &str, width: usize) -> String {
-    if value.len() <= width {
+    if value.width() <= width {
         return value.to_string();
     }
-    if width <= 3 {
-        return take_left(value, width);
+    if width == 0 {
+        return String::new();
     }
-    format!("{}...", take_left(value, width - 3))
+    format!("{}…", take_left(value, width - 1))
+}
+
+/// The longest start of `value` that fits in `width` terminal columns.
+fn take_left(value: &str, width: usize) -> &str {
+    let mut used = 0;
+    let end = value
+        .char_indices()
+        .find(|(_, c)| {
+            used += c.width().unwrap_or(0);
+            used > width
+        })
+        .map_or(value.len(), |(index, _)| index);
+    &value[..end]
 }
 
-fn take_left(value: &str, count: usize) -> String {
-    value.chars().take(count).collect()
+/// The longest end of `value` that fits in `width` terminal columns.
+fn take_right(value: &str, width: usize) -> &str {
+    let mut used = 0;
+    let start = value
+        .char_indices()
+        .rev()
+        .find(|(_, c)| {
+            used += c.width().unwrap_or(0);
+            used > width
+        })
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    &value[start..]
 }
 
+/// A size in the units chosen, such as `2.54 MiB`, or `2.66 MB` in decimal ones, or in bytes
+/// with exact numbers on.
 fn format_bytes(value: u64) -> String {
-    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    if units::exact() {
+        return format!("{} B", group_digits(value));
+    }
+    let units = units::units();
+    let names = units.names();
     let mut size = value as f64;
     let mut unit = 0usize;
-    while size >= 1024.0 && unit + 1 < UNITS.len() {
-        size /= 1024.0;
+    while size >= units.base() && unit + 1 < names.len() {
+        size /= units.base();
         unit += 1;
     }
     if unit == 0 {
-        format!("{} {}", value, UNITS[unit])
+        format!("{} {}", value, names[unit])
     } else {
-        format!("{:.2} {}", size, UNITS[unit])
+        with_decimal_separator(format!("{:.2} {}", size, names[unit]))
     }
 }
 
 fn format_count(value: u64) -> String {
+    if units::exact() {
+        return group_digits(value);
+    }
     if value >= 1_000_000 {
-        return format!("{:.1}M", value as f64 / 1_000_000.0);
+        return with_decimal_separator(format!("{:.1}M", value as f64 / 1_000_000.0));
     }
     if value >= 1_000 {
-        return format!("{:.1}K", value as f64 / 1_000.0);
+        return with_decimal_separator(format!("{:.1}K", value as f64 / 1_000.0));
     }
     value.to_string()
 }
 
+/// Writes a number with the thousands separator between each group of three digits, such as
+/// `1,234,567`.
+fn group_digits(value: u64) -> String {
+    let separator = units::separators().thousands;
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn format_millis(millis: u64) -> String {
+    if millis >= 1000 {
+        with_decimal_separator(format!("{:.2} s", millis as f64 / 1000.0))
+    } else {
+        format!("{millis} ms")
+    }
+}
+
+fn format_percent(ratio: f64) -> String {
+    with_decimal_separator(format!("{:.1}%", ratio * 100.0))
+}
+
+/// What `bytes` of bandwidth cost at `price_per_gb`, billed per decimal GB as Sanity does,
+/// rounded to the cent.
+fn cost(bytes: u64, price_per_gb: f64) -> f64 {
+    (bytes as f64 / 1e9 * price_per_gb * 100.0).round() / 100.0
+}
+
+/// A [`cost`] in dollars, such as `$12.34`.
+fn format_cost(bytes: u64, price_per_gb: f64) -> String {
+    with_decimal_separator(format!("${:.2}", cost(bytes, price_per_gb)))
+}
+
+/// Swaps the `.` Rust formats fractions with for the configured decimal separator.
+fn with_decimal_separator(text: String) -> String {
+    match units::separators().decimal {
+        '.' => text,
+        decimal => text.replace('.', decimal.encode_utf8(&mut [0; 4])),
+    }
+}
+
+/// Sets the system clipboard through the terminal with an OSC 52 escape sequence, which also
+/// works over SSH. Terminals without OSC 52 support ignore it.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut terminal = io::stderr();
+    write!(terminal, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))?;
+    terminal.flush()
+}
+
 fn open_url(url: &str) -> Result<()> {
     if url.trim().is_empty() {
         return Ok(());
@@ -1054,3 +6143,54 @@ fn open_url(url: &str) -> Result<()> {
     };
     cmd.spawn().map(|_| ()).context("failed to open url")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_to_the_width_on_screen() {
+        assert_eq!(truncate_with_ellipsis("hello", 5), "hello");
+        assert_eq!(truncate_with_ellipsis("hello world", 5), "hell…");
+        assert_eq!(truncate_with_ellipsis("hello", 1), "…");
+        assert_eq!(truncate_with_ellipsis("hello", 0), "");
+        // A wide character that would straddle the edge is left out rather than split.
+        assert_eq!(truncate_with_ellipsis("日本語ファイル", 6), "日本…");
+        assert_eq!(truncate_with_ellipsis("日本語ファイル", 2), "…");
+        assert_eq!(truncate_with_ellipsis("😀😀😀", 4), "😀…");
+        // IDs are cut at the end unless `truncate_ids` says otherwise.
+        assert_eq!(truncate_id("abcdefghij", 5), "abcd…");
+    }
+
+    #[test]
+    fn takes_whole_characters_from_either_end() {
+        assert_eq!(take_left("日本語", 3), "日");
+        assert_eq!(take_left("日本語", 4), "日本");
+        assert_eq!(take_left("abc", 0), "");
+        assert_eq!(take_right("日本語", 3), "語");
+        assert_eq!(take_right("abc", 2), "bc");
+        assert_eq!(take_right("abc", 5), "abc");
+    }
+
+    #[test]
+    fn truncates_in_the_middle() {
+        assert_eq!(truncate_middle("abcdefghij", 10), "abcdefghij");
+        assert_eq!(truncate_middle("abcdefghij", 5), "ab…ij");
+        // The end gets the extra column of an odd split.
+        assert_eq!(truncate_middle("abcdefghij", 6), "ab…hij");
+        assert_eq!(truncate_middle("abcdefghij", 2), "a…");
+        assert_eq!(truncate_middle("abcdefghij", 1), "…");
+        assert_eq!(truncate_middle("abcdefghij", 0), "");
+        assert_eq!(truncate_middle("日本語ファイル.pdf", 9), "日本….pdf");
+    }
+
+    #[test]
+    fn scrolls_ids_by_columns() {
+        assert_eq!(format_id_display("abcdefghij", 5, 0), "abcd…");
+        assert_eq!(format_id_display("abcdefghij", 5, 3), "…efg…");
+        // Scrolling stops once the end is in view.
+        assert_eq!(format_id_display("abcdefghij", 5, 100), "…ghij");
+        assert_eq!(format_id_display("short", 10, 8), "short");
+        assert_eq!(format_id_display("日本語ファイル", 6, 3), "…語フ…");
+    }
+}
@@ -0,0 +1,480 @@
+use anyhow::{Context, Result};
+use jiff::{Timestamp, fmt::strtime};
+use serde_json::Value;
+
+const WRAPPER_FIELDS: [&str; 3] = ["message", "record", "log"];
+
+/// A single request, normalized from whichever log format it was read from.
+#[derive(Debug, Clone, Default)]
+pub struct Record {
+    /// Absolute URL, or a path to be resolved against the Sanity CDN.
+    pub url: String,
+    pub request_id: Option<String>,
+    pub timestamp: Option<Timestamp>,
+    pub status: Option<u16>,
+    pub request_size: Option<u64>,
+    pub response_size: Option<u64>,
+    /// Whether a CDN served the response from cache, when the log says.
+    pub cache_hit: Option<bool>,
+    /// Page that linked to or embedded the requested URL.
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+    pub client_ip: Option<String>,
+    /// Time taken to serve the request, in milliseconds.
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    InvalidJson,
+    InvalidCsv,
+    InvalidLogLine,
+    MissingUrl,
+    InvalidUrl,
+}
+
+impl SkipReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::InvalidJson => "invalid JSON",
+            SkipReason::InvalidCsv => "invalid CSV",
+            SkipReason::InvalidLogLine => "invalid access log line",
+            SkipReason::MissingUrl => "missing URL",
+            SkipReason::InvalidUrl => "invalid URL",
+        }
+    }
+}
+
+pub type ParseResult<T> = std::result::Result<T, (SkipReason, String)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum InputFormat {
+    /// One JSON object per line (or a single JSON array)
+    #[default]
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// nginx/Apache common or combined access log format
+    Combined,
+}
+
+/// Field naming of the JSON records, for logs exported by something other than Sanity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Schema {
+    /// Sanity request logs (`body.url`, `body.responseSize`, ...)
+    #[default]
+    Sanity,
+    /// Cloudflare Logpush HTTP requests (`ClientRequestURI`, `EdgeResponseBytes`, ...)
+    Cloudflare,
+    /// Fastly real-time logs (`url`, `response_body_size`, `cache_status`, ...)
+    Fastly,
+}
+
+impl Schema {
+    pub fn fields(self) -> JsonFields {
+        let (
+            url,
+            host,
+            request_id,
+            timestamp,
+            status,
+            request_size,
+            response_size,
+            cache_status,
+            referer,
+            user_agent,
+            client_ip,
+            duration,
+        ) = match self {
+            Schema::Sanity => (
+                "body.url",
+                None,
+                &["requestId", "id"][..],
+                "timestamp",
+                "body.status",
+                "body.requestSize",
+                "body.responseSize",
+                None,
+                "body.referer",
+                "body.userAgent",
+                "body.remoteIp",
+                "body.duration",
+            ),
+            Schema::Cloudflare => (
+                "ClientRequestURI",
+                Some("ClientRequestHost"),
+                &["RayID"][..],
+                "EdgeStartTimestamp",
+                "EdgeResponseStatus",
+                "ClientRequestBytes",
+                "EdgeResponseBytes",
+                Some("CacheCacheStatus"),
+                "ClientRequestReferer",
+                "ClientRequestUserAgent",
+                "ClientIP",
+                "EdgeTimeToFirstByteMs",
+            ),
+            Schema::Fastly => (
+                "url",
+                Some("host"),
+                &["request_id", "id"][..],
+                "timestamp",
+                "response_status",
+                "request_body_size",
+                "response_body_size",
+                Some("cache_status"),
+                "referer",
+                "user_agent",
+                "client_ip",
+                "duration",
+            ),
+        };
+        JsonFields {
+            url: url.to_string(),
+            host: host.map(str::to_string),
+            request_id: request_id.iter().map(|path| path.to_string()).collect(),
+            timestamp: timestamp.to_string(),
+            status: status.to_string(),
+            request_size: request_size.to_string(),
+            response_size: response_size.to_string(),
+            cache_status: cache_status.map(str::to_string),
+            referer: referer.to_string(),
+            user_agent: user_agent.to_string(),
+            client_ip: client_ip.to_string(),
+            duration: duration.to_string(),
+        }
+    }
+}
+
+/// Dotted paths (e.g. `body.url`) to the fields a JSON record is read from.
+#[derive(Debug, Clone)]
+pub struct JsonFields {
+    pub url: String,
+    /// Host to prefix the URL with when it is only a path.
+    pub host: Option<String>,
+    /// Candidates for a unique request ID; the first one present is used.
+    pub request_id: Vec<String>,
+    pub timestamp: String,
+    pub status: String,
+    pub request_size: String,
+    pub response_size: String,
+    pub cache_status: Option<String>,
+    pub referer: String,
+    pub user_agent: String,
+    pub client_ip: String,
+    /// Time taken to serve the request, in milliseconds.
+    pub duration: String,
+}
+
+/// Header names of the CSV columns to read.
+#[derive(Debug, Clone)]
+pub struct CsvColumns {
+    pub url: String,
+    pub size: String,
+}
+
+/// Turns individual lines of one input into records.
+#[derive(Debug, Clone)]
+pub enum LineParser {
+    Json(Box<JsonFields>),
+    Csv(CsvLayout),
+    Combined,
+}
+
+impl LineParser {
+    pub fn parse(&self, line: &str) -> ParseResult<Record> {
+        match self {
+            LineParser::Json(fields) => parse_json(line, fields),
+            LineParser::Csv(layout) => layout.parse(line),
+            LineParser::Combined => parse_combined(line),
+        }
+    }
+}
+
+fn parse_json(line: &str, fields: &JsonFields) -> ParseResult<Record> {
+    let value: Value =
+        serde_json::from_str(line).map_err(|err| (SkipReason::InvalidJson, err.to_string()))?;
+    let value = unwrap_record(value, fields);
+
+    let url = match lookup(&value, &fields.url).and_then(|v| v.as_str()) {
+        Some(url) => url,
+        None => {
+            return Err((
+                SkipReason::MissingUrl,
+                format!("no `{}` string on the record", fields.url),
+            ));
+        }
+    };
+    let host = fields
+        .host
+        .as_deref()
+        .and_then(|host| lookup(&value, host))
+        .and_then(|host| host.as_str())
+        .filter(|host| !host.is_empty() && url.starts_with('/'));
+    let number = |path: &str| lookup(&value, path).and_then(as_u64);
+
+    Ok(Record {
+        url: match host {
+            Some(host) => format!("https://{host}{url}"),
+            None => url.to_string(),
+        },
+        request_id: fields
+            .request_id
+            .iter()
+            .find_map(|path| match lookup(&value, path)? {
+                Value::String(id) => Some(id.clone()),
+                Value::Number(id) => Some(id.to_string()),
+                _ => None,
+            }),
+        timestamp: lookup(&value, &fields.timestamp).and_then(as_timestamp),
+        status: number(&fields.status).and_then(|status| u16::try_from(status).ok()),
+        request_size: number(&fields.request_size),
+        response_size: number(&fields.response_size),
+        cache_hit: fields
+            .cache_status
+            .as_deref()
+            .and_then(|path| lookup(&value, path))
+            .and_then(|status| status.as_str())
+            .map(is_cache_hit),
+        referer: lookup(&value, &fields.referer)
+            .and_then(|referer| referer.as_str())
+            .and_then(non_empty),
+        user_agent: lookup(&value, &fields.user_agent)
+            .and_then(|agent| agent.as_str())
+            .and_then(non_empty),
+        client_ip: lookup(&value, &fields.client_ip)
+            .and_then(|ip| ip.as_str())
+            .and_then(non_empty),
+        duration_ms: lookup(&value, &fields.duration).and_then(as_millis),
+    })
+}
+
+/// Interprets Cloudflare (`hit`, `stale`, ...) and Fastly (`HIT`, `HIT-STALE`, ...) cache states.
+fn is_cache_hit(status: &str) -> bool {
+    let status = status.to_ascii_lowercase();
+    status.starts_with("hit") || matches!(status.as_str(), "stale" | "revalidated" | "updating")
+}
+
+/// Treats the empty and `-` placeholders logs use for missing text fields as absent.
+fn non_empty(value: &str) -> Option<String> {
+    match value.trim() {
+        "" | "-" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// Follows a dotted path such as `body.url` into nested objects.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+/// Log drains (e.g. CloudWatch) often wrap the record under another field, either as an object
+/// or as a JSON-encoded string. Returns the innermost record that carries the URL field's root.
+fn unwrap_record(value: Value, fields: &JsonFields) -> Value {
+    let root = fields.url.split('.').next().unwrap_or(&fields.url);
+    if value.get(root).is_some() {
+        return value;
+    }
+    for field in WRAPPER_FIELDS {
+        let inner = match value.get(field) {
+            Some(Value::String(text)) => serde_json::from_str(text).ok(),
+            Some(inner @ Value::Object(_)) => Some(inner.clone()),
+            _ => None,
+        };
+        if let Some(inner) = inner.filter(|inner: &Value| inner.get(root).is_some()) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Reads RFC 3339 strings, or Unix epoch numbers in seconds, milliseconds, microseconds, or
+/// nanoseconds (guessed from their magnitude).
+fn as_timestamp(value: &Value) -> Option<Timestamp> {
+    match value {
+        Value::String(text) => text
+            .parse()
+            .ok()
+            .or_else(|| text.parse::<i64>().ok().and_then(from_epoch)),
+        Value::Number(num) => num.as_i64().and_then(from_epoch),
+        _ => None,
+    }
+}
+
+fn from_epoch(value: i64) -> Option<Timestamp> {
+    let nanos = match value.unsigned_abs() {
+        0..100_000_000_000 => i128::from(value) * 1_000_000_000,
+        100_000_000_000..100_000_000_000_000 => i128::from(value) * 1_000_000,
+        100_000_000_000_000..100_000_000_000_000_000 => i128::from(value) * 1_000,
+        _ => i128::from(value),
+    };
+    Timestamp::from_nanosecond(nanos).ok()
+}
+
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(num) => num.as_u64(),
+        Value::String(s) => s.parse::<u64>().ok(),
+        _ => None,
+    }
+}
+
+/// Durations are often logged with a fractional part, so round them to whole milliseconds.
+fn as_millis(value: &Value) -> Option<u64> {
+    let millis = match value {
+        Value::Number(num) => num.as_f64()?,
+        Value::String(s) => s.parse::<f64>().ok()?,
+        _ => return None,
+    };
+    (millis >= 0.0).then(|| millis.round() as u64)
+}
+
+/// Column positions resolved from a CSV header row.
+#[derive(Debug, Clone)]
+pub struct CsvLayout {
+    url: usize,
+    size: usize,
+}
+
+impl CsvLayout {
+    pub fn from_header(header: &str, columns: &CsvColumns) -> Result<Self> {
+        let names = split_csv_line(header.trim_start_matches('\u{feff}'))
+            .map_err(|message| anyhow::anyhow!("invalid CSV header: {message}"))?;
+        let find = |name: &str| {
+            names
+                .iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+                .with_context(|| format!("CSV header has no `{name}` column"))
+        };
+        Ok(Self {
+            url: find(&columns.url)?,
+            size: find(&columns.size)?,
+        })
+    }
+
+    fn parse(&self, line: &str) -> ParseResult<Record> {
+        let fields = split_csv_line(line).map_err(|message| (SkipReason::InvalidCsv, message))?;
+        let url = fields
+            .get(self.url)
+            .map(|url| url.trim())
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| (SkipReason::MissingUrl, "empty URL column".to_string()))?;
+        let response_size = fields
+            .get(self.size)
+            .and_then(|size| size.trim().parse::<u64>().ok());
+        Ok(Record {
+            url: url.to_string(),
+            request_id: None,
+            timestamp: None,
+            status: None,
+            request_size: None,
+            response_size,
+            cache_hit: None,
+            referer: None,
+            user_agent: None,
+            client_ip: None,
+            duration_ms: None,
+        })
+    }
+}
+
+/// Splits one CSV line into fields, honoring double-quoted fields and `""` escapes. Quoted
+/// fields spanning multiple lines are not supported.
+fn split_csv_line(line: &str) -> std::result::Result<Vec<String>, String> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut in_quotes = false;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Parses a common/combined access log line:
+/// `host ident user [time] "METHOD /path HTTP/1.1" status bytes ["referer" "user-agent"]`.
+fn parse_combined(line: &str) -> ParseResult<Record> {
+    let invalid = |message: &str| (SkipReason::InvalidLogLine, message.to_string());
+
+    let (time, rest) = line
+        .split_once(" [")
+        .and_then(|(_, rest)| rest.split_once("] "))
+        .ok_or_else(|| invalid("missing [time] field"))?;
+    let (request, rest) = take_quoted(rest).ok_or_else(|| invalid("missing quoted request"))?;
+
+    let mut fields = rest.trim_start().splitn(3, ' ');
+    let status = fields
+        .next()
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| invalid("missing status code"))?;
+    let response_size = match fields.next() {
+        Some("-") => Some(0),
+        Some(bytes) => Some(
+            bytes
+                .parse::<u64>()
+                .map_err(|_| invalid("invalid bytes sent"))?,
+        ),
+        None => return Err(invalid("missing bytes sent")),
+    };
+    // Only the combined format carries a referer and user agent; they follow the bytes sent.
+    let (referer, user_agent) = match fields.next().and_then(take_quoted) {
+        Some((referer, rest)) => (
+            non_empty(&referer),
+            take_quoted(rest).and_then(|(agent, _)| non_empty(&agent)),
+        ),
+        None => (None, None),
+    };
+
+    let target = request.split_whitespace().nth(1).ok_or_else(|| {
+        (
+            SkipReason::MissingUrl,
+            format!("no target in request `{request}`"),
+        )
+    })?;
+
+    Ok(Record {
+        url: target.to_string(),
+        request_id: None,
+        timestamp: strtime::parse("%d/%b/%Y:%H:%M:%S %z", time)
+            .and_then(|time| time.to_timestamp())
+            .ok(),
+        status: Some(status),
+        request_size: None,
+        response_size,
+        cache_hit: None,
+        referer,
+        user_agent,
+        client_ip: line.split_whitespace().next().and_then(non_empty),
+        duration_ms: None,
+    })
+}
+
+/// Reads a double-quoted field with backslash escapes from the start of `text`, returning its
+/// contents and the remainder after the closing quote.
+fn take_quoted(text: &str) -> Option<(String, &str)> {
+    let text = text.trim_start().strip_prefix('"')?;
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+            '"' => return Some((value, &text[idx + 1..])),
+            _ => value.push(ch),
+        }
+    }
+    None
+}
@@ -0,0 +1,998 @@
+use crate::{
+    App, Column, DisplayRow, MAX_VARIANTS, RequestType, SortField, SortOrder, build_type_rows,
+    column_text, cost, detect_request_type, format_bytes, format_cost, format_count,
+    format_percent, format_time_span,
+    ingest::{Request, aggregate_by},
+    truncate_id, type_label,
+};
+use serde::Serialize;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+/// Assets listed in the Markdown top assets table.
+const TOP_ASSETS: usize = 20;
+/// Assets listed by `--summary`.
+const SUMMARY_ASSETS: usize = 10;
+/// Assets listed in the HTML table, which can be sorted and so shows more of them.
+const MAX_HTML_ASSETS: usize = 1000;
+/// Rows listed under each kind of offender.
+const TOP_OFFENDERS: usize = 5;
+/// Images averaging more than this per response are flagged as served larger than needed.
+const LARGE_IMAGE: u64 = 1024 * 1024;
+/// Longest ID or query shown in a table cell.
+const MAX_ID_WIDTH: usize = 60;
+
+/// How `report` writes its summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown, for pasting into a PR or an incident doc
+    #[default]
+    Markdown,
+    /// A single self-contained HTML page with charts and sortable tables
+    Html,
+}
+
+/// Writes a summary of the By Asset rows, with every filter applied: totals, the split by
+/// request type, the top assets by bandwidth, the worst offenders, and what to do about them.
+pub fn write(out: impl Write, app: &App, format: ReportFormat) -> io::Result<()> {
+    let report = Report::new(app);
+    match format {
+        ReportFormat::Markdown => write_markdown(out, &report),
+        ReportFormat::Html => write_html(out, &report),
+    }
+}
+
+/// Writes the totals, the split by request type, and the top assets as aligned plain text,
+/// for reading over SSH or in a cron job's mail.
+pub fn write_summary(mut out: impl Write, app: &App) -> io::Result<()> {
+    let report = Report::new(app);
+    writeln!(out, "{}\n", report.scope.replace('`', ""))?;
+    let totals = report
+        .totals()
+        .into_iter()
+        .map(|(label, value)| vec![label.to_string(), value])
+        .collect();
+    write_aligned(&mut out, &[], totals)?;
+
+    writeln!(out)?;
+    let types = report
+        .types
+        .iter()
+        .map(|row| {
+            vec![
+                row.label.clone(),
+                format_count(row.request_count),
+                format_bytes(row.bandwidth_sum),
+                share(row.bandwidth_sum, report.total.bandwidth),
+            ]
+        })
+        .collect();
+    write_aligned(
+        &mut out,
+        &[
+            ("Type", false),
+            ("Requests", true),
+            ("Bandwidth", true),
+            ("Share", true),
+        ],
+        types,
+    )?;
+
+    writeln!(out, "\nTop {SUMMARY_ASSETS} assets by bandwidth\n")?;
+    let assets = report
+        .assets
+        .iter()
+        .take(SUMMARY_ASSETS)
+        .enumerate()
+        .map(|(rank, row)| {
+            vec![
+                (rank + 1).to_string(),
+                type_label(row.req_type).to_string(),
+                truncate_id(&row.label, MAX_ID_WIDTH),
+                row.ext.clone(),
+                format_count(row.request_count),
+                format_bytes(row.bandwidth_sum),
+                share(row.bandwidth_sum, report.total.bandwidth),
+            ]
+        })
+        .collect();
+    write_aligned(
+        &mut out,
+        &[
+            ("#", true),
+            ("Type", false),
+            ("ID", false),
+            ("Ext", false),
+            ("Requests", true),
+            ("Bandwidth", true),
+            ("Share", true),
+        ],
+        assets,
+    )?;
+    out.flush()
+}
+
+/// Writes what `--summary` prints as JSON, with sizes in bytes and counts in full.
+pub fn write_summary_json(mut out: impl Write, app: &App) -> io::Result<()> {
+    let report = Report::new(app);
+    let total = &report.total;
+    let summary = JsonSummary {
+        scope: JsonScope::of(app),
+        totals: JsonTotals {
+            requests: total.requests,
+            bandwidth: total.bandwidth,
+            bandwidth_cost: app.price_per_gb.map(|price| cost(total.bandwidth, price)),
+            unoptimized_bandwidth: total.unoptimized,
+            cache_hit_rate: (total.cache_lookups > 0)
+                .then(|| total.cache_hits as f64 / total.cache_lookups as f64),
+            client_errors: total.client_errors,
+            server_errors: total.server_errors,
+        },
+        types: report
+            .types
+            .iter()
+            .map(|row| JsonType {
+                kind: row.req_type.name(),
+                requests: row.request_count,
+                bandwidth: row.bandwidth_sum,
+            })
+            .collect(),
+        top_assets: report
+            .assets
+            .iter()
+            .take(SUMMARY_ASSETS)
+            .map(|row| JsonAsset::of(row))
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut out, &summary)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct JsonSummary<'a> {
+    scope: JsonScope,
+    totals: JsonTotals,
+    types: Vec<JsonType>,
+    top_assets: Vec<JsonAsset<'a>>,
+}
+
+/// What the JSON output covers: the inputs, their time span, and the filters applied.
+#[derive(Serialize)]
+pub(crate) struct JsonScope {
+    files: usize,
+    first_request: Option<String>,
+    last_request: Option<String>,
+    filters: Vec<String>,
+}
+
+impl JsonScope {
+    pub(crate) fn of(app: &App) -> Self {
+        let span = app.summary.time_span;
+        JsonScope {
+            files: app.summary.file_count,
+            first_request: span.map(|span| span.first.to_string()),
+            last_request: span.map(|span| span.last.to_string()),
+            filters: app
+                .active_filters()
+                .into_iter()
+                .map(|(_, label)| label)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonTotals {
+    requests: u64,
+    bandwidth: u64,
+    /// Left out when no price per GB is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bandwidth_cost: Option<f64>,
+    unoptimized_bandwidth: u64,
+    /// `null` when the logs have no cache status.
+    cache_hit_rate: Option<f64>,
+    client_errors: u64,
+    server_errors: u64,
+}
+
+#[derive(Serialize)]
+struct JsonType {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    requests: u64,
+    bandwidth: u64,
+}
+
+/// A By Asset row in the JSON output.
+#[derive(Serialize)]
+pub(crate) struct JsonAsset<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: &'a str,
+    ext: &'a str,
+    url: Option<&'a str>,
+    requests: u64,
+    bandwidth: u64,
+}
+
+impl<'a> JsonAsset<'a> {
+    pub(crate) fn of(row: &'a DisplayRow) -> Self {
+        JsonAsset {
+            kind: row.req_type.name(),
+            id: &row.label,
+            ext: &row.ext,
+            url: row.open_url.as_deref(),
+            requests: row.request_count,
+            bandwidth: row.bandwidth_sum,
+        }
+    }
+}
+
+/// Writes rows as columns padded to their widest cell, under the given headings and with
+/// numeric columns flagged `true` aligned right. Without headings, every column but the
+/// first is aligned right.
+pub(crate) fn write_aligned(
+    out: &mut impl Write,
+    headings: &[(&str, bool)],
+    rows: Vec<Vec<String>>,
+) -> io::Result<()> {
+    let columns = rows.first().map_or(headings.len(), Vec::len);
+    let right = |column: usize| match headings.get(column) {
+        Some((_, right)) => *right,
+        None => column > 0,
+    };
+    let mut widths = vec![0; columns];
+    let heading_row: Vec<String> = headings.iter().map(|(name, _)| name.to_string()).collect();
+    let lines: Vec<&Vec<String>> = (!headings.is_empty())
+        .then_some(&heading_row)
+        .into_iter()
+        .chain(&rows)
+        .collect();
+    for line in &lines {
+        for (column, cell) in line.iter().enumerate() {
+            widths[column] = widths[column].max(cell.width());
+        }
+    }
+    for line in lines {
+        let cells: Vec<String> = line
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let padding = " ".repeat(widths[column] - cell.width());
+                if right(column) {
+                    format!("{padding}{cell}")
+                } else {
+                    format!("{cell}{padding}")
+                }
+            })
+            .collect();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Writes the first `limit` By Asset rows in their sorted order, with the TUI's columns and
+/// formatting, plus the column sorted by when the TUI doesn't show it in this view.
+pub fn write_top(mut out: impl Write, app: &App, limit: usize) -> io::Result<()> {
+    let mut columns: Vec<Column> = app
+        .columns()
+        .into_iter()
+        .filter(|column| *column != Column::Trend)
+        .collect();
+    let sorted = sort_column(app.sort_field);
+    if !columns.contains(&sorted) {
+        columns.push(sorted);
+    }
+    let headings: Vec<(&str, bool)> = columns
+        .iter()
+        .map(|column| match column {
+            Column::Id => (app.id_title(), false),
+            Column::Type | Column::Ext => (column.title(), false),
+            column => (column.title(), true),
+        })
+        .collect();
+    let rows = app
+        .items
+        .iter()
+        .take(limit)
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| match column {
+                    Column::Id => truncate_id(&row.label, MAX_ID_WIDTH),
+                    column => column_text(row, *column),
+                })
+                .collect()
+        })
+        .collect();
+    write_aligned(&mut out, &headings, rows)?;
+    out.flush()
+}
+
+fn sort_column(field: SortField) -> Column {
+    match field {
+        SortField::Path => Column::Id,
+        SortField::Ext => Column::Ext,
+        SortField::Requests => Column::Requests,
+        SortField::AvgRequestSize => Column::AvgSize,
+        SortField::Bandwidth => Column::Bandwidth,
+        SortField::Unoptimized => Column::Unoptimized,
+        SortField::Variants => Column::Variants,
+        SortField::CacheHit => Column::CacheHit,
+        SortField::Latency => Column::Latency,
+        SortField::BandwidthDelta => Column::BandwidthDelta,
+        SortField::Change => Column::Change,
+        SortField::RequestSize => Column::RequestSize,
+        SortField::ClientErrors => Column::ClientErrors,
+        SortField::ServerErrors => Column::ServerErrors,
+        SortField::ErrorRate => Column::ErrorRate,
+    }
+}
+
+/// Everything the report shows, gathered once for either format.
+struct Report<'a> {
+    scope: String,
+    total: Totals,
+    /// One row per request type, by bandwidth.
+    types: Vec<DisplayRow>,
+    /// Every asset, by bandwidth.
+    assets: Vec<&'a DisplayRow>,
+    /// Bandwidth per hour or day, labelled with the bucket's start.
+    timeline: Vec<(String, u64)>,
+    offenders: Offenders<'a>,
+    recommendations: Vec<String>,
+    /// What a GB of bandwidth costs, when set.
+    price_per_gb: Option<f64>,
+}
+
+/// Describes why a row is listed among the offenders.
+type Describe = fn(&DisplayRow) -> String;
+
+impl<'a> Report<'a> {
+    fn new(app: &'a App) -> Self {
+        let rows = &app.items;
+        let total = Totals::of(rows);
+        let mut assets: Vec<&DisplayRow> = rows.iter().collect();
+        assets.sort_by_key(|row| std::cmp::Reverse(row.bandwidth_sum));
+        let granularity = app.effective_granularity();
+        let timeline = app
+            .timeline_buckets(granularity)
+            .into_iter()
+            .map(|(start, bucket)| (granularity.format(start), bucket.bandwidth))
+            .collect();
+        let offenders = Offenders::of(rows);
+        Report {
+            scope: scope(app, total.requests),
+            price_per_gb: app.price_per_gb,
+            recommendations: recommendations(app, &total, &offenders),
+            types: type_rows(app),
+            total,
+            assets,
+            timeline,
+            offenders,
+        }
+    }
+
+    /// Label and value of each line of the totals table.
+    fn totals(&self) -> Vec<(&'static str, String)> {
+        let total = &self.total;
+        let mut lines = vec![
+            ("Requests", format_count(total.requests)),
+            ("Bandwidth", format_bytes(total.bandwidth)),
+        ];
+        if let Some(price) = self.price_per_gb {
+            lines.push(("Bandwidth cost", format_cost(total.bandwidth, price)));
+        }
+        lines.extend([
+            (
+                "Average response",
+                format_bytes(total.bandwidth.checked_div(total.requests).unwrap_or(0)),
+            ),
+            (
+                "Unoptimized images",
+                format!(
+                    "{} ({})",
+                    format_bytes(total.unoptimized),
+                    share(total.unoptimized, total.bandwidth)
+                ),
+            ),
+        ]);
+        if total.cache_lookups > 0 {
+            lines.push((
+                "Cache hit ratio",
+                share(total.cache_hits, total.cache_lookups),
+            ));
+        }
+        lines.push((
+            "Failed requests",
+            format!(
+                "{} 4xx, {} 5xx",
+                format_count(total.client_errors),
+                format_count(total.server_errors)
+            ),
+        ));
+        lines
+    }
+
+    /// Each kind of offender: its heading, its rows, and why a row is listed.
+    fn offender_lists(&self) -> [(&'static str, &[&'a DisplayRow], Describe); 4] {
+        let offenders = &self.offenders;
+        [
+            ("Unoptimized images", &offenders.unoptimized, |row| {
+                format!(
+                    "{} of {} served as JPEG/PNG without `auto=format`",
+                    format_bytes(row.unoptimized_bandwidth),
+                    format_bytes(row.bandwidth_sum)
+                )
+            }),
+            ("Most variants", &offenders.variants, |row| {
+                format!("{} variants", format_count(row.variant_count as u64))
+            }),
+            ("Largest images", &offenders.large, |row| {
+                format!("{} per request on average", format_bytes(row.avg_size()))
+            }),
+            ("Most failed requests", &offenders.failing, |row| {
+                format!(
+                    "{} 4xx and {} 5xx of {} requests",
+                    format_count(row.client_errors),
+                    format_count(row.server_errors),
+                    format_count(row.request_count)
+                )
+            }),
+        ]
+    }
+}
+
+fn write_markdown(mut out: impl Write, report: &Report) -> io::Result<()> {
+    writeln!(out, "# Sanity bandwidth report\n")?;
+    writeln!(out, "{}\n", report.scope)?;
+
+    writeln!(out, "## Totals\n")?;
+    writeln!(out, "| | |\n|---|--:|")?;
+    for (label, value) in report.totals() {
+        writeln!(out, "| {label} | {value} |")?;
+    }
+
+    writeln!(out, "\n## Bandwidth by type\n")?;
+    writeln!(
+        out,
+        "| Type | Requests | Bandwidth | Share |\n|---|--:|--:|--:|"
+    )?;
+    for row in &report.types {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            row.label,
+            format_count(row.request_count),
+            format_bytes(row.bandwidth_sum),
+            share(row.bandwidth_sum, report.total.bandwidth)
+        )?;
+    }
+
+    writeln!(out, "\n## Top {TOP_ASSETS} assets by bandwidth\n")?;
+    writeln!(
+        out,
+        "| # | Type | ID | Ext | Requests | Avg size | Bandwidth | Share |\n\
+         |--:|---|---|---|--:|--:|--:|--:|"
+    )?;
+    for (rank, row) in report.assets.iter().take(TOP_ASSETS).enumerate() {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            rank + 1,
+            type_label(row.req_type),
+            cell(&row.label),
+            row.ext,
+            format_count(row.request_count),
+            format_bytes(row.avg_size()),
+            format_bytes(row.bandwidth_sum),
+            share(row.bandwidth_sum, report.total.bandwidth)
+        )?;
+    }
+
+    writeln!(out, "\n## Biggest offenders\n")?;
+    if report.offenders.is_empty() {
+        writeln!(out, "Nothing stands out.\n")?;
+    }
+    for (title, rows, describe) in report.offender_lists() {
+        if rows.is_empty() {
+            continue;
+        }
+        writeln!(out, "### {title}\n")?;
+        for row in rows.iter().take(TOP_OFFENDERS) {
+            let id = truncate_id(&row.label, MAX_ID_WIDTH);
+            writeln!(out, "- `{id}{}`: {}", row.ext, describe(row))?;
+        }
+        if rows.len() > TOP_OFFENDERS {
+            let more = (rows.len() - TOP_OFFENDERS) as u64;
+            writeln!(out, "- and {} more", format_count(more))?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out, "## Recommendations\n")?;
+    if report.recommendations.is_empty() {
+        writeln!(out, "No changes suggested by these logs.")?;
+    }
+    for recommendation in &report.recommendations {
+        writeln!(out, "- {recommendation}")?;
+    }
+    out.flush()
+}
+
+fn write_html(mut out: impl Write, report: &Report) -> io::Result<()> {
+    writeln!(out, "<!doctype html>\n<html lang=\"en\">\n<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>Sanity bandwidth report</title>")?;
+    writeln!(out, "<style>{HTML_STYLE}</style>\n</head>\n<body>")?;
+    writeln!(out, "<h1>Sanity bandwidth report</h1>")?;
+    writeln!(out, "<p class=\"scope\">{}</p>", inline_html(&report.scope))?;
+
+    writeln!(out, "<h2>Totals</h2>\n<table class=\"totals\">")?;
+    for (label, value) in report.totals() {
+        writeln!(out, "<tr><th>{label}</th><td>{}</td></tr>", escape(&value))?;
+    }
+    writeln!(out, "</table>")?;
+
+    if report.timeline.len() > 1 {
+        writeln!(out, "<h2>Bandwidth over time</h2>")?;
+        write_timeline_chart(&mut out, &report.timeline)?;
+    }
+
+    writeln!(out, "<h2>Bandwidth by type</h2>")?;
+    write_type_chart(&mut out, &report.types)?;
+    writeln!(
+        out,
+        "<table class=\"sortable\">\n<thead><tr><th>Type</th><th class=\"num\">Requests</th>\
+         <th class=\"num\">Bandwidth</th><th class=\"num\">Share</th></tr></thead>\n<tbody>"
+    )?;
+    for row in &report.types {
+        writeln!(
+            out,
+            "<tr><td>{}</td>{}{}{}</tr>",
+            escape(&row.label),
+            number_cell(row.request_count, format_count(row.request_count)),
+            number_cell(row.bandwidth_sum, format_bytes(row.bandwidth_sum)),
+            number_cell(
+                row.bandwidth_sum,
+                share(row.bandwidth_sum, report.total.bandwidth)
+            ),
+        )?;
+    }
+    writeln!(out, "</tbody>\n</table>")?;
+
+    let shown = report.assets.len().min(MAX_HTML_ASSETS);
+    writeln!(out, "<h2>Assets by bandwidth</h2>")?;
+    if shown < report.assets.len() {
+        writeln!(
+            out,
+            "<p class=\"note\">The top {} of {}.</p>",
+            format_count(shown as u64),
+            format_count(report.assets.len() as u64)
+        )?;
+    }
+    writeln!(
+        out,
+        "<table class=\"sortable\">\n<thead><tr><th>Type</th><th>ID</th><th>Ext</th>\
+         <th class=\"num\">Requests</th><th class=\"num\">Avg size</th>\
+         <th class=\"num\">Bandwidth</th><th class=\"num\">Unoptimized</th>\
+         <th class=\"num\">Variants</th><th class=\"num\">4xx</th><th class=\"num\">5xx</th>\
+         </tr></thead>\n<tbody>"
+    )?;
+    for row in report.assets.iter().take(shown) {
+        let variants = row.variant_count as u64;
+        writeln!(
+            out,
+            "<tr><td>{}</td><td class=\"id\" title=\"{}\">{}</td><td>{}</td>{}{}{}{}{}{}{}</tr>",
+            type_label(row.req_type),
+            escape(&row.label),
+            escape(&truncate_id(&row.label, MAX_ID_WIDTH)),
+            escape(&row.ext),
+            number_cell(row.request_count, format_count(row.request_count)),
+            number_cell(row.avg_size(), format_bytes(row.avg_size())),
+            number_cell(row.bandwidth_sum, format_bytes(row.bandwidth_sum)),
+            number_cell(
+                row.unoptimized_bandwidth,
+                format_bytes(row.unoptimized_bandwidth)
+            ),
+            number_cell(variants, format_count(variants)),
+            number_cell(row.client_errors, format_count(row.client_errors)),
+            number_cell(row.server_errors, format_count(row.server_errors)),
+        )?;
+    }
+    writeln!(out, "</tbody>\n</table>")?;
+
+    writeln!(out, "<h2>Biggest offenders</h2>")?;
+    if report.offenders.is_empty() {
+        writeln!(out, "<p>Nothing stands out.</p>")?;
+    }
+    for (title, rows, describe) in report.offender_lists() {
+        if rows.is_empty() {
+            continue;
+        }
+        writeln!(out, "<h3>{title}</h3>\n<ul>")?;
+        for row in rows.iter().take(TOP_OFFENDERS) {
+            let id = truncate_id(&row.label, MAX_ID_WIDTH);
+            writeln!(
+                out,
+                "<li><code>{}{}</code>: {}</li>",
+                escape(&id),
+                escape(&row.ext),
+                inline_html(&describe(row))
+            )?;
+        }
+        if rows.len() > TOP_OFFENDERS {
+            let more = (rows.len() - TOP_OFFENDERS) as u64;
+            writeln!(out, "<li>and {} more</li>", format_count(more))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+
+    writeln!(out, "<h2>Recommendations</h2>")?;
+    if report.recommendations.is_empty() {
+        writeln!(out, "<p>No changes suggested by these logs.</p>")?;
+    } else {
+        writeln!(out, "<ul>")?;
+        for recommendation in &report.recommendations {
+            writeln!(out, "<li>{}</li>", inline_html(recommendation))?;
+        }
+        writeln!(out, "</ul>")?;
+    }
+    writeln!(out, "<script>{HTML_SCRIPT}</script>\n</body>\n</html>")?;
+    out.flush()
+}
+
+/// A column of bandwidth per bucket, labelled with the first and last bucket. Hovering a
+/// column shows its time and size.
+fn write_timeline_chart(out: &mut impl Write, timeline: &[(String, u64)]) -> io::Result<()> {
+    const WIDTH: f64 = 900.0;
+    const HEIGHT: f64 = 160.0;
+    let max = timeline
+        .iter()
+        .map(|(_, bytes)| *bytes)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let step = WIDTH / timeline.len() as f64;
+    writeln!(
+        out,
+        "<svg class=\"chart\" viewBox=\"0 0 {WIDTH} {}\" role=\"img\">",
+        HEIGHT + 20.0
+    )?;
+    for (idx, (label, bytes)) in timeline.iter().enumerate() {
+        let height = *bytes as f64 / max * HEIGHT;
+        writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{height:.1}\">\
+             <title>{}: {}</title></rect>",
+            idx as f64 * step,
+            HEIGHT - height,
+            (step - 1.0).max(0.5),
+            escape(label),
+            format_bytes(*bytes)
+        )?;
+    }
+    if let (Some((first, _)), Some((last, _))) = (timeline.first(), timeline.last()) {
+        let y = HEIGHT + 15.0;
+        writeln!(
+            out,
+            "<text x=\"0\" y=\"{y}\">{}</text>\
+             <text x=\"{WIDTH}\" y=\"{y}\" text-anchor=\"end\">{}</text>",
+            escape(first),
+            escape(last)
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// A bar per request type, scaled to the largest.
+fn write_type_chart(out: &mut impl Write, types: &[DisplayRow]) -> io::Result<()> {
+    const LABEL_WIDTH: f64 = 140.0;
+    const BAR_WIDTH: f64 = 640.0;
+    const ROW_HEIGHT: f64 = 24.0;
+    let max = types
+        .iter()
+        .map(|row| row.bandwidth_sum)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    writeln!(
+        out,
+        "<svg class=\"chart\" viewBox=\"0 0 900 {}\" role=\"img\">",
+        types.len() as f64 * ROW_HEIGHT
+    )?;
+    for (idx, row) in types.iter().enumerate() {
+        let y = idx as f64 * ROW_HEIGHT;
+        let width = row.bandwidth_sum as f64 / max * BAR_WIDTH;
+        writeln!(
+            out,
+            "<text x=\"0\" y=\"{:.1}\">{}</text>\
+             <rect x=\"{LABEL_WIDTH}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{:.1}\"></rect>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            y + 16.0,
+            escape(&row.label),
+            y + 4.0,
+            ROW_HEIGHT - 8.0,
+            LABEL_WIDTH + width + 6.0,
+            y + 16.0,
+            format_bytes(row.bandwidth_sum)
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// A right-aligned cell showing `text` that sorts by `value`.
+fn number_cell(value: u64, text: String) -> String {
+    format!(
+        "<td class=\"num\" data-value=\"{value}\">{}</td>",
+        escape(&text)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes text written for Markdown, turning its `code spans` into `<code>` elements.
+fn inline_html(text: &str) -> String {
+    text.split('`')
+        .enumerate()
+        .map(|(idx, part)| match idx % 2 {
+            0 => escape(part),
+            _ => format!("<code>{}</code>", escape(part)),
+        })
+        .collect()
+}
+
+const HTML_STYLE: &str = r#"
+body { font: 15px/1.5 system-ui, sans-serif; max-width: 1100px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }
+.scope, .note { color: #656d76; }
+table { border-collapse: collapse; margin: 1rem 0; }
+th, td { padding: 4px 10px; border-bottom: 1px solid #d0d7de; text-align: left; }
+.num { text-align: right; font-variant-numeric: tabular-nums; }
+.sortable th { cursor: pointer; user-select: none; }
+.sortable th[data-order="asc"]::after { content: " \2191"; }
+.sortable th[data-order="desc"]::after { content: " \2193"; }
+td.id { max-width: 28rem; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.totals th { font-weight: normal; color: #656d76; }
+.chart { width: 100%; height: auto; font-size: 12px; }
+.chart rect { fill: #f03e2f; }
+code { background: #f6f8fa; padding: 0 3px; border-radius: 3px; }
+"#;
+
+/// Sorts a table by the clicked heading, numbers by their `data-value`, largest first.
+const HTML_SCRIPT: &str = r#"
+for (const th of document.querySelectorAll(".sortable th")) {
+  th.addEventListener("click", () => {
+    const table = th.closest("table");
+    const descending = th.dataset.order !== "desc";
+    for (const other of table.querySelectorAll("th")) delete other.dataset.order;
+    th.dataset.order = descending ? "desc" : "asc";
+    const key = (row) => {
+      const cell = row.cells[th.cellIndex];
+      return cell.dataset.value !== undefined
+        ? Number(cell.dataset.value)
+        : cell.textContent.toLowerCase();
+    };
+    const body = table.tBodies[0];
+    const rows = [...body.rows].sort((a, b) => {
+      const [x, y] = [key(a), key(b)];
+      const order = x < y ? -1 : x > y ? 1 : 0;
+      return descending ? -order : order;
+    });
+    body.append(...rows);
+  });
+}
+"#;
+
+/// What the report covers: the inputs, their time span, and the filters applied.
+pub(crate) fn scope(app: &App, requests: u64) -> String {
+    let summary = app.summary;
+    let files = match summary.file_count {
+        1 => "1 file".to_string(),
+        count => format!("{count} files"),
+    };
+    let mut scope = format!("{files} · {} requests", format_count(requests));
+    if let Some(span) = summary.time_span {
+        scope.push_str(&format!(" · {}", format_time_span(span)));
+    }
+    let filters: Vec<String> = app
+        .active_filters()
+        .into_iter()
+        .map(|(_, label)| format!("`{label}`"))
+        .collect();
+    if !filters.is_empty() {
+        scope.push_str(&format!(" · filtered by {}", filters.join(", ")));
+    }
+    scope
+}
+
+/// The By Type headings for the requests the report covers, by bandwidth.
+fn type_rows(app: &App) -> Vec<DisplayRow> {
+    let filter = app.request_filter();
+    let stats: Vec<_> = aggregate_by(
+        &app.requests,
+        |request| filter.includes(request),
+        Request::path_key,
+    )
+    .into_values()
+    .collect();
+    let mut rows = build_type_rows(&stats, SortOrder::by(SortField::Bandwidth, true));
+    rows.retain(|row| row.is_group);
+    rows
+}
+
+#[derive(Debug, Default)]
+struct Totals {
+    requests: u64,
+    bandwidth: u64,
+    unoptimized: u64,
+    cache_lookups: u64,
+    cache_hits: u64,
+    client_errors: u64,
+    server_errors: u64,
+}
+
+impl Totals {
+    fn of(rows: &[DisplayRow]) -> Self {
+        let mut totals = Totals::default();
+        for row in rows {
+            totals.requests += row.request_count;
+            totals.bandwidth += row.bandwidth_sum;
+            totals.unoptimized += row.unoptimized_bandwidth;
+            totals.cache_lookups += row.cache_lookups;
+            totals.cache_hits += row.cache_hits;
+            totals.client_errors += row.client_errors;
+            totals.server_errors += row.server_errors;
+        }
+        totals
+    }
+}
+
+/// Every row standing out by each measure the recommendations are based on, worst first.
+struct Offenders<'a> {
+    unoptimized: Vec<&'a DisplayRow>,
+    variants: Vec<&'a DisplayRow>,
+    large: Vec<&'a DisplayRow>,
+    failing: Vec<&'a DisplayRow>,
+}
+
+impl<'a> Offenders<'a> {
+    fn of(rows: &'a [DisplayRow]) -> Self {
+        let worst = |keep: fn(&DisplayRow) -> bool, measure: fn(&DisplayRow) -> u64| {
+            let mut found: Vec<&DisplayRow> = rows.iter().filter(|row| keep(row)).collect();
+            found.sort_by_key(|row| std::cmp::Reverse(measure(row)));
+            found
+        };
+        Offenders {
+            unoptimized: worst(
+                |row| row.unoptimized_bandwidth > 0,
+                |row| row.unoptimized_bandwidth,
+            ),
+            variants: worst(
+                |row| row.variant_count > MAX_VARIANTS,
+                |row| row.variant_count as u64,
+            ),
+            large: worst(
+                |row| row.req_type == RequestType::Image && row.avg_size() > LARGE_IMAGE,
+                DisplayRow::avg_size,
+            ),
+            failing: worst(
+                |row| row.client_errors + row.server_errors > 0,
+                |row| row.client_errors + row.server_errors,
+            ),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.unoptimized.is_empty()
+            && self.variants.is_empty()
+            && self.large.is_empty()
+            && self.failing.is_empty()
+    }
+}
+
+fn recommendations(app: &App, total: &Totals, offenders: &Offenders) -> Vec<String> {
+    let mut recommendations = Vec::new();
+    if total.unoptimized > 0 {
+        recommendations.push(format!(
+            "Add `auto=format` to image URLs (`.auto('format')` with `@sanity/image-url`) so \
+             browsers get WebP or AVIF: {} ({}) of the bandwidth went to JPEG and PNG images \
+             served as is.",
+            format_bytes(total.unoptimized),
+            share(total.unoptimized, total.bandwidth)
+        ));
+    }
+    if !offenders.variants.is_empty() {
+        recommendations.push(format!(
+            "Round image widths to a fixed set of breakpoints: {} requested in more than \
+             {MAX_VARIANTS} variants, so each new width misses the CDN cache.",
+            count_of(offenders.variants.len(), "image is", "images are")
+        ));
+    }
+    if !offenders.large.is_empty() {
+        recommendations.push(format!(
+            "Request images at the size they're displayed with `w`/`h`: {} over {} per \
+             request.",
+            count_of(offenders.large.len(), "image averages", "images average"),
+            format_bytes(LARGE_IMAGE)
+        ));
+    }
+    let (uncached, queries) = uncached_queries(app);
+    if uncached > 0 && uncached * 10 >= queries {
+        recommendations.push(format!(
+            "Set `useCdn: true` where fresh data isn't needed: {} of {} GROQ queries went to \
+             the uncached `api.sanity.io` instead of `apicdn.sanity.io`.",
+            format_count(uncached),
+            format_count(queries)
+        ));
+    }
+    if total.cache_lookups > 0 && total.cache_hits * 2 < total.cache_lookups {
+        recommendations.push(format!(
+            "Look into the low cache hit ratio ({}); varying query strings on the same asset \
+             are a common cause.",
+            share(total.cache_hits, total.cache_lookups)
+        ));
+    }
+    if let Some(row) = offenders.failing.iter().max_by_key(|row| row.client_errors)
+        && row.client_errors > 0
+    {
+        recommendations.push(format!(
+            "Remove references to missing assets, starting with `{}{}`, which failed {} times.",
+            truncate_id(&row.label, MAX_ID_WIDTH),
+            row.ext,
+            format_count(row.client_errors)
+        ));
+    }
+    recommendations
+}
+
+/// GROQ queries sent to the uncached API host, and all GROQ queries, among the requests the
+/// report covers.
+fn uncached_queries(app: &App) -> (u64, u64) {
+    let filter = app.request_filter();
+    let mut uncached = 0;
+    let mut queries = 0;
+    for request in app
+        .requests
+        .iter()
+        .filter(|request| filter.includes(request))
+    {
+        if detect_request_type(request.path()) != RequestType::Query {
+            continue;
+        }
+        queries += 1;
+        if request
+            .url
+            .host_str()
+            .is_some_and(|host| host.ends_with(".api.sanity.io"))
+        {
+            uncached += 1;
+        }
+    }
+    (uncached, queries)
+}
+
+fn count_of(count: usize, one: &str, many: &str) -> String {
+    if count == 1 {
+        format!("1 {one}")
+    } else {
+        format!("{count} {many}")
+    }
+}
+
+pub(crate) fn share(part: u64, whole: u64) -> String {
+    if whole == 0 {
+        return "-".to_string();
+    }
+    format_percent(part as f64 / whole as f64)
+}
+
+/// Escapes pipes so an ID or query can't break the table, and shortens long ones.
+fn cell(text: &str) -> String {
+    truncate_id(text, MAX_ID_WIDTH).replace('|', "\\|")
+}
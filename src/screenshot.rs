@@ -0,0 +1,110 @@
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+/// Modifiers and the SGR parameter that turns each on.
+const MODIFIERS: [(Modifier, u8); 9] = [
+    (Modifier::BOLD, 1),
+    (Modifier::DIM, 2),
+    (Modifier::ITALIC, 3),
+    (Modifier::UNDERLINED, 4),
+    (Modifier::SLOW_BLINK, 5),
+    (Modifier::RAPID_BLINK, 6),
+    (Modifier::REVERSED, 7),
+    (Modifier::HIDDEN, 8),
+    (Modifier::CROSSED_OUT, 9),
+];
+
+/// Writes a rendered frame as plain text, one line per row with trailing spaces removed.
+pub fn write_text(mut out: impl Write, buffer: &Buffer) -> io::Result<()> {
+    for row in rows(buffer) {
+        let line: String = row.iter().map(|cell| cell.symbol()).collect();
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    out.flush()
+}
+
+/// Writes a rendered frame with its colors and text styles as ANSI escape sequences, for
+/// viewing with `cat` or `less -R`. Each line ends with the style reset.
+pub fn write_ansi(mut out: impl Write, buffer: &Buffer) -> io::Result<()> {
+    for row in rows(buffer) {
+        let mut current = None;
+        for cell in row {
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if current != Some(style) {
+                write!(out, "\x1b[{}m", sgr(cell))?;
+                current = Some(style);
+            }
+            write!(out, "{}", cell.symbol())?;
+        }
+        writeln!(out, "\x1b[0m")?;
+    }
+    out.flush()
+}
+
+/// The cells of each row that are drawn, leaving out those covered by a wide character
+/// before them.
+fn rows(buffer: &Buffer) -> impl Iterator<Item = Vec<&Cell>> {
+    let area = buffer.area;
+    (area.top()..area.bottom()).map(move |y| {
+        let mut cells = Vec::new();
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            let cell = buffer.get(x, y);
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+            covered = cell.symbol().width().saturating_sub(1);
+            cells.push(cell);
+        }
+        cells
+    })
+}
+
+/// SGR parameters setting a cell's whole style, starting from a reset.
+fn sgr(cell: &Cell) -> String {
+    let mut parameters = vec!["0".to_string()];
+    parameters.extend(
+        MODIFIERS
+            .iter()
+            .filter(|(modifier, _)| cell.modifier.contains(*modifier))
+            .map(|(_, parameter)| parameter.to_string()),
+    );
+    parameters.extend(color(cell.fg, 30));
+    parameters.extend(color(cell.bg, 40));
+    parameters.join(";")
+}
+
+/// The SGR parameter for a color, with `base` 30 for the foreground or 40 for the background.
+fn color(color: Color, base: u8) -> Option<String> {
+    let basic = |offset: u8| Some((base + offset).to_string());
+    let bright = |offset: u8| Some((base + 60 + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => basic(0),
+        Color::Red => basic(1),
+        Color::Green => basic(2),
+        Color::Yellow => basic(3),
+        Color::Blue => basic(4),
+        Color::Magenta => basic(5),
+        Color::Cyan => basic(6),
+        Color::Gray => basic(7),
+        Color::DarkGray => bright(0),
+        Color::LightRed => bright(1),
+        Color::LightGreen => bright(2),
+        Color::LightYellow => bright(3),
+        Color::LightBlue => bright(4),
+        Color::LightMagenta => bright(5),
+        Color::LightCyan => bright(6),
+        Color::White => bright(7),
+        Color::Rgb(red, green, blue) => Some(format!("{};2;{red};{green};{blue}", base + 8)),
+        Color::Indexed(index) => Some(format!("{};5;{index}", base + 8)),
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use flate2::bufread::MultiGzDecoder;
+use memmap2::Mmap;
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Experimental: the request log endpoint below isn't part of Sanity's documented HTTP API, so
+/// its path and parameters may change or not exist for every plan.
+const SANITY_API_URL: &str = "https://api.sanity.io/v2021-06-07";
+
+#[derive(Debug, Clone)]
+pub enum Input {
+    Path(String),
+    SanityApi {
+        project: String,
+        dataset: Option<String>,
+        token: String,
+    },
+}
+
+impl Input {
+    /// Opens the input for reading, adding the raw (still compressed) byte count to `bytes_read`.
+    pub fn open(&self, bytes_read: Arc<AtomicU64>) -> Result<Box<dyn BufRead>> {
+        let source = match self {
+            Input::Path(path) => open_path(path)?,
+            Input::SanityApi {
+                project,
+                dataset,
+                token,
+            } => open_sanity_logs(project, dataset.as_deref(), token)?,
+        };
+        let counted = CountingReader {
+            inner: source,
+            bytes_read,
+        };
+        decompress(BufReader::new(Box::new(counted)))
+    }
+
+    /// Memory-maps local, uncompressed files so they can be parsed without copying.
+    pub fn map(&self) -> Result<Option<Mmap>> {
+        let Input::Path(path) = self else {
+            return Ok(None);
+        };
+        if is_remote(path) {
+            return Ok(None);
+        }
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only and only lives for the duration of the load. Like any
+        // mmap, truncating the file while it is being parsed is not supported.
+        let mapped = unsafe { Mmap::map(&file)? };
+        if mapped.starts_with(&GZIP_MAGIC) || mapped.starts_with(&ZSTD_MAGIC) {
+            return Ok(None);
+        }
+        Ok(Some(mapped))
+    }
+
+    /// Size of the raw input in bytes, when it is known up front.
+    pub fn size(&self) -> Option<u64> {
+        self.metadata().map(|m| m.len())
+    }
+
+    /// Last modification time of local files, used to detect changes on disk.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.metadata().and_then(|m| m.modified().ok())
+    }
+
+    fn metadata(&self) -> Option<fs::Metadata> {
+        match self {
+            Input::Path(path) if !is_remote(path) => fs::metadata(path).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Path(path) => f.write_str(path),
+            Input::SanityApi { project, .. } => write!(f, "request logs for project {project}"),
+        }
+    }
+}
+
+pub fn is_remote(path: &str) -> bool {
+    ["s3://", "gs://", "https://", "http://"]
+        .iter()
+        .any(|scheme| path.starts_with(scheme))
+}
+
+fn open_path(path: &str) -> Result<Box<dyn Read>> {
+    let source: Box<dyn Read> = if path.starts_with("s3://") {
+        Box::new(CommandReader::spawn("aws", &["s3", "cp", path, "-"])?)
+    } else if path.starts_with("gs://") {
+        Box::new(CommandReader::spawn("gcloud", &["storage", "cat", path])?)
+    } else if path.starts_with("https://") || path.starts_with("http://") {
+        let response = ureq::get(path).call()?;
+        Box::new(response.into_body().into_reader())
+    } else {
+        Box::new(File::open(path)?)
+    };
+    Ok(source)
+}
+
+/// Downloads the project's request logs in one response. The endpoint is undocumented and
+/// unconfirmed; it takes no time window and isn't paginated, so `--since` and `--until` are
+/// applied to the records as they are parsed, and anything the response leaves out is missed.
+fn open_sanity_logs(project: &str, dataset: Option<&str>, token: &str) -> Result<Box<dyn Read>> {
+    let url = format!("{SANITY_API_URL}/projects/{project}/request-logs");
+    let mut request = ureq::get(&url).header("Authorization", format!("Bearer {token}"));
+    if let Some(dataset) = dataset {
+        request = request.query("dataset", dataset);
+    }
+    let response = request.call()?;
+    Ok(Box::new(response.into_body().into_reader()))
+}
+
+fn decompress(mut reader: BufReader<Box<dyn Read>>) -> Result<Box<dyn BufRead>> {
+    let header = reader.fill_buf()?;
+    if header.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if header.starts_with(&ZSTD_MAGIC) {
+        let decoder = zstd::Decoder::with_buffer(reader).context("invalid zstd stream")?;
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Streams the stdout of an external command, surfacing a non-zero exit as a read error.
+struct CommandReader {
+    program: &'static str,
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl CommandReader {
+    fn spawn(program: &'static str, args: &[&str]) -> Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run `{program}`; is it installed?"))?;
+        let stdout = child.stdout.take().context("missing command output")?;
+        Ok(Self {
+            program,
+            child,
+            stdout,
+        })
+    }
+}
+
+impl Read for CommandReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stdout.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            let status = self.child.wait()?;
+            if !status.success() {
+                return Err(io::Error::other(format!(
+                    "`{}` exited with {status}",
+                    self.program
+                )));
+            }
+        }
+        Ok(read)
+    }
+}
+
+struct CountingReader {
+    inner: Box<dyn Read>,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    Column, SortField, StartView,
+    config::{self, PresetType},
+    source::{self, Input},
+};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::PathBuf,
+};
+
+/// What the TUI remembers between runs, in `~/.local/state/sanity-log-explorer/state.toml`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Columns hidden from the columns menu, for logs that haven't been opened before.
+    pub hidden_columns: BTreeSet<Column>,
+    /// Where the TUI was left for each set of logs, keyed by [`session_key`].
+    pub sessions: BTreeMap<String, Session>,
+}
+
+impl State {
+    /// Reads the saved state. A missing or unreadable file, such as one written by an older
+    /// version, starts over from the defaults rather than stopping the TUI from opening.
+    pub fn load() -> Self {
+        state_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = state_path().context("no home directory to save state in")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let text = toml::to_string(self).context("failed to encode state")?;
+        fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// The view, sort, filters, and columns the TUI was left with for one set of logs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    #[serde(
+        serialize_with = "value_enum",
+        deserialize_with = "config::value_enum",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub view: Option<StartView>,
+    #[serde(
+        serialize_with = "value_enum",
+        deserialize_with = "config::value_enum",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sort: Option<SortField>,
+    pub descending: bool,
+    /// The tiebreaker picked with `,`.
+    #[serde(
+        serialize_with = "value_enum",
+        deserialize_with = "config::value_enum",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub then_sort: Option<SortField>,
+    pub then_descending: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_columns: Option<BTreeSet<Column>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<SavedFilters>,
+}
+
+/// Filters restored together, so those given on the command line can replace them all.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedFilters {
+    /// Name of the filter preset applied, which the others were changed from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referer: Option<String>,
+    pub hidden_types: Vec<PresetType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_bandwidth: Option<u64>,
+    /// Text of the `/` filter bar.
+    pub text: String,
+}
+
+/// Names a set of logs by their full paths, or URLs and Sanity projects for those read over
+/// the network. None when a file can't be found, as the load will fail anyway.
+pub fn session_key(inputs: &[Input]) -> Option<String> {
+    let names = inputs
+        .iter()
+        .map(|input| match input {
+            Input::Path(path) if source::is_remote(path) => Some(path.clone()),
+            Input::Path(path) => fs::canonicalize(path)
+                .ok()
+                .map(|path| path.display().to_string()),
+            Input::SanityApi {
+                project, dataset, ..
+            } => Some(format!(
+                "sanity:{project}/{}",
+                dataset.as_deref().unwrap_or("*")
+            )),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!names.is_empty()).then(|| names.join(" + "))
+}
+
+/// Writes a value by its command-line name, as `config::value_enum` reads it.
+fn value_enum<S: Serializer, T: ValueEnum>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value
+        .as_ref()
+        .and_then(ValueEnum::to_possible_value)
+        .map(|value| value.get_name().to_string())
+        .serialize(serializer)
+}
+
+/// `$XDG_STATE_HOME/sanity-log-explorer/state.toml`, falling back to `~/.local/state`.
+fn state_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })?;
+    Some(base.join("sanity-log-explorer").join("state.toml"))
+}
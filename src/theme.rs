@@ -0,0 +1,168 @@
+use crate::RequestType;
+use ratatui::style::{Color, Modifier, Style};
+use std::sync::OnceLock;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Color schemes for the TUI, chosen with `--theme` or `theme` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ThemeName {
+    /// For terminals with a dark background
+    #[default]
+    Dark,
+    /// For terminals with a light background
+    Light,
+    /// Bright colors and bold text, for low-contrast displays
+    HighContrast,
+    /// No colors, only bold, dim, and other text styles
+    Monochrome,
+}
+
+/// Every style the TUI draws with, so a theme can change them all at once.
+#[derive(Debug)]
+pub struct Theme {
+    /// Dividers, hints, placeholders, and other secondary text.
+    pub dim: Style,
+    /// Keys, prompts, charts, and file locations.
+    pub accent: Style,
+    /// Warnings, such as unoptimized bandwidth or skipped lines.
+    pub warning: Style,
+    /// Errors, and growth against a baseline.
+    pub error: Style,
+    /// Confirmations, and savings against a baseline.
+    pub good: Style,
+    /// Names, such as filter presets and query parameters.
+    pub name: Style,
+    /// Request types, in `RequestType::ALL` order.
+    types: [Style; 10],
+    /// Heatmap cells from no traffic to the busiest hour, as the character each is filled
+    /// with and its style.
+    pub heat: [(char, Style); 7],
+}
+
+/// Uses the named theme for the rest of the run. Only the first call has any effect.
+pub fn set(name: ThemeName) {
+    let _ = THEME.set(Theme::named(name));
+}
+
+/// The theme in use, dark unless another was set.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::named(ThemeName::Dark))
+}
+
+impl Theme {
+    fn named(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::HighContrast => Theme::high_contrast(),
+            ThemeName::Monochrome => Theme::monochrome(),
+        }
+    }
+
+    pub fn request_type(&self, kind: RequestType) -> Style {
+        let index = RequestType::ALL
+            .iter()
+            .position(|other| *other == kind)
+            .unwrap_or(0);
+        self.types[index]
+    }
+
+    fn dark() -> Self {
+        Theme {
+            dim: fg(Color::DarkGray),
+            accent: fg(Color::Cyan),
+            warning: fg(Color::Yellow),
+            error: fg(Color::Red),
+            good: fg(Color::Green),
+            name: fg(Color::Magenta),
+            types: [
+                Color::Green,
+                Color::Blue,
+                Color::Yellow,
+                Color::Magenta,
+                Color::Cyan,
+                Color::LightBlue,
+                Color::LightMagenta,
+                Color::LightYellow,
+                Color::LightGreen,
+                Color::Gray,
+            ]
+            .map(fg),
+            heat: [235, 22, 28, 64, 136, 166, 160].map(|index| (' ', bg(Color::Indexed(index)))),
+        }
+    }
+
+    /// Darker shades from the 256-color palette, as yellow and dark gray wash out on white.
+    fn light() -> Self {
+        Theme {
+            dim: fg(Color::Indexed(243)),
+            accent: fg(Color::Indexed(25)),
+            warning: fg(Color::Indexed(130)),
+            error: fg(Color::Indexed(124)),
+            good: fg(Color::Indexed(28)),
+            name: fg(Color::Indexed(90)),
+            types: [28, 25, 130, 90, 30, 61, 127, 94, 64, 240]
+                .map(|index| fg(Color::Indexed(index))),
+            heat: [254, 194, 150, 107, 178, 166, 160].map(|index| (' ', bg(Color::Indexed(index)))),
+        }
+    }
+
+    fn high_contrast() -> Self {
+        let bold = |color| fg(color).add_modifier(Modifier::BOLD);
+        Theme {
+            dim: fg(Color::Gray),
+            accent: bold(Color::LightCyan),
+            warning: bold(Color::LightYellow),
+            error: bold(Color::LightRed),
+            good: bold(Color::LightGreen),
+            name: bold(Color::LightMagenta),
+            types: [
+                Color::LightGreen,
+                Color::LightBlue,
+                Color::LightYellow,
+                Color::LightMagenta,
+                Color::LightCyan,
+                Color::White,
+                Color::LightMagenta,
+                Color::LightYellow,
+                Color::LightGreen,
+                Color::Gray,
+            ]
+            .map(bold),
+            heat: [
+                Color::Black,
+                Color::Blue,
+                Color::Cyan,
+                Color::Green,
+                Color::Yellow,
+                Color::LightRed,
+                Color::Red,
+            ]
+            .map(|color| (' ', bg(color))),
+        }
+    }
+
+    /// Text styles only, with the heatmap drawn in shades of a block character.
+    fn monochrome() -> Self {
+        let style = |modifier| Style::default().add_modifier(modifier);
+        Theme {
+            dim: style(Modifier::DIM),
+            accent: style(Modifier::BOLD),
+            warning: style(Modifier::BOLD),
+            error: style(Modifier::BOLD | Modifier::UNDERLINED),
+            good: Style::default(),
+            name: style(Modifier::ITALIC),
+            types: [Style::default(); 10],
+            heat: [' ', '·', ':', '░', '▒', '▓', '█'].map(|fill| (fill, Style::default())),
+        }
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn bg(color: Color) -> Style {
+    Style::default().bg(color)
+}
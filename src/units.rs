@@ -0,0 +1,114 @@
+use std::{
+    env,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+static DECIMAL: AtomicBool = AtomicBool::new(false);
+static EXACT: AtomicBool = AtomicBool::new(false);
+static SEPARATORS: OnceLock<Separators> = OnceLock::new();
+
+/// How sizes are shown and read, chosen with `units` in the config file or `u` in the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ByteUnits {
+    /// Powers of 1024, written KiB, MiB, GiB
+    #[default]
+    Binary,
+    /// Powers of 1000, written KB, MB, GB, as on Sanity's invoices
+    Decimal,
+}
+
+impl ByteUnits {
+    /// Bytes in each step up from one unit to the next.
+    pub fn base(self) -> f64 {
+        match self {
+            ByteUnits::Binary => 1024.0,
+            ByteUnits::Decimal => 1000.0,
+        }
+    }
+
+    /// Names of bytes and each unit up from them.
+    pub fn names(self) -> [&'static str; 5] {
+        match self {
+            ByteUnits::Binary => ["B", "KiB", "MiB", "GiB", "TiB"],
+            ByteUnits::Decimal => ["B", "KB", "MB", "GB", "TB"],
+        }
+    }
+}
+
+/// Uses `units` for sizes from now on, in every thread.
+pub fn set(units: ByteUnits) {
+    DECIMAL.store(units == ByteUnits::Decimal, Ordering::Relaxed);
+}
+
+/// The units sizes are shown in, binary unless others were set.
+pub fn units() -> ByteUnits {
+    if DECIMAL.load(Ordering::Relaxed) {
+        ByteUnits::Decimal
+    } else {
+        ByteUnits::Binary
+    }
+}
+
+/// Shows counts and sizes in full, such as `1,234,567` and `2,662,144 B`, rather than
+/// abbreviated as `1.2M` and `2.54 MiB`.
+pub fn set_exact(exact: bool) {
+    EXACT.store(exact, Ordering::Relaxed);
+}
+
+pub fn exact() -> bool {
+    EXACT.load(Ordering::Relaxed)
+}
+
+/// Characters written between groups of three digits and before the fraction of a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separators {
+    pub thousands: char,
+    pub decimal: char,
+}
+
+impl Separators {
+    /// The separators of the locale in `LC_ALL`, `LC_NUMERIC`, or `LANG`, taking the first
+    /// that's set as the C library does. Unknown locales, `C`, and `POSIX` use `1,234.5`.
+    fn from_locale() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let locale = locale.split(['.', '@']).next().unwrap_or_default();
+        let (language, region) = locale.split_once('_').unwrap_or((locale, ""));
+        let (thousands, decimal) = match (language, region) {
+            ("de" | "fr" | "it", "CH") => ('\'', '.'),
+            (
+                "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl" | "sr"
+                | "tr",
+                _,
+            ) => ('.', ','),
+            (
+                "bg" | "cs" | "et" | "fi" | "fr" | "hu" | "lt" | "lv" | "nb" | "nn" | "no" | "pl"
+                | "ru" | "sk" | "sv" | "uk",
+                _,
+            ) => ('\u{a0}', ','),
+            _ => (',', '.'),
+        };
+        Separators { thousands, decimal }
+    }
+}
+
+/// Uses these separators for numbers from now on, in place of those of the locale. Only the
+/// first call has an effect.
+pub fn set_separators(thousands: Option<char>, decimal: Option<char>) {
+    let locale = Separators::from_locale();
+    let _ = SEPARATORS.set(Separators {
+        thousands: thousands.unwrap_or(locale.thousands),
+        decimal: decimal.unwrap_or(locale.decimal),
+    });
+}
+
+/// The separators set from the config file, or else those of the locale.
+pub fn separators() -> Separators {
+    *SEPARATORS.get_or_init(Separators::from_locale)
+}